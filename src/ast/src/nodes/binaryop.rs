@@ -10,8 +10,13 @@ use kaleidoscope_codegen::{
     IRRepresentableExpression
 };
 
-use super::Operator;
-use crate::prelude::*;
+use super::{
+    binary_operator_function_name,
+    funccall::{build_call, has_function},
+    Operator,
+    VariableExpressionNode
+};
+use crate::{node::reify_expr_node_ref, prelude::*};
 
 
 /// An AST representing an operator with 2 expressions by its side.
@@ -59,6 +64,50 @@ impl BinaryOperatorNode {
     pub fn get_second(&self) -> &Box<dyn ExprNode> {
         &self.second
     }
+
+    /// Generate the IR for `&&` or `||`, where the second argument is only
+    /// evaluated if the first argument does not decide the result.
+    fn represent_logical_expression<'ctx>(
+        &self,
+        left: BasicValueEnum<'ctx>,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<BasicValueEnum<'ctx>> {
+        let left = NumValue::new(left.into_struct_value(), code_gen.clone())?;
+        let right = || {
+            let right = self.second.represent_expression(code_gen)?;
+            log::trace!("Representation for right value generated");
+            NumValue::new(right.into_struct_value(), code_gen.clone())
+        };
+        let result = match *self.operator {
+            Operator::LogicalAnd => left.logical_and(right)?,
+            _ => left.logical_or(right)?
+        };
+        log::trace!("IR generation done");
+        Ok(BasicValueEnum::StructValue(result.into()))
+    }
+
+    /// Generate the IR for `=`, which stores the second argument in the
+    /// variable named by the first argument. The assignment evaluates to
+    /// the stored value.
+    fn represent_assignment<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<BasicValueEnum<'ctx>> {
+        let variable =
+            reify_expr_node_ref::<VariableExpressionNode>(&self.first).ok_or_else(|| {
+                cgerror::Error::new(
+                    format!("Cannot assign to '{}', which is not a variable", self.first),
+                    cgerror::ErrorKind::NotAssignableError,
+                    None
+                )
+            })?;
+        let value = self.second.represent_expression(code_gen)?;
+        log::trace!("Representation for assigned value generated");
+        let slot = code_gen.get_variable(variable.get_identifier().get_value())?;
+        code_gen.get_inner().get_builder().build_store(slot, value);
+        log::trace!("IR generation done");
+        Ok(value)
+    }
 }
 
 impl Clone for BinaryOperatorNode {
@@ -79,27 +128,48 @@ impl IRRepresentableExpression for BinaryOperatorNode {
         log::trace!(
             "Entering <BinaryOperatorNode as IRRepresentableExpression>::represent_expression"
         );
+        if *self.operator == Operator::Assign {
+            return self.represent_assignment(code_gen);
+        }
+        let name = binary_operator_function_name(*self.operator);
+        let user_defined = !self.operator.is_builtin() || has_function(code_gen, &name);
+        let left = self.first.represent_expression(code_gen)?;
+        log::trace!("Representation for left value generated");
+        if let (false, Operator::LogicalAnd | Operator::LogicalOr) = (user_defined, *self.operator)
+        {
+            return self.represent_logical_expression(left, code_gen);
+        }
+        let right = self.second.represent_expression(code_gen)?;
+        log::trace!("Representation for right value generated");
+        if user_defined {
+            log::trace!("Calling user-defined binary operator {}", self.operator);
+            return build_call(code_gen, &name, &[left, right]);
+        }
         let left = NumValue::new(
-            self.first
-                .represent_expression(code_gen)?
-                .as_basic_value_enum()
-                .into_struct_value(),
+            left.as_basic_value_enum().into_struct_value(),
             code_gen.clone()
         )?;
-        log::trace!("Representation for left value generated");
         let right = NumValue::new(
-            self.second
-                .represent_expression(code_gen)?
-                .as_basic_value_enum()
-                .into_struct_value(),
+            right.as_basic_value_enum().into_struct_value(),
             code_gen.clone()
         )?;
-        log::trace!("Representation for right value generated");
         let result: StructValue<'ctx> = match *self.operator {
             Operator::Plus => (&left + &right).into(),
             Operator::Minus => (&left - &right).into(),
             Operator::Multiply => (&left * &right).into(),
             Operator::Divide => (&left / &right).into(),
+            Operator::Power => left.pow(&right)?.into(),
+            Operator::Modulo => (&left % &right).into(),
+            Operator::LessThan => left.less_than(&right)?.into(),
+            Operator::LessThanEqual => left.less_than_equal(&right)?.into(),
+            Operator::Equals => left.equals(&right)?.into(),
+            Operator::NotEquals => left.not_equals(&right)?.into(),
+            Operator::GreaterThanEqual => left.greater_than_equal(&right)?.into(),
+            Operator::GreaterThan => left.greater_than(&right)?.into(),
+            Operator::BitwiseAnd => left.bitwise_and(&right)?.into(),
+            Operator::BitwiseOr => left.bitwise_or(&right)?.into(),
+            Operator::ShiftLeft => left.shift_left(&right)?.into(),
+            Operator::ShiftRight => left.shift_right(&right)?.into(),
             _ =>
                 return Err(cgerror::Error::new(
                     format!("Unknown binary operator: {}", self.operator),
@@ -0,0 +1,98 @@
+//! A module defining a [`BlockNode`].
+
+use std::fmt;
+
+use inkwell::values::BasicValueEnum;
+use kaleidoscope_codegen::{error as cgerror, CodeGen, IRRepresentableExpression};
+
+use crate::prelude::*;
+
+
+/// An AST representing a list of expressions in curly brackets, separated by
+/// semicolons.
+///
+/// # Example
+///
+/// ```text
+/// {
+///     putchard(72);
+///     putchard(105);
+///     0
+/// }
+/// ```
+///
+/// The expressions are evaluated in order, and the block evaluates to the
+/// value of the last one. An empty block evaluates to `0`.
+#[derive(Debug)]
+pub struct BlockNode {
+    expressions: Vec<Box<dyn ExprNode>>
+}
+
+impl BlockNode {
+    /// Create a new [`BlockNode`].
+    pub fn new(expressions: Vec<Box<dyn ExprNode>>) -> Self {
+        Self { expressions }
+    }
+
+    /// Get the expressions in the block, in order.
+    pub fn get_expressions(&self) -> &[Box<dyn ExprNode>] {
+        &self.expressions[..]
+    }
+}
+
+impl fmt::Display for BlockNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for (index, expression) in self.expressions.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", expression)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl Clone for BlockNode {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.expressions
+                .iter()
+                .map(|expression| expression.expr_node_clone())
+                .collect()
+        )
+    }
+}
+
+impl Node for BlockNode {
+    fn node_id_of_val(&self) -> NodeId {
+        Self::node_id()
+    }
+
+    fn node_clone(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+}
+
+impl IRRepresentableExpression for BlockNode {
+    fn represent_expression<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<BasicValueEnum<'ctx>> {
+        log::trace!("Entering <BlockNode as IRRepresentableExpression>::represent_expression");
+        let mut value = BasicValueEnum::StructValue(code_gen.make_num_from_i128(0));
+        for expression in self.expressions.iter() {
+            value = expression.represent_expression(code_gen)?;
+        }
+        log::trace!("IR generation done");
+        Ok(value)
+    }
+}
+
+impl NodeType for BlockNode {}
+
+impl ExprNode for BlockNode {
+    fn expr_node_clone(&self) -> Box<dyn ExprNode> {
+        Box::new(self.clone())
+    }
+}
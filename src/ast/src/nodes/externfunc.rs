@@ -26,6 +26,12 @@ impl ExternFunctionNode {
     pub fn get_prototype(&self) -> &FunctionPrototypeNode {
         &*self.prototype
     }
+
+    /// Get the documentation of the function. See
+    /// [`FunctionPrototypeNode::get_documentation`].
+    pub fn get_documentation(&self) -> Option<&str> {
+        self.prototype.get_documentation()
+    }
 }
 
 impl Clone for ExternFunctionNode {
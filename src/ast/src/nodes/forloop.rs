@@ -0,0 +1,238 @@
+//! A module defining a [`ForLoopNode`].
+
+use std::fmt;
+
+use inkwell::values::{BasicValueEnum, PointerValue, StructValue};
+use kaleidoscope_codegen::{
+    builtins::NumValue,
+    error as cgerror,
+    scope::NamedValue,
+    CodeGen,
+    IRRepresentableExpression,
+    LoopContext
+};
+
+use super::IdentifierNode;
+use crate::prelude::*;
+
+
+/// An AST representing a loop over a range of numbers.
+///
+/// # Example
+///
+/// ```text
+/// for i = 1, i < n, 2 in
+///     putchard(i)
+/// ```
+///
+/// The loop variable (`i`) starts at the first expression, and the body is
+/// run for as long as the condition (`i < n`) is true. After each iteration,
+/// the step (`2`) is added to the loop variable. The step can be left out,
+/// in which case it is `1`. The loop variable can only be used inside the
+/// loop, and the loop itself always evaluates to `0`. Inside the body,
+/// `continue` skips to the step and `break` leaves the loop.
+#[derive(Debug)]
+pub struct ForLoopNode {
+    variable:  Box<IdentifierNode>,
+    start:     Box<dyn ExprNode>,
+    condition: Box<dyn ExprNode>,
+    step:      Option<Box<dyn ExprNode>>,
+    body:      Box<dyn ExprNode>
+}
+
+impl ForLoopNode {
+    /// Create a new [`ForLoopNode`].
+    pub fn new(
+        variable: Box<IdentifierNode>,
+        start: Box<dyn ExprNode>,
+        condition: Box<dyn ExprNode>,
+        step: Option<Box<dyn ExprNode>>,
+        body: Box<dyn ExprNode>
+    ) -> Self {
+        Self {
+            variable,
+            start,
+            condition,
+            step,
+            body
+        }
+    }
+
+    /// Get the loop variable.
+    pub fn get_variable(&self) -> &IdentifierNode {
+        &self.variable
+    }
+
+    /// Get the starting value of the loop variable.
+    pub fn get_start(&self) -> &dyn ExprNode {
+        &*self.start
+    }
+
+    /// Get the condition that must be true for the loop to continue.
+    pub fn get_condition(&self) -> &dyn ExprNode {
+        &*self.condition
+    }
+
+    /// Get the step added to the loop variable after each iteration, if
+    /// there is one.
+    pub fn get_step(&self) -> Option<&dyn ExprNode> {
+        self.step.as_deref()
+    }
+
+    /// Get the body of the loop.
+    pub fn get_body(&self) -> &dyn ExprNode {
+        &*self.body
+    }
+
+    /// Generate the condition, body and step of the loop, where `variable`
+    /// is the stack slot of the loop variable.
+    fn represent_loop<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>,
+        variable: PointerValue<'ctx>
+    ) -> cgerror::Result<()> {
+        let header_block = code_gen.append_block("loop_header")?;
+        let body_block = code_gen.append_block("loop_body")?;
+        let step_block = code_gen.append_block("loop_step")?;
+        let exit_block = code_gen.append_block("loop_exit")?;
+        code_gen
+            .get_inner()
+            .get_builder()
+            .build_unconditional_branch(header_block);
+
+        code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(header_block);
+        let condition = NumValue::new(
+            self.condition
+                .represent_expression(code_gen)?
+                .into_struct_value(),
+            code_gen.clone()
+        )?
+        .is_truthy()?;
+        code_gen
+            .get_inner()
+            .get_builder()
+            .build_conditional_branch(condition, body_block, exit_block);
+
+        code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(body_block);
+        // `continue` skips to the step, so the loop variable is still
+        // updated.
+        code_gen.enter_loop(LoopContext::new(step_block, exit_block));
+        let body = self.body.represent_expression(code_gen);
+        code_gen.exit_loop();
+        body?;
+        log::trace!("Representation for loop body generated");
+        code_gen
+            .get_inner()
+            .get_builder()
+            .build_unconditional_branch(step_block);
+
+        code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(step_block);
+        let step = match &self.step {
+            Some(step) => step.represent_expression(code_gen)?.into_struct_value(),
+            None => code_gen.make_num_from_i128(1)
+        };
+        // The body may have assigned to the loop variable, so it is loaded
+        // again.
+        let current = code_gen
+            .get_inner()
+            .get_builder()
+            .build_load(variable, "loop_variable")
+            .into_struct_value();
+        let current = NumValue::new(current, code_gen.clone())?;
+        let step = NumValue::new(step, code_gen.clone())?;
+        let next: StructValue<'ctx> = (&current + &step).into();
+        code_gen
+            .get_inner()
+            .get_builder()
+            .build_store(variable, next);
+        code_gen
+            .get_inner()
+            .get_builder()
+            .build_unconditional_branch(header_block);
+
+        code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(exit_block);
+        Ok(())
+    }
+}
+
+impl fmt::Display for ForLoopNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(for {} = {}, {}",
+            self.variable, self.start, self.condition
+        )?;
+        if let Some(step) = &self.step {
+            write!(f, ", {}", step)?;
+        }
+        write!(f, " in {})", self.body)
+    }
+}
+
+impl Clone for ForLoopNode {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.variable.clone(),
+            self.start.expr_node_clone(),
+            self.condition.expr_node_clone(),
+            self.step.as_ref().map(|step| step.expr_node_clone()),
+            self.body.expr_node_clone()
+        )
+    }
+}
+
+impl Node for ForLoopNode {
+    fn node_id_of_val(&self) -> NodeId {
+        Self::node_id()
+    }
+
+    fn node_clone(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+}
+
+impl IRRepresentableExpression for ForLoopNode {
+    fn represent_expression<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<BasicValueEnum<'ctx>> {
+        log::trace!("Entering <ForLoopNode as IRRepresentableExpression>::represent_expression");
+        let name = self.get_variable().get_value();
+        let start = self.start.represent_expression(code_gen)?;
+        log::trace!("Representation for start value generated");
+        let variable = code_gen.create_variable(name)?;
+        code_gen
+            .get_inner()
+            .get_builder()
+            .build_store(variable, start);
+
+        // The loop variable shadows any variable with the same name, but only
+        // until the end of the loop.
+        code_gen.with_scope(|| {
+            code_gen.set_value(name.to_string(), NamedValue::Variable(variable));
+            self.represent_loop(code_gen, variable)
+        })?;
+        log::trace!("IR generation done");
+        Ok(BasicValueEnum::StructValue(code_gen.make_num_from_i128(0)))
+    }
+}
+
+impl NodeType for ForLoopNode {}
+
+impl ExprNode for ForLoopNode {
+    fn expr_node_clone(&self) -> Box<dyn ExprNode> {
+        Box::new(self.clone())
+    }
+}
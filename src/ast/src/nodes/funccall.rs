@@ -62,43 +62,63 @@ impl IRRepresentableExpression for FunctionCallNode {
             "Entering <FunctionCallNode as IRRepresentableExpression>::represent_expression"
         );
         let name = self.get_identifier().get_value();
-        log::trace!("Trying to find function with the name '{}'", name);
-        let function = match code_gen.get_inner().get_module().get_function(name) {
-            Some(function) => function,
-            None => {
-                log::trace!("Could not find function with the name '{}'", name);
-                return Err(cgerror::Error::new(
-                    format!("Could not find function with name '{}'", name),
-                    cgerror::ErrorKind::UndefinedNameError,
-                    None
-                ));
-            }
-        };
-        log::trace!("Found function with the name '{}", name);
         log::trace!("Pushing arguments to call stack");
         let mut args: Vec<BasicValueEnum> = Vec::with_capacity(self.get_arguments().len());
         for arg in self.get_arguments() {
             args.push(arg.represent_expression(code_gen)?.as_basic_value_enum());
         }
-        log::trace!("Generating IR for function call");
-        match code_gen
-            .get_inner()
-            .get_builder()
-            .build_call(function, &*args, "call_tmp")
-            .try_as_basic_value()
-        {
-            Either::Left(basic) => {
-                log::trace!("Function call successfully generated");
-                Ok(basic)
-            },
-            Either::Right(_instruction) => {
-                log::trace!("Invalid function call");
-                Err(cgerror::Error::new(
-                    format!("Function converted to instruction value"),
-                    cgerror::ErrorKind::NotBasicValueError,
-                    None
-                ))
-            }
+        build_call(code_gen, name, &args)
+    }
+}
+
+/// Check whether the module already has a function called `name`, which is
+/// how a user-defined operator replaces a built-in one.
+pub(crate) fn has_function(code_gen: &CodeGen<'_>, name: &str) -> bool {
+    code_gen
+        .get_inner()
+        .get_module()
+        .get_function(name)
+        .is_some()
+}
+
+/// Generate a call to the function called `name` with `args`. This is also
+/// used to call the functions that implement user-defined operators.
+pub(crate) fn build_call<'ctx>(
+    code_gen: &CodeGen<'ctx>,
+    name: &str,
+    args: &[BasicValueEnum<'ctx>]
+) -> cgerror::Result<BasicValueEnum<'ctx>> {
+    log::trace!("Trying to find function with the name '{}'", name);
+    let function = match code_gen.get_inner().get_module().get_function(name) {
+        Some(function) => function,
+        None => {
+            log::trace!("Could not find function with the name '{}'", name);
+            return Err(cgerror::Error::new(
+                format!("Could not find function with name '{}'", name),
+                cgerror::ErrorKind::UndefinedNameError,
+                None
+            ));
+        }
+    };
+    log::trace!("Found function with the name '{}", name);
+    log::trace!("Generating IR for function call");
+    match code_gen
+        .get_inner()
+        .get_builder()
+        .build_call(function, args, "call_tmp")
+        .try_as_basic_value()
+    {
+        Either::Left(basic) => {
+            log::trace!("Function call successfully generated");
+            Ok(basic)
+        },
+        Either::Right(_instruction) => {
+            log::trace!("Invalid function call");
+            Err(cgerror::Error::new(
+                format!("Function converted to instruction value"),
+                cgerror::ErrorKind::NotBasicValueError,
+                None
+            ))
         }
     }
 }
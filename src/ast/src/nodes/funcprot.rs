@@ -6,21 +6,73 @@ use inkwell::{module::Linkage, values::AnyValueEnum};
 use kaleidoscope_codegen::{error as cgerror, CodeGen, IRRepresentableNode};
 use kaleidoscope_macro::iterator_to_str;
 
-use super::IdentifierNode;
+use super::{IdentifierNode, Operator};
 use crate::prelude::*;
 
+/// The default precedence of a user-defined binary operator if none is
+/// given in its definition.
+pub const DEFAULT_BINARY_PRECEDENCE: u32 = 30;
+
+/// What a [`FunctionPrototypeNode`] declares: either an ordinary function or
+/// a user-defined operator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PrototypeKind {
+    /// An ordinary function, called by its name.
+    Function,
+    /// A prefix operator taking 1 argument (e.g. `def unary$ (x)`).
+    Unary(Operator),
+    /// An infix operator taking 2 arguments with a precedence (e.g.
+    /// `def binary@ 5 (a, b)`).
+    Binary(Operator, u32)
+}
+
+impl PrototypeKind {
+    /// Get the name of the function that implements a user-defined operator,
+    /// which is the keyword used to define it followed by the operator
+    /// (e.g. `binary@`). [`None`] is returned for an ordinary function.
+    pub fn operator_function_name(&self) -> Option<String> {
+        match self {
+            PrototypeKind::Function => None,
+            PrototypeKind::Unary(operator) => Some(unary_operator_function_name(*operator)),
+            PrototypeKind::Binary(operator, _) => Some(binary_operator_function_name(*operator))
+        }
+    }
+}
+
+impl Default for PrototypeKind {
+    fn default() -> Self {
+        PrototypeKind::Function
+    }
+}
+
+/// Get the name of the function implementing a user-defined unary operator.
+pub fn unary_operator_function_name(operator: Operator) -> String {
+    format!("unary{}", operator)
+}
+
+/// Get the name of the function implementing a user-defined binary operator.
+pub fn binary_operator_function_name(operator: Operator) -> String {
+    format!("binary{}", operator)
+}
+
 /// A node representing a function prototype. This contains the name of the
 /// function and the parameters the function accepts.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FunctionPrototypeNode {
-    identifier: Box<IdentifierNode>,
-    parameters: Vec<Box<IdentifierNode>>
+    identifier:    Box<IdentifierNode>,
+    parameters:    Vec<Box<IdentifierNode>>,
+    documentation: Option<String>,
+    kind:          PrototypeKind
 }
 
 impl fmt::Display for FunctionPrototypeNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let params = iterator_to_str!(self.parameters.iter(), ", ");
-        write!(f, "def {}({})", self.identifier, params)
+        match self.kind {
+            PrototypeKind::Binary(_, precedence) =>
+                write!(f, "def {} {}({})", self.identifier, precedence, params),
+            _ => write!(f, "def {}({})", self.identifier, params)
+        }
     }
 }
 
@@ -30,10 +82,68 @@ impl FunctionPrototypeNode {
     pub fn new(identifier: Box<IdentifierNode>, parameters: Vec<Box<IdentifierNode>>) -> Self {
         FunctionPrototypeNode {
             identifier,
-            parameters
+            parameters,
+            documentation: None,
+            kind: PrototypeKind::Function
+        }
+    }
+
+    /// Create a prototype for a user-defined operator. The name of the
+    /// function is decided by `kind` (see
+    /// [`PrototypeKind::operator_function_name`]).
+    pub fn new_operator(kind: PrototypeKind, parameters: Vec<Box<IdentifierNode>>) -> Self {
+        let name = kind.operator_function_name().unwrap_or_default();
+        Self {
+            kind,
+            ..Self::new(Box::new(IdentifierNode::new(name)), parameters)
         }
     }
 
+    /// Get what this prototype declares.
+    pub fn get_kind(&self) -> PrototypeKind {
+        self.kind
+    }
+
+    /// Check if this prototype declares a user-defined unary operator.
+    pub fn is_unary_operator(&self) -> bool {
+        matches!(self.kind, PrototypeKind::Unary(_))
+    }
+
+    /// Check if this prototype declares a user-defined binary operator.
+    pub fn is_binary_operator(&self) -> bool {
+        matches!(self.kind, PrototypeKind::Binary(..))
+    }
+
+    /// Get the operator declared by this prototype, if it declares one.
+    pub fn get_operator(&self) -> Option<Operator> {
+        match self.kind {
+            PrototypeKind::Function => None,
+            PrototypeKind::Unary(operator) | PrototypeKind::Binary(operator, _) => Some(operator)
+        }
+    }
+
+    /// Get the precedence of the binary operator declared by this prototype.
+    pub fn get_binary_precedence(&self) -> Option<u32> {
+        match self.kind {
+            PrototypeKind::Binary(_, precedence) => Some(precedence),
+            _ => None
+        }
+    }
+
+    /// Attach documentation (from `##` comments) to this prototype, returning
+    /// the modified prototype.
+    pub fn with_documentation(mut self, documentation: Option<String>) -> Self {
+        self.documentation = documentation;
+        self
+    }
+
+    /// Get the documentation written in the `##` comments before the
+    /// function. Each line of the documentation comes from a separate
+    /// comment.
+    pub fn get_documentation(&self) -> Option<&str> {
+        self.documentation.as_deref()
+    }
+
     /// Get the identifier in the prototype.
     pub fn get_identifier(&self) -> &IdentifierNode {
         &*self.identifier
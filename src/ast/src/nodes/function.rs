@@ -2,12 +2,22 @@
 
 use std::fmt;
 
-use inkwell::values::AnyValueEnum;
-use kaleidoscope_codegen::{error as cgerror, CodeGen, IRRepresentableNode};
+use inkwell::values::{AnyValueEnum, FunctionValue};
+use kaleidoscope_codegen::{
+    error as cgerror,
+    scope::NamedValue,
+    CodeGen,
+    IRRepresentableNode,
+    ReturnContext
+};
 
-use super::FunctionPrototypeNode;
+use super::{FunctionPrototypeNode, IdentifierNode};
 use crate::prelude::*;
 
+/// The name of the function that a top-level expression is wrapped in, so
+/// that it can be run with the JIT.
+pub const ANONYMOUS_FUNCTION_NAME: &str = "__anon_expr";
+
 /// A node representing a function definition.
 #[derive(Debug)]
 pub struct FunctionNode {
@@ -21,11 +31,27 @@ impl FunctionNode {
         Self { prototype, body }
     }
 
+    /// Wrap a top-level expression in a function without parameters called
+    /// [`ANONYMOUS_FUNCTION_NAME`].
+    pub fn new_anonymous(body: Box<dyn ExprNode>) -> Self {
+        let identifier = Box::new(IdentifierNode::new(ANONYMOUS_FUNCTION_NAME.to_string()));
+        Self::new(
+            Box::new(FunctionPrototypeNode::new(identifier, Vec::new())),
+            body
+        )
+    }
+
     /// Get the prototype in the function definition.
     pub fn get_prototype(&self) -> &FunctionPrototypeNode {
         &*self.prototype
     }
 
+    /// Get the documentation of the function. See
+    /// [`FunctionPrototypeNode::get_documentation`].
+    pub fn get_documentation(&self) -> Option<&str> {
+        self.prototype.get_documentation()
+    }
+
     /// Get the body of the function.
     pub fn get_body(&self) -> &dyn ExprNode {
         &*self.body
@@ -57,42 +83,25 @@ impl Node for FunctionNode {
 impl NodeType for FunctionNode {}
 
 
-impl IRRepresentableNode for FunctionNode {
-    fn represent_node<'ctx>(
+impl FunctionNode {
+    /// Generate the body of `function`, which has already been declared.
+    /// If this fails, the caller has to delete the function from the
+    /// module.
+    fn represent_body<'ctx>(
         &self,
+        function: FunctionValue<'ctx>,
         code_gen: &CodeGen<'ctx>
-    ) -> cgerror::Result<AnyValueEnum<'ctx>> {
-        log::trace!("Entering <FunctionNode as IRRepresentableNode>::represent_node");
-        let name = self.get_prototype().get_identifier().get_value();
-        log::trace!("Generating IR for {}'s prototype", name);
-        let possible_function = {
-            let inner = code_gen.get_inner();
-            let module = inner.get_module();
-            module.get_function(name)
-        };
-        let function = match possible_function {
-            Some(f) => {
-                log::trace!("Pre-declared function prototype found");
-                f
-            },
-            None => {
-                log::trace!("Trying to register a new function prototype for '{}'", name);
-                let ir = self.get_prototype().represent_node(code_gen)?;
-                log::trace!("Function prototype that was created: {:?}", ir);
-                ir.into_function_value()
-            }
-        };
+    ) -> cgerror::Result<()> {
         log::trace!("Creating block for function");
         let block = code_gen.get_context().append_basic_block(function, "entry");
         code_gen.get_inner().get_builder().position_at_end(block);
-        log::trace!("Pushing parameter names to named_values table");
+        log::trace!("Storing parameters in variables");
         code_gen.clear_named_values();
         for index in 0..self.get_prototype().count_parameters() {
             let param_name = self
                 .get_prototype()
                 .nth_parameter(index)
                 .ok_or_else(|| {
-                    unsafe { function.delete() };
                     cgerror::Error::new(
                         format!(
                             "Tried to get parameter at index {} but it does not exist.",
@@ -105,7 +114,6 @@ impl IRRepresentableNode for FunctionNode {
                 .get_value()
                 .to_string();
             let argument = function.get_nth_param(index as u32).ok_or_else(|| {
-                unsafe { function.delete() };
                 cgerror::Error::new(
                     format!(
                         "Tried to get argument at index {} but it does not exist.",
@@ -115,18 +123,75 @@ impl IRRepresentableNode for FunctionNode {
                     None
                 )
             })?;
-            code_gen.set_value(param_name, Box::new(argument));
+            // Parameters live in stack slots, so they can be assigned to.
+            let slot = code_gen.create_variable(&param_name)?;
+            code_gen
+                .get_inner()
+                .get_builder()
+                .build_store(slot, argument);
+            code_gen.set_value(param_name, NamedValue::Variable(slot));
         }
+        // Both the end of the body and `return` store the returned value in
+        // a stack slot and branch to the exit block, which returns it.
+        let return_slot = code_gen.create_variable("return_value")?;
+        let exit_block = code_gen.append_block("exit")?;
         log::trace!("Generating return value from expression");
-        let retval = self.get_body().represent_expression(code_gen)?;
+        code_gen.enter_function(ReturnContext::new(return_slot, exit_block));
+        let retval = self.get_body().represent_expression(code_gen);
+        code_gen.exit_function();
+        let retval = retval?;
         log::trace!("Generating return instruction from return value");
-        code_gen
-            .get_inner()
-            .get_builder()
-            .build_return(Some(&retval));
+        {
+            let inner = code_gen.get_inner();
+            let builder = inner.get_builder();
+            builder.build_store(return_slot, retval);
+            builder.build_unconditional_branch(exit_block);
+            builder.position_at_end(exit_block);
+            let retval = builder.build_load(return_slot, "return_value");
+            builder.build_return(Some(&retval));
+        }
+        Ok(())
+    }
+}
+
+
+impl IRRepresentableNode for FunctionNode {
+    fn represent_node<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<AnyValueEnum<'ctx>> {
+        log::trace!("Entering <FunctionNode as IRRepresentableNode>::represent_node");
+        let name = self.get_prototype().get_identifier().get_value();
+        log::trace!("Generating IR for {}'s prototype", name);
+        let possible_function = {
+            let inner = code_gen.get_inner();
+            let module = inner.get_module();
+            module.get_function(name)
+        };
+        let function = match possible_function {
+            Some(f) => {
+                log::trace!("Pre-declared function prototype found");
+                f
+            },
+            None => {
+                log::trace!("Trying to register a new function prototype for '{}'", name);
+                let ir = self.get_prototype().represent_node(code_gen)?;
+                log::trace!("Function prototype that was created: {:?}", ir);
+                ir.into_function_value()
+            }
+        };
+        if let Err(error) = self.represent_body(function, code_gen) {
+            log::trace!(
+                "Could not generate the body of '{}', deleting it's declaration from module",
+                name
+            );
+            unsafe { function.delete() };
+            return Err(error);
+        }
         log::trace!("Verifying function...");
         if function.verify(true) {
             log::trace!("'{}' verified", name);
+            code_gen.optimise_function(&function);
             Ok(AnyValueEnum::FunctionValue(function))
         } else {
             log::trace!(
@@ -142,3 +207,18 @@ impl IRRepresentableNode for FunctionNode {
         }
     }
 }
+
+/// Evaluate a top-level expression by compiling it into a function called
+/// [`ANONYMOUS_FUNCTION_NAME`] and running it with the JIT, as the REPL
+/// does. The function is removed from the module afterwards.
+pub fn evaluate_expression<'ctx>(
+    expression: Box<dyn ExprNode>,
+    code_gen: &CodeGen<'ctx>
+) -> cgerror::Result<f64> {
+    let function = FunctionNode::new_anonymous(expression)
+        .represent_node(code_gen)?
+        .into_function_value();
+    let result = code_gen.run_function(ANONYMOUS_FUNCTION_NAME);
+    unsafe { function.delete() };
+    result
+}
@@ -0,0 +1,194 @@
+//! A module defining a [`GlobalNode`].
+
+use std::fmt;
+
+use inkwell::values::{AnyValueEnum, StructValue};
+use kaleidoscope_codegen::{builtins::NumValue, error as cgerror, CodeGen, IRRepresentableNode};
+
+use super::IdentifierNode;
+use crate::prelude::*;
+
+/// The name of the temporary function that the value of a global is
+/// generated in.
+const GLOBAL_INITIALIZER_FUNCTION_NAME: &str = "__global_init__";
+
+/// Whether a [`GlobalNode`] can be assigned to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GlobalKind {
+    /// `const`: the value never changes, so uses of it are replaced by the
+    /// value itself.
+    Constant,
+    /// `global`: a variable that any function can assign to.
+    Variable
+}
+
+impl GlobalKind {
+    /// Get the keyword that declares this kind of global.
+    pub fn to_keyword(&self) -> &str {
+        match self {
+            GlobalKind::Constant => "const",
+            GlobalKind::Variable => "global"
+        }
+    }
+}
+
+impl fmt::Display for GlobalKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_keyword())
+    }
+}
+
+/// An AST representing a top-level constant or variable, such as
+/// `const PI = 3.14159` or `global counter = 0`. See [`GlobalKind`].
+///
+/// The global is an LLVM global initialised to its value, which must be
+/// known before the programme runs, such as a number or an expression on
+/// numbers and other constants (`const TAU = 2 * PI`). Functions can use it by
+/// name, unless a local name is the same.
+#[derive(Debug)]
+pub struct GlobalNode {
+    kind:       GlobalKind,
+    identifier: Box<IdentifierNode>,
+    value:      Box<dyn ExprNode>
+}
+
+impl GlobalNode {
+    /// Create a new [`GlobalNode`].
+    pub fn new(
+        kind: GlobalKind,
+        identifier: Box<IdentifierNode>,
+        value: Box<dyn ExprNode>
+    ) -> Self {
+        Self {
+            kind,
+            identifier,
+            value
+        }
+    }
+
+    /// Get whether this is a constant or a variable.
+    pub fn get_kind(&self) -> GlobalKind {
+        self.kind
+    }
+
+    /// Get the name of the global.
+    pub fn get_identifier(&self) -> &IdentifierNode {
+        &self.identifier
+    }
+
+    /// Get the value the global starts out as.
+    pub fn get_value(&self) -> &dyn ExprNode {
+        &*self.value
+    }
+
+    /// Generate the value of the global, which must be known before the
+    /// programme runs, such as `2 * PI`. The value is generated in a
+    /// temporary function, which is deleted afterwards, and a
+    /// [`cgerror::ErrorKind::NotConstantError`] is returned if it is not a
+    /// constant.
+    fn represent_initializer<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<StructValue<'ctx>> {
+        let num_type = code_gen.get_num_type();
+        let function = code_gen.get_inner().get_module().add_function(
+            GLOBAL_INITIALIZER_FUNCTION_NAME,
+            num_type.fn_type(&[], false),
+            None
+        );
+        let previous_block = code_gen.get_inner().get_builder().get_insert_block();
+        let entry_block = code_gen.get_context().append_basic_block(function, "entry");
+        code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(entry_block);
+        // Constants are not part of the function, so they outlive it.
+        let value = self
+            .value
+            .represent_expression(code_gen)
+            .and_then(|value| NumValue::new(value.into_struct_value(), code_gen.clone()))
+            .map(|value| {
+                if value.is_const() {
+                    Some(value.into())
+                } else {
+                    None
+                }
+            });
+        match previous_block {
+            Some(block) => code_gen.get_inner().get_builder().position_at_end(block),
+            None => code_gen
+                .get_inner()
+                .get_builder()
+                .clear_insertion_position()
+        }
+        unsafe { function.delete() };
+        value?.ok_or_else(|| self.make_not_constant_error())
+    }
+
+    fn make_not_constant_error(&self) -> cgerror::Error {
+        cgerror::Error::new(
+            format!(
+                "The value of '{}' must be known before the programme runs, not '{}'",
+                self.identifier, self.value
+            ),
+            cgerror::ErrorKind::NotConstantError,
+            None
+        )
+    }
+}
+
+impl Clone for GlobalNode {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.kind,
+            self.identifier.clone(),
+            self.value.expr_node_clone()
+        )
+    }
+}
+
+impl fmt::Display for GlobalNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} = {}", self.kind, self.identifier, self.value)
+    }
+}
+
+impl Node for GlobalNode {
+    fn node_id_of_val(&self) -> NodeId {
+        Self::node_id()
+    }
+
+    fn node_clone(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+}
+
+impl NodeType for GlobalNode {}
+
+impl IRRepresentableNode for GlobalNode {
+    fn represent_node<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<AnyValueEnum<'ctx>> {
+        log::trace!("Entering <GlobalNode as IRRepresentableNode>::represent_node");
+        let name = self.identifier.get_value();
+        if code_gen.get_inner().get_module().get_global(name).is_some() {
+            return Err(cgerror::Error::new(
+                format!("Global '{}' is already defined", name),
+                cgerror::ErrorKind::RedefinitionError,
+                None
+            ));
+        }
+        let initializer = self.represent_initializer(code_gen)?;
+        log::trace!("Initializer for '{}' generated", name);
+        let num_type = code_gen.get_num_type();
+        let global = code_gen
+            .get_inner()
+            .get_module()
+            .add_global(num_type, None, name);
+        global.set_initializer(&initializer);
+        global.set_constant(self.kind == GlobalKind::Constant);
+        log::trace!("Done");
+        Ok(AnyValueEnum::PointerValue(global.as_pointer_value()))
+    }
+}
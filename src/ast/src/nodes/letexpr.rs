@@ -0,0 +1,125 @@
+//! A module defining a [`LetNode`].
+
+use std::fmt;
+
+use inkwell::values::BasicValueEnum;
+use kaleidoscope_codegen::{
+    error as cgerror,
+    scope::NamedValue,
+    CodeGen,
+    IRRepresentableExpression
+};
+
+use super::IdentifierNode;
+use crate::prelude::*;
+
+
+/// An AST representing an immutable name bound to a value, which can only be
+/// used in an expression.
+///
+/// # Example
+///
+/// ```text
+/// let x = f(1) in
+///     let y = x * x in
+///         x + y
+/// ```
+///
+/// The name shadows any other name that is the same until the end of the
+/// body. Unlike the variables of a [`super::VarInNode`], it cannot be
+/// assigned to, so its value is used directly instead of being kept in a
+/// stack slot. The whole expression evaluates to the value of the body.
+#[derive(Debug)]
+pub struct LetNode {
+    identifier: Box<IdentifierNode>,
+    value:      Box<dyn ExprNode>,
+    body:       Box<dyn ExprNode>
+}
+
+impl LetNode {
+    /// Create a new [`LetNode`].
+    pub fn new(
+        identifier: Box<IdentifierNode>,
+        value: Box<dyn ExprNode>,
+        body: Box<dyn ExprNode>
+    ) -> Self {
+        Self {
+            identifier,
+            value,
+            body
+        }
+    }
+
+    /// Get the name that is bound.
+    pub fn get_identifier(&self) -> &IdentifierNode {
+        &self.identifier
+    }
+
+    /// Get the value the name is bound to.
+    pub fn get_value(&self) -> &dyn ExprNode {
+        &*self.value
+    }
+
+    /// Get the expression the name can be used in.
+    pub fn get_body(&self) -> &dyn ExprNode {
+        &*self.body
+    }
+}
+
+impl fmt::Display for LetNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(let {} = {} in {})",
+            self.identifier, self.value, self.body
+        )
+    }
+}
+
+impl Clone for LetNode {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.identifier.clone(),
+            self.value.expr_node_clone(),
+            self.body.expr_node_clone()
+        )
+    }
+}
+
+impl Node for LetNode {
+    fn node_id_of_val(&self) -> NodeId {
+        Self::node_id()
+    }
+
+    fn node_clone(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+}
+
+impl IRRepresentableExpression for LetNode {
+    fn represent_expression<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<BasicValueEnum<'ctx>> {
+        log::trace!("Entering <LetNode as IRRepresentableExpression>::represent_expression");
+        let name = self.identifier.get_value();
+        // The value is generated outside the new scope, so `let x = x + 1`
+        // uses the outer `x`.
+        let value = self.value.represent_expression(code_gen)?;
+        log::trace!("Representation for the value of '{}' generated", name);
+        let result = code_gen.with_scope(|| {
+            code_gen.set_value(name.to_string(), NamedValue::Value(value));
+            self.body.represent_expression(code_gen)
+        });
+        log::trace!("IR generation done");
+        result
+    }
+}
+
+impl NodeType for LetNode {}
+
+impl ExprNode for LetNode {
+    fn expr_node_clone(&self) -> Box<dyn ExprNode> {
+        Box::new(self.clone())
+    }
+}
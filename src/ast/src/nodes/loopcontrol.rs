@@ -0,0 +1,109 @@
+//! A module defining a [`LoopControlNode`].
+
+use std::fmt;
+
+use inkwell::values::BasicValueEnum;
+use kaleidoscope_codegen::{error as cgerror, CodeGen, IRRepresentableExpression};
+
+use crate::prelude::*;
+
+
+/// The ways of leaving the body of a loop early.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LoopControl {
+    /// `break`: leave the innermost loop.
+    Break,
+    /// `continue`: start the next iteration of the innermost loop.
+    Continue
+}
+
+impl LoopControl {
+    /// Get the keyword for this statement.
+    pub fn to_keyword(&self) -> &str {
+        match self {
+            LoopControl::Break => "break",
+            LoopControl::Continue => "continue"
+        }
+    }
+}
+
+impl fmt::Display for LoopControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_keyword())
+    }
+}
+
+/// An AST representing `break` or `continue` inside a loop. See
+/// [`LoopControl`].
+///
+/// Any code after it in the same iteration is never run, so the value it
+/// evaluates to (`0`) is never used.
+#[derive(Debug, Clone)]
+pub struct LoopControlNode {
+    control: LoopControl
+}
+
+impl LoopControlNode {
+    /// Create a new [`LoopControlNode`].
+    pub fn new(control: LoopControl) -> Self {
+        Self { control }
+    }
+
+    /// Get whether this is `break` or `continue`.
+    pub fn get_control(&self) -> LoopControl {
+        self.control
+    }
+}
+
+impl fmt::Display for LoopControlNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.control)
+    }
+}
+
+impl Node for LoopControlNode {
+    fn node_id_of_val(&self) -> NodeId {
+        Self::node_id()
+    }
+
+    fn node_clone(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+}
+
+impl IRRepresentableExpression for LoopControlNode {
+    fn represent_expression<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<BasicValueEnum<'ctx>> {
+        log::trace!(
+            "Entering <LoopControlNode as IRRepresentableExpression>::represent_expression"
+        );
+        let current_loop = code_gen.get_current_loop(self.control.to_keyword())?;
+        let target = match self.control {
+            LoopControl::Break => current_loop.get_break_block(),
+            LoopControl::Continue => current_loop.get_continue_block()
+        };
+        // Anything generated after the branch goes into a block that is
+        // never reached.
+        let unreachable_block = code_gen.append_block("after_loop_control")?;
+        code_gen
+            .get_inner()
+            .get_builder()
+            .build_unconditional_branch(target);
+        code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(unreachable_block);
+        log::trace!("IR generation done");
+        Ok(BasicValueEnum::StructValue(code_gen.make_num_from_i128(0)))
+    }
+}
+
+impl NodeType for LoopControlNode {}
+
+impl ExprNode for LoopControlNode {
+    fn expr_node_clone(&self) -> Box<dyn ExprNode> {
+        Box::new(self.clone())
+    }
+}
@@ -1,25 +1,49 @@
 //! A module defining all the nodes in a Kaleidoscope Abstract Syntax Tree.
 
 mod binaryop;
+mod block;
 mod externfunc;
 mod float;
+mod forloop;
 mod funccall;
 mod funcprot;
 mod function;
+mod global;
 mod identifier;
 mod integer;
+mod letexpr;
+mod loopcontrol;
 mod operator;
+mod program;
+mod returnexpr;
 mod unaryop;
 mod varexpr;
+mod varin;
+mod whileloop;
 
 pub use binaryop::BinaryOperatorNode;
+pub use block::BlockNode;
 pub use externfunc::ExternFunctionNode;
 pub use float::{FloatNode, FloatType};
+pub use forloop::ForLoopNode;
 pub use funccall::FunctionCallNode;
-pub use funcprot::FunctionPrototypeNode;
-pub use function::FunctionNode;
+pub use funcprot::{
+    binary_operator_function_name,
+    unary_operator_function_name,
+    FunctionPrototypeNode,
+    PrototypeKind,
+    DEFAULT_BINARY_PRECEDENCE
+};
+pub use function::{evaluate_expression, FunctionNode, ANONYMOUS_FUNCTION_NAME};
+pub use global::{GlobalKind, GlobalNode};
 pub use identifier::IdentifierNode;
 pub use integer::{IntegerNode, IntegerType};
+pub use letexpr::LetNode;
+pub use loopcontrol::{LoopControl, LoopControlNode};
 pub use operator::Operator;
+pub use program::{ProgramItem, ProgramNode};
+pub use returnexpr::ReturnNode;
 pub use unaryop::UnaryOperatorNode;
 pub use varexpr::VariableExpressionNode;
+pub use varin::{VarBinding, VarInNode};
+pub use whileloop::WhileLoopNode;
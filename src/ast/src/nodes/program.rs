@@ -0,0 +1,165 @@
+//! A module defining a [`ProgramNode`].
+
+use std::fmt;
+
+use super::{ExternFunctionNode, FunctionNode, GlobalNode};
+use crate::{node::reify_node, prelude::*};
+
+/// A top-level item in a [`ProgramNode`].
+#[derive(Debug)]
+pub enum ProgramItem {
+    /// An extern function declaration.
+    Extern(Box<ExternFunctionNode>),
+    /// A function definition.
+    Function(Box<FunctionNode>),
+    /// A global constant or variable.
+    Global(Box<GlobalNode>),
+    /// An expression outside of any function.
+    Expression(Box<dyn ExprNode>)
+}
+
+impl ProgramItem {
+    /// Convert a node returned by the parser into a [`ProgramItem`]. If the
+    /// node cannot be a top-level item, it is returned as an error.
+    pub fn from_node_enum(node: NodeEnum) -> std::result::Result<Self, NodeEnum> {
+        match node {
+            NodeEnum::ExprNode(expression) => Ok(ProgramItem::Expression(expression)),
+            NodeEnum::AnyNode(node) =>
+                if node.node_id_of_val() == ExternFunctionNode::node_id() {
+                    Ok(ProgramItem::Extern(reify_node(node).unwrap()))
+                } else if node.node_id_of_val() == FunctionNode::node_id() {
+                    Ok(ProgramItem::Function(reify_node(node).unwrap()))
+                } else if node.node_id_of_val() == GlobalNode::node_id() {
+                    Ok(ProgramItem::Global(reify_node(node).unwrap()))
+                } else {
+                    Err(NodeEnum::AnyNode(node))
+                },
+        }
+    }
+
+    /// Convert this item back into a [`NodeEnum`].
+    pub fn into_node_enum(self) -> NodeEnum {
+        match self {
+            ProgramItem::Extern(node) => NodeEnum::AnyNode(node),
+            ProgramItem::Function(node) => NodeEnum::AnyNode(node),
+            ProgramItem::Global(node) => NodeEnum::AnyNode(node),
+            ProgramItem::Expression(node) => NodeEnum::ExprNode(node)
+        }
+    }
+}
+
+impl Clone for ProgramItem {
+    fn clone(&self) -> Self {
+        match self {
+            ProgramItem::Extern(node) => ProgramItem::Extern(node.clone()),
+            ProgramItem::Function(node) => ProgramItem::Function(node.clone()),
+            ProgramItem::Global(node) => ProgramItem::Global(node.clone()),
+            ProgramItem::Expression(node) => ProgramItem::Expression(node.expr_node_clone())
+        }
+    }
+}
+
+impl fmt::Display for ProgramItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgramItem::Extern(node) => write!(f, "{}", node),
+            ProgramItem::Function(node) => write!(f, "{}", node),
+            ProgramItem::Global(node) => write!(f, "{}", node),
+            ProgramItem::Expression(node) => write!(f, "{}", node)
+        }
+    }
+}
+
+/// The root of the AST of a whole Kaleidoscope programme. It contains the
+/// extern functions, function definitions, globals and top-level expressions
+/// in the programme, in the order they appear.
+#[derive(Debug, Clone, Default)]
+pub struct ProgramNode {
+    items: Vec<ProgramItem>
+}
+
+impl ProgramNode {
+    /// Create a new [`ProgramNode`].
+    pub fn new(items: Vec<ProgramItem>) -> Self {
+        Self { items }
+    }
+
+    /// Add an item to the end of the programme.
+    pub fn push(&mut self, item: ProgramItem) -> &mut Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Get the items in the programme.
+    pub fn get_items(&self) -> &[ProgramItem] {
+        &self.items[..]
+    }
+
+    /// Take the items out of the programme.
+    pub fn into_items(self) -> Vec<ProgramItem> {
+        self.items
+    }
+
+    /// Get the number of items in the programme.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Check if there are no items in the programme.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Iterate over the extern functions in the programme.
+    pub fn externs(&self) -> impl Iterator<Item = &ExternFunctionNode> {
+        self.items.iter().filter_map(|item| match item {
+            ProgramItem::Extern(node) => Some(&**node),
+            _ => None
+        })
+    }
+
+    /// Iterate over the function definitions in the programme.
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionNode> {
+        self.items.iter().filter_map(|item| match item {
+            ProgramItem::Function(node) => Some(&**node),
+            _ => None
+        })
+    }
+
+    /// Iterate over the global constants and variables in the programme.
+    pub fn globals(&self) -> impl Iterator<Item = &GlobalNode> {
+        self.items.iter().filter_map(|item| match item {
+            ProgramItem::Global(node) => Some(&**node),
+            _ => None
+        })
+    }
+
+    /// Iterate over the top-level expressions in the programme.
+    pub fn expressions(&self) -> impl Iterator<Item = &dyn ExprNode> {
+        self.items.iter().filter_map(|item| match item {
+            ProgramItem::Expression(node) => Some(&**node),
+            _ => None
+        })
+    }
+}
+
+impl fmt::Display for ProgramNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for item in self.items.iter() {
+            writeln!(f, "{};", item)?;
+        }
+        Ok(())
+    }
+}
+
+impl Node for ProgramNode {
+    fn node_id_of_val(&self) -> NodeId {
+        Self::node_id()
+    }
+
+    fn node_clone(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+}
+
+impl NodeType for ProgramNode {}
@@ -0,0 +1,92 @@
+//! A module defining a [`ReturnNode`].
+
+use std::fmt;
+
+use inkwell::values::BasicValueEnum;
+use kaleidoscope_codegen::{error as cgerror, CodeGen, IRRepresentableExpression};
+
+use crate::prelude::*;
+
+
+/// An AST representing an early return from a function.
+///
+/// # Example
+///
+/// ```text
+/// def find(n)
+///     for i = 0, i < n in
+///         f(i) == 0 && return i
+/// ```
+///
+/// The value is returned from the function straight away, so any code after
+/// it is never run, and the value it evaluates to is never used.
+#[derive(Debug)]
+pub struct ReturnNode {
+    value: Box<dyn ExprNode>
+}
+
+impl ReturnNode {
+    /// Create a new [`ReturnNode`].
+    pub fn new(value: Box<dyn ExprNode>) -> Self {
+        Self { value }
+    }
+
+    /// Get the value that is returned.
+    pub fn get_value(&self) -> &dyn ExprNode {
+        &*self.value
+    }
+}
+
+impl fmt::Display for ReturnNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(return {})", self.value)
+    }
+}
+
+impl Clone for ReturnNode {
+    fn clone(&self) -> Self {
+        Self::new(self.value.expr_node_clone())
+    }
+}
+
+impl Node for ReturnNode {
+    fn node_id_of_val(&self) -> NodeId {
+        Self::node_id()
+    }
+
+    fn node_clone(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+}
+
+impl IRRepresentableExpression for ReturnNode {
+    fn represent_expression<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<BasicValueEnum<'ctx>> {
+        log::trace!("Entering <ReturnNode as IRRepresentableExpression>::represent_expression");
+        let context = code_gen.get_return_context()?;
+        let value = self.value.represent_expression(code_gen)?;
+        log::trace!("Representation for returned value generated");
+        // Anything generated after the branch goes into a block that is
+        // never reached.
+        let unreachable_block = code_gen.append_block("after_return")?;
+        {
+            let inner = code_gen.get_inner();
+            let builder = inner.get_builder();
+            builder.build_store(context.get_return_slot(), value);
+            builder.build_unconditional_branch(context.get_exit_block());
+            builder.position_at_end(unreachable_block);
+        }
+        log::trace!("IR generation done");
+        Ok(value)
+    }
+}
+
+impl NodeType for ReturnNode {}
+
+impl ExprNode for ReturnNode {
+    fn expr_node_clone(&self) -> Box<dyn ExprNode> {
+        Box::new(self.clone())
+    }
+}
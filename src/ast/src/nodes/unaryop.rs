@@ -2,10 +2,19 @@
 
 use std::fmt;
 
-use inkwell::values::BasicValueEnum;
-use kaleidoscope_codegen::{error as cgerror, CodeGen, IRRepresentableExpression};
+use inkwell::values::{BasicValue, BasicValueEnum, StructValue};
+use kaleidoscope_codegen::{
+    builtins::NumValue,
+    error as cgerror,
+    CodeGen,
+    IRRepresentableExpression
+};
 
-use super::Operator;
+use super::{
+    funccall::{build_call, has_function},
+    unary_operator_function_name,
+    Operator
+};
 use crate::prelude::*;
 
 /// An AST representing an operator which takes 1 argument.
@@ -13,8 +22,11 @@ use crate::prelude::*;
 /// # Examples
 ///
 /// ```text
-/// !true // Boolean Not: false
-/// ~50 // Bitwise Not: 205 (8-bit unsigned integer)
+/// -5 // Negation: -5
+/// !0 // Logical Not: 1
+/// !2.5 // Logical Not: 0
+/// ~6 // Bitwise Not: -7
+/// $x // User-defined: unary$(x)
 /// ```
 #[derive(Debug)]
 pub struct UnaryOperatorNode {
@@ -64,16 +76,35 @@ impl Node for UnaryOperatorNode {
 impl IRRepresentableExpression for UnaryOperatorNode {
     fn represent_expression<'ctx>(
         &self,
-        _code_gen: &CodeGen<'ctx>
+        code_gen: &CodeGen<'ctx>
     ) -> cgerror::Result<BasicValueEnum<'ctx>> {
         log::trace!(
             "Entering <UnaryOperatorNode as IRRepresentableExpression>::represent_expression"
         );
-        Err(cgerror::Error::new(
-            format!("Unknown unary operator: {}", self.get_operator()),
-            cgerror::ErrorKind::UnknownOperationError,
-            None
-        ))
+        let operand = self.first.represent_expression(code_gen)?;
+        log::trace!("Representation for operand generated");
+        let name = unary_operator_function_name(*self.operator);
+        if !self.operator.is_builtin() || has_function(code_gen, &name) {
+            log::trace!("Calling user-defined unary operator {}", self.operator);
+            return build_call(code_gen, &name, &[operand]);
+        }
+        let operand = NumValue::new(
+            operand.as_basic_value_enum().into_struct_value(),
+            code_gen.clone()
+        )?;
+        let result: StructValue<'ctx> = match *self.operator {
+            Operator::Minus => (-&operand).into(),
+            Operator::Not => (!&operand).into(),
+            Operator::BitwiseNot => operand.bitwise_not()?.into(),
+            _ =>
+                return Err(cgerror::Error::new(
+                    format!("Unknown unary operator: {}", self.get_operator()),
+                    cgerror::ErrorKind::UnknownOperationError,
+                    None
+                )),
+        };
+        log::trace!("IR generation done");
+        Ok(BasicValueEnum::StructValue(result))
     }
 }
 
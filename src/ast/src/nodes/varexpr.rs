@@ -10,7 +10,8 @@ use crate::prelude::*;
 
 
 /// An expression where it's just one variable. This is essentially like
-/// `y` in the statement `x = y` in typical "C-like" languages.
+/// `y` in the statement `x = y` in typical "C-like" languages. If `y` is a
+/// mutable variable, its value is loaded from its stack slot.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VariableExpressionNode {
     identifier: Box<IdentifierNode>
@@ -53,21 +54,8 @@ impl IRRepresentableExpression for VariableExpressionNode {
             "Entering <VariableExpressionNode as IRRepresentableExpression>::represent_expression"
         );
         let name = self.get_identifier().get_value();
-        log::trace!("Finding value of '{}'", name);
-        match code_gen.get_value(name) {
-            Some(value) => {
-                log::trace!("Value of '{}' found", name);
-                Ok(value)
-            },
-            None => {
-                log::trace!("Could not find identifier named '{}'", name);
-                Err(cgerror::Error::new(
-                    format!("Could not find identifier named '{}'", name),
-                    cgerror::ErrorKind::UndefinedNameError,
-                    None
-                ))
-            }
-        }
+        log::trace!("Loading value of '{}'", name);
+        code_gen.load_value(name)
     }
 }
 
@@ -0,0 +1,140 @@
+//! A module defining a [`VarInNode`].
+
+use std::fmt;
+
+use inkwell::values::BasicValueEnum;
+use kaleidoscope_codegen::{
+    error as cgerror,
+    scope::NamedValue,
+    CodeGen,
+    IRRepresentableExpression
+};
+
+use super::IdentifierNode;
+use crate::prelude::*;
+
+
+/// A variable declared by a [`VarInNode`], along with the expression it
+/// starts out as (if there is one).
+pub type VarBinding = (Box<IdentifierNode>, Option<Box<dyn ExprNode>>);
+
+/// An AST representing mutable local variables that can be used in an
+/// expression.
+///
+/// # Example
+///
+/// ```text
+/// var x = 1, y in
+///     y = x + 2
+/// ```
+///
+/// Each variable is given the value of its expression, or `0` if it does not
+/// have one. The expression of a variable can use the variables declared
+/// before it. The variables can only be used in the body, and shadow any
+/// variables with the same name outside of it. The whole expression
+/// evaluates to the value of the body.
+#[derive(Debug)]
+pub struct VarInNode {
+    bindings: Vec<VarBinding>,
+    body:     Box<dyn ExprNode>
+}
+
+impl VarInNode {
+    /// Create a new [`VarInNode`].
+    pub fn new(bindings: Vec<VarBinding>, body: Box<dyn ExprNode>) -> Self {
+        Self { bindings, body }
+    }
+
+    /// Get the variables that are declared, in order.
+    pub fn get_bindings(&self) -> &[VarBinding] {
+        &self.bindings[..]
+    }
+
+    /// Get the expression the variables are used in.
+    pub fn get_body(&self) -> &dyn ExprNode {
+        &*self.body
+    }
+
+    /// Give each variable a stack slot and its starting value, in the
+    /// current scope.
+    fn represent_bindings<'ctx>(&self, code_gen: &CodeGen<'ctx>) -> cgerror::Result<()> {
+        for (identifier, value) in self.bindings.iter() {
+            let name = identifier.get_value();
+            let value = match value {
+                Some(value) => value.represent_expression(code_gen)?,
+                None => BasicValueEnum::StructValue(code_gen.make_num_from_i128(0))
+            };
+            log::trace!("Representation for the value of '{}' generated", name);
+            let slot = code_gen.create_variable(name)?;
+            code_gen.get_inner().get_builder().build_store(slot, value);
+            code_gen.set_value(name.to_string(), NamedValue::Variable(slot));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for VarInNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(var ")?;
+        for (index, (identifier, value)) in self.bindings.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", identifier)?;
+            if let Some(value) = value {
+                write!(f, " = {}", value)?;
+            }
+        }
+        write!(f, " in {})", self.body)
+    }
+}
+
+impl Clone for VarInNode {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.bindings
+                .iter()
+                .map(|(identifier, value)| {
+                    (
+                        identifier.clone(),
+                        value.as_ref().map(|value| value.expr_node_clone())
+                    )
+                })
+                .collect(),
+            self.body.expr_node_clone()
+        )
+    }
+}
+
+impl Node for VarInNode {
+    fn node_id_of_val(&self) -> NodeId {
+        Self::node_id()
+    }
+
+    fn node_clone(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+}
+
+impl IRRepresentableExpression for VarInNode {
+    fn represent_expression<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<BasicValueEnum<'ctx>> {
+        log::trace!("Entering <VarInNode as IRRepresentableExpression>::represent_expression");
+        let result = code_gen.with_scope(|| {
+            self.represent_bindings(code_gen)?;
+            self.body.represent_expression(code_gen)
+        });
+        log::trace!("IR generation done");
+        result
+    }
+}
+
+impl NodeType for VarInNode {}
+
+impl ExprNode for VarInNode {
+    fn expr_node_clone(&self) -> Box<dyn ExprNode> {
+        Box::new(self.clone())
+    }
+}
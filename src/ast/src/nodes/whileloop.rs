@@ -0,0 +1,136 @@
+//! A module defining a [`WhileLoopNode`].
+
+use std::fmt;
+
+use inkwell::values::BasicValueEnum;
+use kaleidoscope_codegen::{
+    builtins::NumValue,
+    error as cgerror,
+    CodeGen,
+    IRRepresentableExpression,
+    LoopContext
+};
+
+use crate::prelude::*;
+
+
+/// An AST representing a loop that runs for as long as a condition is true.
+///
+/// # Example
+///
+/// ```text
+/// while x < 10 do
+///     x = x + 1
+/// ```
+///
+/// The condition is checked before each iteration, so the body might never
+/// run. Inside the body, `continue` goes back to the condition and `break`
+/// leaves the loop. The loop itself always evaluates to `0`.
+#[derive(Debug)]
+pub struct WhileLoopNode {
+    condition: Box<dyn ExprNode>,
+    body:      Box<dyn ExprNode>
+}
+
+impl WhileLoopNode {
+    /// Create a new [`WhileLoopNode`].
+    pub fn new(condition: Box<dyn ExprNode>, body: Box<dyn ExprNode>) -> Self {
+        Self { condition, body }
+    }
+
+    /// Get the condition that must be true for the loop to continue.
+    pub fn get_condition(&self) -> &dyn ExprNode {
+        &*self.condition
+    }
+
+    /// Get the body of the loop.
+    pub fn get_body(&self) -> &dyn ExprNode {
+        &*self.body
+    }
+}
+
+impl fmt::Display for WhileLoopNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(while {} do {})", self.condition, self.body)
+    }
+}
+
+impl Clone for WhileLoopNode {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.condition.expr_node_clone(),
+            self.body.expr_node_clone()
+        )
+    }
+}
+
+impl Node for WhileLoopNode {
+    fn node_id_of_val(&self) -> NodeId {
+        Self::node_id()
+    }
+
+    fn node_clone(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+}
+
+impl IRRepresentableExpression for WhileLoopNode {
+    fn represent_expression<'ctx>(
+        &self,
+        code_gen: &CodeGen<'ctx>
+    ) -> cgerror::Result<BasicValueEnum<'ctx>> {
+        log::trace!("Entering <WhileLoopNode as IRRepresentableExpression>::represent_expression");
+        let header_block = code_gen.append_block("while_header")?;
+        let body_block = code_gen.append_block("while_body")?;
+        let exit_block = code_gen.append_block("while_exit")?;
+        code_gen
+            .get_inner()
+            .get_builder()
+            .build_unconditional_branch(header_block);
+
+        code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(header_block);
+        let condition = NumValue::new(
+            self.condition
+                .represent_expression(code_gen)?
+                .into_struct_value(),
+            code_gen.clone()
+        )?
+        .is_truthy()?;
+        code_gen
+            .get_inner()
+            .get_builder()
+            .build_conditional_branch(condition, body_block, exit_block);
+
+        code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(body_block);
+        code_gen.enter_loop(LoopContext::new(header_block, exit_block));
+        let body = self.body.represent_expression(code_gen);
+        code_gen.exit_loop();
+        body?;
+        log::trace!("Representation for loop body generated");
+        code_gen
+            .get_inner()
+            .get_builder()
+            .build_unconditional_branch(header_block);
+
+        code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(exit_block);
+        log::trace!("IR generation done");
+        Ok(BasicValueEnum::StructValue(code_gen.make_num_from_i128(0)))
+    }
+}
+
+impl NodeType for WhileLoopNode {}
+
+impl ExprNode for WhileLoopNode {
+    fn expr_node_clone(&self) -> Box<dyn ExprNode> {
+        Box::new(self.clone())
+    }
+}
@@ -19,16 +19,25 @@ macro_rules! node_types_to_id {
 fn test_ensure_all_nodes_have_unique_ids() {
     let nodes = node_types_to_id![
         BinaryOperatorNode,
+        BlockNode,
         ExternFunctionNode,
         FloatNode,
+        ForLoopNode,
         FunctionCallNode,
         FunctionPrototypeNode,
         FunctionNode,
+        GlobalNode,
         IdentifierNode,
         IntegerNode,
+        LetNode,
+        LoopControlNode,
         Operator,
+        ProgramNode,
+        ReturnNode,
         UnaryOperatorNode,
-        VariableExpressionNode
+        VariableExpressionNode,
+        VarInNode,
+        WhileLoopNode
     ];
     for (lindex, lhs) in nodes.iter().enumerate() {
         for rhs in &nodes[lindex + 1..] {
@@ -1,14 +1,17 @@
 use std::{
     convert::AsRef,
-    io::{prelude::*, stdin, stdout}
+    io::{prelude::*, stdin, stdout},
+    process::exit
 };
 
+use clap::{App, Arg};
 use inkwell::{context::Context, values::AnyValue, OptimizationLevel};
 use kaleidoscope_ast::{
     node::{reify_node_ref, NodeEnum},
-    nodes::{ExternFunctionNode, FunctionNode}
+    nodes::{evaluate_expression, ExternFunctionNode, FunctionNode, GlobalNode}
 };
 use kaleidoscope_codegen::{create_code_gen, IRRepresentableNode};
+use kaleidoscope_lexer::tokenizer::{IdentifierRules, TokenizerOptions};
 use kaleidoscope_parser::driver::Interpreter;
 
 
@@ -28,6 +31,49 @@ fn press_enter_to_continue(prompt: &dyn AsRef<str>) {
 fn main() {
     kaleidoscope_logging::init(None).unwrap();
 
+    let matches = App::new("LLVM Kaleidoscope REPL")
+        .version("0.1.0")
+        .author("Renoir Tan")
+        .about("REPL for LLVM's Kaleidoscope")
+        .arg(
+            Arg::with_name("comment_char")
+                .value_name("COMMENT_CHAR")
+                .long("comment-char")
+                .help("The character that starts a comment. Default is '#'.")
+                .multiple(false)
+        )
+        .arg(
+            Arg::with_name("unicode_identifiers")
+                .long("unicode-identifiers")
+                .help("Allow non-ASCII letters and digits in identifiers.")
+                .multiple(false)
+        )
+        .arg(
+            Arg::with_name("tab_width")
+                .value_name("TAB_WIDTH")
+                .long("tab-width")
+                .help("The number of columns a tab takes up. Default is 8.")
+                .multiple(false)
+        )
+        .get_matches();
+    let mut options = TokenizerOptions::new();
+    if let Some(comment_char) = matches.value_of("comment_char") {
+        let mut units = comment_char.chars();
+        match (units.next(), units.next()) {
+            (Some(unit), None) => options = options.with_comment_char(unit),
+            _ => {
+                eprintln!("The comment character must be exactly 1 character long.");
+                exit(1);
+            }
+        }
+    }
+    if matches.is_present("unicode_identifiers") {
+        options = options.with_identifier_rules(IdentifierRules::Unicode);
+    }
+    if let Some(tab_width) = matches.value_of("tab_width") {
+        options = options.with_tab_width(tab_width.parse().expect("Invalid tab width."));
+    }
+
     let context = Context::create();
     let module = context.create_module("__main__");
     let engine = module
@@ -40,6 +86,7 @@ fn main() {
     log::debug!("STARTING REPL");
     println!("{}", WELCOME_MESSAGE);
     let mut repl = Interpreter::default();
+    repl.set_tokenizer_options(options);
     for node in &mut repl {
         let node = node.unwrap();
         if let Some(node) = node {
@@ -53,11 +100,15 @@ fn main() {
                         log::debug!("Extern function node detected");
                         let ir = external.represent_node(&code_gen).unwrap();
                         println!("{}", ir.print_to_string().to_string());
+                    } else if let Some(global) = reify_node_ref::<GlobalNode>(&node) {
+                        log::debug!("Global node detected");
+                        let ir = global.represent_node(&code_gen).unwrap();
+                        println!("{}", ir.print_to_string().to_string());
                     },
                 NodeEnum::ExprNode(node) => {
-                    log::debug!("Other node type detected");
-                    let ir = node.represent_expression(&code_gen).unwrap();
-                    println!("{}", ir.print_to_string().to_string());
+                    log::debug!("Top-level expression detected");
+                    let value = evaluate_expression(node, &code_gen).unwrap();
+                    println!("Evaluated to {}", value);
                 }
             }
         }
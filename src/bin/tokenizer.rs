@@ -1,13 +1,31 @@
-use std::{convert::TryFrom, path::PathBuf, process::exit};
+use std::{
+    convert::TryFrom,
+    fs::read_to_string,
+    io::{stdin, stdout, Read, Write},
+    path::PathBuf,
+    process::exit
+};
 
 use clap::{App, Arg};
-use kaleidoscope_lexer::tokenizer::{FileStream, LexerSerializer, TokenIterator, Tokenizer};
+use kaleidoscope_lexer::{
+    highlight::{highlight_source_with, HighlightFormat},
+    tokenizer::{
+        FileStream,
+        IdentifierRules,
+        LexerSerializer,
+        TokenIterator,
+        TokenTable,
+        TokenizerOptions
+    }
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum OutputFormats {
     Debug,
     Json,
-    Toml
+    Ndjson,
+    Toml,
+    Highlight(HighlightFormat)
 }
 
 impl OutputFormats {
@@ -15,8 +33,9 @@ impl OutputFormats {
         Some(match string {
             "debug" => OutputFormats::Debug,
             "json" => OutputFormats::Json,
+            "ndjson" => OutputFormats::Ndjson,
             "toml" => OutputFormats::Toml,
-            _ => return None
+            _ => OutputFormats::Highlight(HighlightFormat::from_string(string)?)
         })
     }
 }
@@ -57,21 +76,57 @@ fn main() {
                 .value_name("OUTPUT_FORMAT")
                 .short("F")
                 .long("output-format")
-                .help("The output format. Default is Rust's debug method.")
+                .help(
+                    "The output format (debug, json, ndjson, toml, html or ansi). \
+                    Default is Rust's debug method."
+                )
                 .multiple(false)
                 .default_value("debug")
         )
+        .arg(
+            Arg::with_name("comment_char")
+                .value_name("COMMENT_CHAR")
+                .long("comment-char")
+                .help("The character that starts a comment. Default is '#'.")
+                .multiple(false)
+        )
+        .arg(
+            Arg::with_name("unicode_identifiers")
+                .long("unicode-identifiers")
+                .help("Allow non-ASCII letters and digits in identifiers.")
+                .multiple(false)
+        )
+        .arg(
+            Arg::with_name("tab_width")
+                .value_name("TAB_WIDTH")
+                .long("tab-width")
+                .help("The number of columns a tab takes up. Default is 8.")
+                .multiple(false)
+        )
         .get_matches();
+    let mut options = TokenizerOptions::new();
+    if let Some(comment_char) = matches.value_of("comment_char") {
+        let mut units = comment_char.chars();
+        match (units.next(), units.next()) {
+            (Some(unit), None) => options = options.with_comment_char(unit),
+            _ => {
+                eprintln!("The comment character must be exactly 1 character long.");
+                exit(1);
+            }
+        }
+    }
+    if matches.is_present("unicode_identifiers") {
+        options = options.with_identifier_rules(IdentifierRules::Unicode);
+    }
+    if let Some(tab_width) = matches.value_of("tab_width") {
+        options = options.with_tab_width(tab_width.parse().expect("Invalid tab width."));
+    }
     let output_format = OutputFormats::from_string(matches.value_of("output_format").unwrap())
         .expect("Invalid output format.");
-    let file = match matches.value_of("input_file") {
-        Some(path) => FileStream::try_from(&*PathBuf::from(path)).unwrap(),
-        None => FileStream::default()
-    };
-    let tokenizer = Tokenizer::new();
-    let mut token_iterator = TokenIterator::new(file, tokenizer);
+    let input_file = matches.value_of("input_file");
     match output_format {
-        OutputFormats::Debug =>
+        OutputFormats::Debug => {
+            let mut token_iterator = open_token_iterator(input_file, options);
             while !token_iterator.eof_reached() {
                 match token_iterator.next_token() {
                     Ok(token) => println!("{:?}", token),
@@ -80,14 +135,80 @@ fn main() {
                         exit(1);
                     }
                 }
-            },
+            }
+        },
         OutputFormats::Json => {
+            let token_iterator = open_token_iterator(input_file, options);
             let itok = LexerSerializer::new(token_iterator);
-            println!("{}", serde_json::to_string_pretty(&itok).unwrap());
+            match serde_json::to_string_pretty(&itok) {
+                Ok(json) => println!("{}", json),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            }
+        },
+        OutputFormats::Ndjson => {
+            let mut token_iterator = open_token_iterator(input_file, options);
+            let stdout = stdout();
+            let mut handle = stdout.lock();
+            while !token_iterator.eof_reached() {
+                match token_iterator.next_token() {
+                    Ok(token) => {
+                        writeln!(handle, "{}", serde_json::to_string(&token).unwrap()).unwrap();
+                        handle.flush().unwrap();
+                    },
+                    Err(error) => {
+                        eprintln!("{}", error);
+                        exit(1);
+                    }
+                }
+            }
         },
         OutputFormats::Toml => {
-            let itok = LexerSerializer::new(token_iterator);
-            println!("{}", toml::to_string_pretty(&itok).unwrap());
+            // Tables must be written after plain values in TOML, which
+            // `toml::Value` takes care of by sorting the keys.
+            let token_iterator = open_token_iterator(input_file, options);
+            let table = match TokenTable::try_from(token_iterator) {
+                Ok(table) => table,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            };
+            let value = toml::Value::try_from(&table).unwrap();
+            println!("{}", toml::to_string_pretty(&value).unwrap());
+        },
+        OutputFormats::Highlight(format) => {
+            // The original source is needed to preserve comments and
+            // whitespace.
+            let source = match input_file {
+                Some(path) => read_to_string(path).unwrap(),
+                None => {
+                    let mut source = String::new();
+                    stdin().read_to_string(&mut source).unwrap();
+                    source
+                }
+            };
+            match highlight_source_with(&source, format, options) {
+                Ok(output) => print!("{}", output),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            }
         }
     }
 }
+
+/// Tokenize the input file, or standard input if there is none.
+fn open_token_iterator<'a>(
+    input_file: Option<&str>,
+    options: TokenizerOptions
+) -> TokenIterator<'a> {
+    let file = match input_file {
+        Some(path) => FileStream::try_from(&*PathBuf::from(path)).unwrap(),
+        None => FileStream::default()
+    };
+    TokenIterator::new(file, options.build())
+}
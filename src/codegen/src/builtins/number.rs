@@ -1,12 +1,13 @@
 use std::{
     cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd},
+    convert::TryFrom,
     fmt,
-    ops::{Add, Div, Mul, Sub}
+    ops::{Add, Div, Mul, Neg, Not, Rem, Sub}
 };
 
 use inkwell::{
     types::StructType,
-    values::{AggregateValue, BasicValue, BasicValueEnum, FloatValue, IntValue, StructValue},
+    values::{BasicValue, BasicValueEnum, FloatValue, FunctionValue, IntValue, StructValue},
     FloatPredicate,
     IntPredicate
 };
@@ -18,6 +19,13 @@ use crate::{
 
 pub const NUM_TYPE_NAME: &'static str = "num";
 
+/// The name of the helper function generated to raise an integer to an
+/// integer power.
+pub const INT_POW_FUNCTION_NAME: &str = "__int_pow__";
+
+/// The name of the LLVM intrinsic used to raise a float to a float power.
+pub const FLOAT_POW_INTRINSIC_NAME: &str = "llvm.pow.f64";
+
 pub fn make_number_type<'ctx>(code_gen: &CodeGen<'ctx>) -> StructType<'ctx> {
     let int_type = code_gen.get_int_type().into();
     let float_type = code_gen.get_float_type().into();
@@ -84,6 +92,27 @@ impl_int_math!(add_ints, build_int_add, "add_tmp_int");
 impl_int_math!(sub_ints, build_int_sub, "sub_tmp_int");
 impl_int_math!(mul_ints, build_int_mul, "mul_tmp_int");
 impl_int_math!(div_ints, build_int_signed_div, "div_tmp_int");
+impl_int_math!(rem_ints, build_int_signed_rem, "rem_tmp_int");
+impl_int_math!(and_ints, build_and, "and_tmp_int");
+impl_int_math!(or_ints, build_or, "or_tmp_int");
+impl_int_math!(shl_ints, build_left_shift, "shl_tmp_int");
+
+
+/// Shift `left` to the right by `right` bits, keeping the sign of `left`.
+pub fn shr_ints<'ctx>(
+    left: IntValue<'ctx>,
+    right: IntValue<'ctx>,
+    code_gen: &CodeGen<'ctx>
+) -> Result<IntValue<'ctx>> {
+    if check_int_types(left, right) {
+        Ok(code_gen
+            .get_inner()
+            .get_builder()
+            .build_right_shift(left, right, true, "shr_tmp_int"))
+    } else {
+        Err(make_bit_width_error(left, right))
+    }
+}
 
 
 macro_rules! impl_int_cmp {
@@ -111,6 +140,7 @@ impl_int_cmp!(cmp_le_ints, IntPredicate::SLE, "cmp_le_tmp_int");
 impl_int_cmp!(cmp_eq_ints, IntPredicate::EQ, "cmp_eq_tmp_int");
 impl_int_cmp!(cmp_ge_ints, IntPredicate::SGE, "cmp_ge_tmp_int");
 impl_int_cmp!(cmp_gt_ints, IntPredicate::SGT, "cmp_gt_tmp_int");
+impl_int_cmp!(cmp_ne_ints, IntPredicate::NE, "cmp_ne_tmp_int");
 
 
 macro_rules! impl_float_math {
@@ -137,6 +167,220 @@ impl_float_math!(add_floats, build_float_add, "add_tmp_float");
 impl_float_math!(sub_floats, build_float_sub, "sub_tmp_float");
 impl_float_math!(mul_floats, build_float_mul, "mul_tmp_float");
 impl_float_math!(div_floats, build_float_div, "div_tmp_float");
+impl_float_math!(rem_floats, build_float_rem, "rem_tmp_float");
+
+
+/// Get the function that raises an integer to an integer power, generating
+/// it in the module the first time it is needed. The power is calculated by
+/// repeated multiplication, so the exponent must not be negative
+/// ([`NumValue::pow`] uses floats for negative exponents instead).
+fn get_int_pow_function<'ctx>(code_gen: &CodeGen<'ctx>) -> FunctionValue<'ctx> {
+    if let Some(function) = code_gen
+        .get_inner()
+        .get_module()
+        .get_function(INT_POW_FUNCTION_NAME)
+    {
+        return function;
+    }
+    let context = code_gen.get_context();
+    let int_type = code_gen.get_int_type();
+    let fn_type = int_type.fn_type(&[int_type.into(), int_type.into()], false);
+    let function =
+        code_gen
+            .get_inner()
+            .get_module()
+            .add_function(INT_POW_FUNCTION_NAME, fn_type, None);
+    let base = function.get_nth_param(0).unwrap().into_int_value();
+    let exponent = function.get_nth_param(1).unwrap().into_int_value();
+
+    // Remember where the builder was, since this function may be generated
+    // in the middle of another one.
+    let previous_block = code_gen.get_inner().get_builder().get_insert_block();
+    let entry_block = context.append_basic_block(function, "entry");
+    let loop_block = context.append_basic_block(function, "loop");
+    let body_block = context.append_basic_block(function, "body");
+    let done_block = context.append_basic_block(function, "done");
+    let one = code_gen.make_i128(1);
+    let zero = code_gen.make_i128(0);
+
+    code_gen
+        .get_inner()
+        .get_builder()
+        .position_at_end(entry_block);
+    code_gen
+        .get_inner()
+        .get_builder()
+        .build_unconditional_branch(loop_block);
+
+    code_gen
+        .get_inner()
+        .get_builder()
+        .position_at_end(loop_block);
+    let result = code_gen
+        .get_inner()
+        .get_builder()
+        .build_phi(int_type, "pow_result");
+    let counter = code_gen
+        .get_inner()
+        .get_builder()
+        .build_phi(int_type, "pow_counter");
+    let result_value = result.as_basic_value().into_int_value();
+    let counter_value = counter.as_basic_value().into_int_value();
+    let is_positive = cmp_gt_ints(counter_value, zero, code_gen).unwrap();
+    code_gen.get_inner().get_builder().build_conditional_branch(
+        is_positive,
+        body_block,
+        done_block
+    );
+
+    code_gen
+        .get_inner()
+        .get_builder()
+        .position_at_end(body_block);
+    let next_result = mul_ints(result_value, base, code_gen).unwrap();
+    let next_counter = sub_ints(counter_value, one, code_gen).unwrap();
+    code_gen
+        .get_inner()
+        .get_builder()
+        .build_unconditional_branch(loop_block);
+    result.add_incoming(&[(&one, entry_block), (&next_result, body_block)]);
+    counter.add_incoming(&[(&exponent, entry_block), (&next_counter, body_block)]);
+
+    code_gen
+        .get_inner()
+        .get_builder()
+        .position_at_end(done_block);
+    code_gen
+        .get_inner()
+        .get_builder()
+        .build_return(Some(&result_value));
+
+    if let Some(block) = previous_block {
+        code_gen.get_inner().get_builder().position_at_end(block);
+    }
+    function
+}
+
+
+/// Get the `llvm.pow` intrinsic for 64-bit floats, declaring it in the
+/// module the first time it is needed.
+fn get_float_pow_intrinsic<'ctx>(code_gen: &CodeGen<'ctx>) -> FunctionValue<'ctx> {
+    if let Some(function) = code_gen
+        .get_inner()
+        .get_module()
+        .get_function(FLOAT_POW_INTRINSIC_NAME)
+    {
+        return function;
+    }
+    let float_type = code_gen.get_float_type();
+    let fn_type = float_type.fn_type(&[float_type.into(), float_type.into()], false);
+    code_gen
+        .get_inner()
+        .get_module()
+        .add_function(FLOAT_POW_INTRINSIC_NAME, fn_type, None)
+}
+
+
+pub fn pow_ints<'ctx>(
+    left: IntValue<'ctx>,
+    right: IntValue<'ctx>,
+    code_gen: &CodeGen<'ctx>
+) -> Result<IntValue<'ctx>> {
+    if !check_int_types(left, right) {
+        return Err(make_bit_width_error(left, right));
+    }
+    // Constants are raised to a power right away, so that the result is a
+    // constant too.
+    if let (Some(base), Some(exponent)) = (
+        get_known_small_int(left, code_gen),
+        get_known_small_int(right, code_gen)
+    ) {
+        if let Ok(exponent) = u32::try_from(exponent) {
+            return Ok(code_gen.make_i128((base as i128).wrapping_pow(exponent)));
+        }
+    }
+    let function = get_int_pow_function(code_gen);
+    code_gen
+        .get_inner()
+        .get_builder()
+        .build_call(function, &[left.into(), right.into()], "pow_tmp_int")
+        .try_as_basic_value()
+        .left()
+        .map(|value| value.into_int_value())
+        .ok_or_else(|| {
+            Error::new(
+                format!("{} did not return a value", INT_POW_FUNCTION_NAME),
+                ErrorKind::NotBasicValueError,
+                None
+            )
+        })
+}
+
+
+pub fn pow_floats<'ctx>(
+    left: FloatValue<'ctx>,
+    right: FloatValue<'ctx>,
+    code_gen: &CodeGen<'ctx>
+) -> Result<FloatValue<'ctx>> {
+    if !check_float_formats(left, right) {
+        return Err(make_float_format_error(left, right));
+    }
+    if let (Some((base, _)), Some((exponent, _))) = (left.get_constant(), right.get_constant()) {
+        return Ok(code_gen.make_f64(base.powf(exponent)));
+    }
+    let function = get_float_pow_intrinsic(code_gen);
+    code_gen
+        .get_inner()
+        .get_builder()
+        .build_call(function, &[left.into(), right.into()], "pow_tmp_float")
+        .try_as_basic_value()
+        .left()
+        .map(|value| value.into_float_value())
+        .ok_or_else(|| {
+            Error::new(
+                format!("{} did not return a value", FLOAT_POW_INTRINSIC_NAME),
+                ErrorKind::NotBasicValueError,
+                None
+            )
+        })
+}
+
+
+pub fn neg_int<'ctx>(value: IntValue<'ctx>, code_gen: &CodeGen<'ctx>) -> IntValue<'ctx> {
+    code_gen
+        .get_inner()
+        .get_builder()
+        .build_int_neg(value, "neg_tmp_int")
+}
+
+
+pub fn bitwise_not_int<'ctx>(value: IntValue<'ctx>, code_gen: &CodeGen<'ctx>) -> IntValue<'ctx> {
+    code_gen
+        .get_inner()
+        .get_builder()
+        .build_not(value, "bitwise_not_tmp_int")
+}
+
+
+/// Extend a 1-bit boolean into an integer that is either `0` or `1`.
+pub fn bool_to_int<'ctx>(value: IntValue<'ctx>, code_gen: &CodeGen<'ctx>) -> IntValue<'ctx> {
+    let int_type = code_gen.get_int_type();
+    code_gen
+        .get_inner()
+        .get_builder()
+        .build_int_z_extend(value, int_type, "bool_tmp_int")
+}
+
+
+pub fn not_int<'ctx>(value: IntValue<'ctx>, code_gen: &CodeGen<'ctx>) -> Result<IntValue<'ctx>> {
+    let zero = code_gen.make_i128(0);
+    let is_zero = cmp_eq_ints(value, zero, code_gen)?;
+    let int_type = code_gen.get_int_type();
+    Ok(code_gen
+        .get_inner()
+        .get_builder()
+        .build_int_z_extend(is_zero, int_type, "not_tmp_int"))
+}
 
 
 macro_rules! impl_float_cmp {
@@ -164,6 +408,48 @@ impl_float_cmp!(cmp_le_floats, FloatPredicate::OLE, "cmp_le_tmp_float");
 impl_float_cmp!(cmp_eq_floats, FloatPredicate::OEQ, "cmp_eq_tmp_float");
 impl_float_cmp!(cmp_ge_floats, FloatPredicate::OGE, "cmp_ge_tmp_float");
 impl_float_cmp!(cmp_gt_floats, FloatPredicate::OGT, "cmp_gt_tmp_float");
+impl_float_cmp!(cmp_ne_floats, FloatPredicate::UNE, "cmp_ne_tmp_float");
+
+
+pub fn neg_float<'ctx>(value: FloatValue<'ctx>, code_gen: &CodeGen<'ctx>) -> FloatValue<'ctx> {
+    code_gen
+        .get_inner()
+        .get_builder()
+        .build_float_neg(value, "neg_tmp_float")
+}
+
+
+pub fn not_float<'ctx>(
+    value: FloatValue<'ctx>,
+    code_gen: &CodeGen<'ctx>
+) -> Result<IntValue<'ctx>> {
+    let zero = code_gen.make_f64(0.0);
+    let is_zero = cmp_eq_floats(value, zero, code_gen)?;
+    let int_type = code_gen.get_int_type();
+    Ok(code_gen
+        .get_inner()
+        .get_builder()
+        .build_int_z_extend(is_zero, int_type, "not_tmp_float"))
+}
+
+
+/// Get the value of a 1-bit boolean if it is a constant, which means it is
+/// already known while generating code.
+fn get_known_bool(value: IntValue<'_>) -> Option<bool> {
+    value.get_zero_extended_constant().map(|value| value != 0)
+}
+
+/// Get the value of a constant integer that fits in 64 bits.
+fn get_known_small_int<'ctx>(value: IntValue<'ctx>, code_gen: &CodeGen<'ctx>) -> Option<i64> {
+    if !value.is_constant_int() {
+        return None;
+    }
+    let small = value.const_truncate(code_gen.get_context().i64_type());
+    if small.const_s_extend(value.get_type()) != value {
+        return None;
+    }
+    small.get_sign_extended_constant()
+}
 
 
 pub struct NumValue<'ctx> {
@@ -199,84 +485,208 @@ impl<'ctx> NumValue<'ctx> {
         }
     }
 
-    fn make_true(&self) -> IntValue<'ctx> {
-        self.code_gen.make_bool(true)
-    }
-
-    #[allow(unused)]
-    fn make_false(&self) -> IntValue<'ctx> {
-        self.code_gen.make_bool(false)
+    fn extract_field(&self, index: u32, name: &str) -> BasicValueEnum<'ctx> {
+        self.code_gen
+            .get_inner()
+            .get_builder()
+            .build_extract_value(self.value, index, name)
+            .unwrap()
     }
 
-    pub fn destructure(&self) -> [BasicValueEnum; 3] {
+    pub fn destructure(&self) -> [BasicValueEnum<'ctx>; 3] {
         [
-            self.value.const_extract_value(&mut [0]),
-            self.value.const_extract_value(&mut [1]),
-            self.value.const_extract_value(&mut [2])
+            self.extract_field(0, "int_field"),
+            self.extract_field(1, "float_field"),
+            self.extract_field(2, "int_switch")
         ]
     }
 
+    /// Get the 1-bit boolean which is true if this number is an integer.
     pub fn get_int_switch(&self) -> IntValue<'ctx> {
-        self.value.const_extract_value(&mut [2]).into_int_value()
+        self.extract_field(2, "int_switch").into_int_value()
     }
 
-    pub fn is_int(&self) -> bool {
-        self.get_int_switch() == self.make_true()
+    /// Check if this number is an integer. [`None`] is returned if this is
+    /// only known at runtime, such as when the number was loaded from a
+    /// variable.
+    pub fn is_int(&self) -> Option<bool> {
+        get_known_bool(self.get_int_switch())
     }
 
-    pub fn is_float(&self) -> bool {
-        !self.is_int()
+    /// Check if this number is a float. [`None`] is returned if this is only
+    /// known at runtime.
+    pub fn is_float(&self) -> Option<bool> {
+        self.is_int().map(|is_int| !is_int)
     }
 
     pub fn get_raw_int_value(&self) -> IntValue<'ctx> {
-        self.value.const_extract_value(&mut [0]).into_int_value()
+        self.extract_field(0, "int_field").into_int_value()
     }
 
     pub fn get_raw_float_value(&self) -> FloatValue<'ctx> {
-        self.value.const_extract_value(&mut [1]).into_float_value()
+        self.extract_field(1, "float_field").into_float_value()
+    }
+
+    /// Check if this number is a constant.
+    pub fn is_const(&self) -> bool {
+        self.get_int_switch().is_const()
+            && self.get_raw_int_value().is_const()
+            && self.get_raw_float_value().is_const()
+    }
+
+    /// Get the 1-bit boolean which is true if both this number and `rhs` are
+    /// integers.
+    fn get_both_int_switch(&self, rhs: &Self) -> IntValue<'ctx> {
+        match (self.is_int(), rhs.is_int()) {
+            (Some(false), _) | (_, Some(false)) => self.code_gen.make_bool(false),
+            (Some(true), Some(true)) => self.code_gen.make_bool(true),
+            _ => {
+                let left = self.get_int_switch();
+                let right = rhs.get_int_switch();
+                self.code_gen
+                    .get_inner()
+                    .get_builder()
+                    .build_and(left, right, "both_int_tmp")
+            }
+        }
+    }
+
+    /// Generate the value given by `int_path` if `is_int` is true and the
+    /// value given by `float_path` if it is false. If `is_int` is a constant,
+    /// only one path is generated. Otherwise, both paths are generated in
+    /// their own blocks, and the result is picked at runtime with a phi node.
+    fn build_type_switch<F, G>(
+        &self,
+        is_int: IntValue<'ctx>,
+        int_path: F,
+        float_path: G
+    ) -> Result<BasicValueEnum<'ctx>>
+    where
+        F: FnOnce() -> Result<BasicValueEnum<'ctx>>,
+        G: FnOnce() -> Result<BasicValueEnum<'ctx>>
+    {
+        match get_known_bool(is_int) {
+            Some(true) => return int_path(),
+            Some(false) => return float_path(),
+            None => {}
+        }
+        let int_block = self.code_gen.append_block("num_int")?;
+        let float_block = self.code_gen.append_block("num_float")?;
+        let merge_block = self.code_gen.append_block("num_merge")?;
+        self.code_gen
+            .get_inner()
+            .get_builder()
+            .build_conditional_branch(is_int, int_block, float_block);
+
+        self.code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(int_block);
+        let int_value = int_path()?;
+        let int_end_block = self.code_gen.get_current_block()?;
+        self.code_gen
+            .get_inner()
+            .get_builder()
+            .build_unconditional_branch(merge_block);
+
+        self.code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(float_block);
+        let float_value = float_path()?;
+        let float_end_block = self.code_gen.get_current_block()?;
+        self.code_gen
+            .get_inner()
+            .get_builder()
+            .build_unconditional_branch(merge_block);
+
+        self.code_gen
+            .get_inner()
+            .get_builder()
+            .position_at_end(merge_block);
+        let phi = self
+            .code_gen
+            .get_inner()
+            .get_builder()
+            .build_phi(int_value.get_type(), "num_tmp");
+        phi.add_incoming(&[(&int_value, int_end_block), (&float_value, float_end_block)]);
+        Ok(phi.as_basic_value())
     }
 
     pub fn to_float(&self) -> Result<Self> {
-        let float = if self.is_float() {
-            self.code_gen.copy_float(self.get_raw_float_value())
-        } else {
-            self.code_gen.int_to_float(self.get_raw_int_value())
+        let float = match self.is_int() {
+            Some(true) => self.code_gen.int_to_float(self.get_raw_int_value()),
+            Some(false) => self.get_raw_float_value(),
+            None => {
+                let is_int = self.get_int_switch();
+                let converted = self.code_gen.int_to_float(self.get_raw_int_value());
+                let float = self.get_raw_float_value();
+                self.code_gen
+                    .get_inner()
+                    .get_builder()
+                    .build_select(is_int, converted, float, "to_float_tmp")
+                    .into_float_value()
+            }
         };
         let raw = self.code_gen.make_num_from_float(float)?;
         Self::new(raw, self.code_gen.clone())
     }
 
     pub fn to_int(&self) -> Result<Self> {
-        let integer = if self.is_int() {
-            self.code_gen.copy_int(self.get_raw_int_value())
-        } else {
-            self.code_gen.float_to_int(self.get_raw_float_value())
+        let integer = match self.is_int() {
+            Some(true) => self.get_raw_int_value(),
+            Some(false) => self.code_gen.float_to_int(self.get_raw_float_value()),
+            None => {
+                let is_int = self.get_int_switch();
+                let integer = self.get_raw_int_value();
+                let converted = self.code_gen.float_to_int(self.get_raw_float_value());
+                self.code_gen
+                    .get_inner()
+                    .get_builder()
+                    .build_select(is_int, integer, converted, "to_int_tmp")
+                    .into_int_value()
+            }
         };
         let raw = self.code_gen.make_num_from_int(integer)?;
         Self::new(raw, self.code_gen.clone())
     }
 
     pub fn cast_to_same_type_as(&self, other: &Self) -> Result<Self> {
-        if other.is_int() {
-            self.to_int()
-        } else {
-            self.to_float()
+        match other.is_int() {
+            Some(true) => self.to_int(),
+            Some(false) => self.to_float(),
+            None => {
+                let is_int = other.get_int_switch();
+                let integer: StructValue = self.to_int()?.into();
+                let float: StructValue = self.to_float()?.into();
+                let raw = self
+                    .code_gen
+                    .get_inner()
+                    .get_builder()
+                    .build_select(is_int, integer, float, "cast_tmp")
+                    .into_struct_value();
+                Self::new(raw, self.code_gen.clone())
+            }
         }
     }
 
+    /// Get the integer or float inside this number. If the type of the
+    /// number is only known at runtime, the number itself is returned.
     pub fn simplify_to_basic_value(&self) -> Box<dyn BasicValue<'ctx> + 'ctx> {
-        if self.is_int() {
-            Box::new(self.get_raw_int_value())
-        } else {
-            Box::new(self.get_raw_float_value())
+        match self.is_int() {
+            Some(true) => Box::new(self.get_raw_int_value()),
+            Some(false) => Box::new(self.get_raw_float_value()),
+            None => Box::new(self.value)
         }
     }
 
+    /// Get the integer or float inside this number. If the type of the
+    /// number is only known at runtime, the number itself is returned.
     pub fn simplify_to_basic_value_enum(&self) -> BasicValueEnum<'ctx> {
-        if self.is_int() {
-            self.get_raw_int_value().as_basic_value_enum()
-        } else {
-            self.get_raw_float_value().as_basic_value_enum()
+        match self.is_int() {
+            Some(true) => self.get_raw_int_value().as_basic_value_enum(),
+            Some(false) => self.get_raw_float_value().as_basic_value_enum(),
+            None => self.value.as_basic_value_enum()
         }
     }
 }
@@ -296,32 +706,64 @@ impl<'ctx> fmt::Display for NumValue<'ctx> {
 }
 
 
+impl<'ctx> NumValue<'ctx> {
+    /// Generate an operation on two numbers. If both numbers are integers,
+    /// `int_op` is used, otherwise both numbers are converted to floats and
+    /// `float_op` is used. The result is a number.
+    fn build_arithmetic<F, G>(&self, rhs: &Self, int_op: F, float_op: G) -> Result<Self>
+    where
+        F: FnOnce(IntValue<'ctx>, IntValue<'ctx>, &CodeGen<'ctx>) -> Result<IntValue<'ctx>>,
+        G: FnOnce(FloatValue<'ctx>, FloatValue<'ctx>, &CodeGen<'ctx>) -> Result<FloatValue<'ctx>>
+    {
+        self.build_arithmetic_if(self.get_both_int_switch(rhs), rhs, int_op, float_op)
+    }
+
+    /// Like [`NumValue::build_arithmetic`], but `int_op` is only used if
+    /// `is_int` is true, and both numbers are converted to floats otherwise.
+    fn build_arithmetic_if<F, G>(
+        &self,
+        is_int: IntValue<'ctx>,
+        rhs: &Self,
+        int_op: F,
+        float_op: G
+    ) -> Result<Self>
+    where
+        F: FnOnce(IntValue<'ctx>, IntValue<'ctx>, &CodeGen<'ctx>) -> Result<IntValue<'ctx>>,
+        G: FnOnce(FloatValue<'ctx>, FloatValue<'ctx>, &CodeGen<'ctx>) -> Result<FloatValue<'ctx>>
+    {
+        let raw = self.build_type_switch(
+            is_int,
+            || {
+                let result = int_op(
+                    self.get_raw_int_value(),
+                    rhs.get_raw_int_value(),
+                    &self.code_gen
+                )?;
+                Ok(self.code_gen.make_num_from_int(result)?.into())
+            },
+            || {
+                let left = self.to_float()?;
+                let right = rhs.to_float()?;
+                let result = float_op(
+                    left.get_raw_float_value(),
+                    right.get_raw_float_value(),
+                    &self.code_gen
+                )?;
+                Ok(self.code_gen.make_num_from_float(result)?.into())
+            }
+        )?;
+        NumValue::new(raw.into_struct_value(), self.code_gen.clone())
+    }
+}
+
+
 macro_rules! impl_binop_for_numvalue {
     ($trait_name: ident, $fn_name: ident, $int_op: ident, $float_op: ident) => {
         impl<'ctx> $trait_name for &NumValue<'ctx> {
             type Output = NumValue<'ctx>;
 
             fn $fn_name(self, rhs: Self) -> Self::Output {
-                let raw = if self.is_int() && rhs.is_int() {
-                    let result = $int_op(
-                        self.get_raw_int_value(),
-                        rhs.get_raw_int_value(),
-                        &self.code_gen
-                    )
-                    .unwrap();
-                    self.code_gen.make_num_from_int(result).unwrap()
-                } else {
-                    let left = self.to_float().unwrap();
-                    let right = rhs.to_float().unwrap();
-                    let result = $float_op(
-                        left.get_raw_float_value(),
-                        right.get_raw_float_value(),
-                        &self.code_gen
-                    )
-                    .unwrap();
-                    self.code_gen.make_num_from_float(result).unwrap()
-                };
-                NumValue::new(raw, self.code_gen.clone()).unwrap()
+                self.build_arithmetic(rhs, $int_op, $float_op).unwrap()
             }
         }
     };
@@ -333,27 +775,293 @@ impl_binop_for_numvalue!(Mul, mul, mul_ints, mul_floats);
 impl_binop_for_numvalue!(Div, div, div_ints, div_floats);
 
 
+impl_binop_for_numvalue!(Rem, rem, rem_ints, rem_floats);
+
+
+/// Make the error returned when an operator that only works on integers is
+/// used on a float.
+fn make_int_only_error(operator: &str) -> Error {
+    Error::new(
+        format!(
+            "Operator '{}' can only be used on integers, not floats",
+            operator
+        ),
+        ErrorKind::TypeError,
+        None
+    )
+}
+
+
+impl<'ctx> NumValue<'ctx> {
+    /// Get this number as an integer for an operator that only works on
+    /// integers. If the number is known to be a float while generating
+    /// code, a [`ErrorKind::TypeError`] is returned. If it is only known at
+    /// runtime, there is no way to report an error, so a float is truncated
+    /// towards zero instead (`f(1.5)` gives `1` for `def f(a) a & 1;`).
+    fn get_int_operand(&self, operator: &str) -> Result<IntValue<'ctx>> {
+        match self.is_int() {
+            Some(true) => Ok(self.get_raw_int_value()),
+            Some(false) => Err(make_int_only_error(operator)),
+            None => Ok(self.to_int()?.get_raw_int_value())
+        }
+    }
+}
+
+
+// Operators that only make sense on integers, which give a
+// `ErrorKind::TypeError` if either number is a float.
+macro_rules! impl_int_only_binop_for_numvalue {
+    ($fn_name: ident, $int_op: ident, $operator: expr) => {
+        impl<'ctx> NumValue<'ctx> {
+            pub fn $fn_name(&self, rhs: &Self) -> Result<Self> {
+                let left = self.get_int_operand($operator)?;
+                let right = rhs.get_int_operand($operator)?;
+                let result = $int_op(left, right, &self.code_gen)?;
+                let raw = self.code_gen.make_num_from_int(result)?;
+                NumValue::new(raw, self.code_gen.clone())
+            }
+        }
+    };
+}
+
+impl_int_only_binop_for_numvalue!(bitwise_and, and_ints, "&");
+impl_int_only_binop_for_numvalue!(bitwise_or, or_ints, "|");
+impl_int_only_binop_for_numvalue!(shift_left, shl_ints, "<<");
+impl_int_only_binop_for_numvalue!(shift_right, shr_ints, ">>");
+
+
+// Comparisons, which give an integer `1` if the comparison is true and `0`
+// otherwise.
+macro_rules! impl_cmp_for_numvalue {
+    ($fn_name: ident, $int_cmp: ident, $float_cmp: ident, $operator: expr) => {
+        impl<'ctx> NumValue<'ctx> {
+            pub fn $fn_name(&self, rhs: &Self) -> Result<Self> {
+                let is_true = self.build_type_switch(
+                    self.get_both_int_switch(rhs),
+                    || {
+                        Ok($int_cmp(
+                            self.get_raw_int_value(),
+                            rhs.get_raw_int_value(),
+                            &self.code_gen
+                        )?
+                        .into())
+                    },
+                    || {
+                        let left = self.to_float()?;
+                        let right = rhs.to_float()?;
+                        Ok($float_cmp(
+                            left.get_raw_float_value(),
+                            right.get_raw_float_value(),
+                            &self.code_gen
+                        )?
+                        .into())
+                    }
+                )?;
+                let result = bool_to_int(is_true.into_int_value(), &self.code_gen);
+                let raw = self.code_gen.make_num_from_int(result)?;
+                NumValue::new(raw, self.code_gen.clone())
+            }
+        }
+    };
+}
+
+impl_cmp_for_numvalue!(less_than, cmp_lt_ints, cmp_lt_floats, "<");
+impl_cmp_for_numvalue!(less_than_equal, cmp_le_ints, cmp_le_floats, "<=");
+impl_cmp_for_numvalue!(equals, cmp_eq_ints, cmp_eq_floats, "==");
+impl_cmp_for_numvalue!(not_equals, cmp_ne_ints, cmp_ne_floats, "!=");
+impl_cmp_for_numvalue!(greater_than_equal, cmp_ge_ints, cmp_ge_floats, ">=");
+impl_cmp_for_numvalue!(greater_than, cmp_gt_ints, cmp_gt_floats, ">");
+
+
+impl<'ctx> NumValue<'ctx> {
+    /// Flip every bit of an integer. A [`ErrorKind::TypeError`] is returned
+    /// if the number is known to be a float.
+    pub fn bitwise_not(&self) -> Result<Self> {
+        let operand = self.get_int_operand("~")?;
+        let result = bitwise_not_int(operand, &self.code_gen);
+        let raw = self.code_gen.make_num_from_int(result)?;
+        NumValue::new(raw, self.code_gen.clone())
+    }
+
+    /// Check if this number counts as true, which is the case for any
+    /// integer or float other than zero. The result is a 1-bit boolean.
+    pub fn is_truthy(&self) -> Result<IntValue<'ctx>> {
+        let is_true = self.build_type_switch(
+            self.get_int_switch(),
+            || {
+                let zero = self.code_gen.make_i128(0);
+                Ok(cmp_ne_ints(self.get_raw_int_value(), zero, &self.code_gen)?.into())
+            },
+            || {
+                let zero = self.code_gen.make_f64(0.0);
+                Ok(cmp_ne_floats(self.get_raw_float_value(), zero, &self.code_gen)?.into())
+            }
+        )?;
+        Ok(is_true.into_int_value())
+    }
+
+    /// Short-circuiting logical and. `rhs` generates the right-hand side,
+    /// and is only evaluated at runtime if this number is true. The result is
+    /// an integer `1` if both sides are true and `0` otherwise.
+    pub fn logical_and<F>(&self, rhs: F) -> Result<Self>
+    where
+        F: FnOnce() -> Result<Self>
+    {
+        self.build_short_circuit(true, rhs)
+    }
+
+    /// Short-circuiting logical or. `rhs` generates the right-hand side, and
+    /// is only evaluated at runtime if this number is false. The result is an
+    /// integer `1` if either side is true and `0` otherwise.
+    pub fn logical_or<F>(&self, rhs: F) -> Result<Self>
+    where
+        F: FnOnce() -> Result<Self>
+    {
+        self.build_short_circuit(false, rhs)
+    }
+
+    /// Branch to the code generated by `rhs` only if it can change the result
+    /// of the logical operator, then merge both paths with a phi node. If
+    /// both sides are constants, so is the result.
+    fn build_short_circuit<F>(&self, is_and: bool, rhs: F) -> Result<Self>
+    where
+        F: FnOnce() -> Result<Self>
+    {
+        let left = self.is_truthy()?;
+        // If the left-hand side is a constant, it either decides the result
+        // by itself or the result is whether the right-hand side is true, so
+        // no branches are needed.
+        let result = match get_known_bool(left) {
+            Some(known) if known != is_and => left,
+            Some(_) => rhs()?.is_truthy()?,
+            None => {
+                // Checking if the left-hand side is true may have moved the builder
+                // to another block.
+                let left_block = self.code_gen.get_current_block()?;
+                let rhs_block = self.code_gen.append_block("logical_rhs")?;
+                let merge_block = self.code_gen.append_block("logical_merge")?;
+                let (then_block, else_block) = if is_and {
+                    (rhs_block, merge_block)
+                } else {
+                    (merge_block, rhs_block)
+                };
+                self.code_gen
+                    .get_inner()
+                    .get_builder()
+                    .build_conditional_branch(left, then_block, else_block);
+
+                self.code_gen
+                    .get_inner()
+                    .get_builder()
+                    .position_at_end(rhs_block);
+                let right = rhs()?.is_truthy()?;
+                // Generating the right-hand side may have moved the builder to
+                // another block.
+                let right_block = self.code_gen.get_current_block()?;
+                self.code_gen
+                    .get_inner()
+                    .get_builder()
+                    .build_unconditional_branch(merge_block);
+
+                self.code_gen
+                    .get_inner()
+                    .get_builder()
+                    .position_at_end(merge_block);
+                let bool_type = self.code_gen.get_bool_type();
+                let phi = self
+                    .code_gen
+                    .get_inner()
+                    .get_builder()
+                    .build_phi(bool_type, "logical_tmp");
+                phi.add_incoming(&[(&left, left_block), (&right, right_block)]);
+                phi.as_basic_value().into_int_value()
+            }
+        };
+        let result = bool_to_int(result, &self.code_gen);
+        let raw = self.code_gen.make_num_from_int(result)?;
+        NumValue::new(raw, self.code_gen.clone())
+    }
+
+    /// Raise this number to the power of `rhs`. If both numbers are
+    /// integers and `rhs` is not negative, the result is an integer,
+    /// otherwise both are converted to floats first (so `2 ^ -1` is `0.5`).
+    pub fn pow(&self, rhs: &Self) -> Result<Self> {
+        let both_int = self.get_both_int_switch(rhs);
+        let is_int = match get_known_bool(both_int) {
+            Some(false) => both_int,
+            _ => {
+                let zero = self.code_gen.make_i128(0);
+                let is_natural = cmp_ge_ints(rhs.get_raw_int_value(), zero, &self.code_gen)?;
+                self.code_gen.get_inner().get_builder().build_and(
+                    both_int,
+                    is_natural,
+                    "int_pow_tmp"
+                )
+            }
+        };
+        self.build_arithmetic_if(is_int, rhs, pow_ints, pow_floats)
+    }
+
+    /// Compare two numbers while generating code, which is only possible if
+    /// both of them are constants.
+    fn compare_constants<F>(&self, other: &Self, compare: F) -> Option<bool>
+    where
+        F: FnOnce(&Self, &Self) -> Result<Self>
+    {
+        if !(self.is_const() && other.is_const()) {
+            return None;
+        }
+        get_known_bool(compare(self, other).ok()?.is_truthy().ok()?)
+    }
+}
+
+
+impl<'ctx> Neg for &NumValue<'ctx> {
+    type Output = NumValue<'ctx>;
+
+    fn neg(self) -> Self::Output {
+        let raw = self
+            .build_type_switch(
+                self.get_int_switch(),
+                || {
+                    let result = neg_int(self.get_raw_int_value(), &self.code_gen);
+                    Ok(self.code_gen.make_num_from_int(result)?.into())
+                },
+                || {
+                    let result = neg_float(self.get_raw_float_value(), &self.code_gen);
+                    Ok(self.code_gen.make_num_from_float(result)?.into())
+                }
+            )
+            .unwrap();
+        NumValue::new(raw.into_struct_value(), self.code_gen.clone()).unwrap()
+    }
+}
+
+
+/// Logical not, which gives an integer `1` if the value is zero and `0`
+/// otherwise.
+impl<'ctx> Not for &NumValue<'ctx> {
+    type Output = NumValue<'ctx>;
+
+    fn not(self) -> Self::Output {
+        let is_true = self.is_truthy().unwrap();
+        let is_false = self
+            .code_gen
+            .get_inner()
+            .get_builder()
+            .build_not(is_true, "not_tmp");
+        let result = bool_to_int(is_false, &self.code_gen);
+        let raw = self.code_gen.make_num_from_int(result).unwrap();
+        NumValue::new(raw, self.code_gen.clone()).unwrap()
+    }
+}
+
+
+/// Numbers can only be compared while generating code if both are
+/// constants. Otherwise, they are never equal.
 impl<'ctx> PartialEq for NumValue<'ctx> {
     fn eq(&self, other: &Self) -> bool {
-        if self.is_int() && other.is_int() {
-            cmp_eq_ints(
-                self.get_raw_int_value(),
-                other.get_raw_int_value(),
-                &self.code_gen
-            )
-            .unwrap()
-                == self.make_true()
-        } else {
-            let left = self.to_float().unwrap();
-            let right = other.to_float().unwrap();
-            cmp_eq_floats(
-                left.get_raw_float_value(),
-                right.get_raw_float_value(),
-                &self.code_gen
-            )
-            .unwrap()
-                == self.make_true()
-        }
+        self.compare_constants(other, Self::equals) == Some(true)
     }
 }
 
@@ -361,72 +1069,18 @@ impl<'ctx> PartialEq for NumValue<'ctx> {
 impl<'ctx> Eq for NumValue<'ctx> {}
 
 
+/// Numbers can only be ordered while generating code if both are constants.
+/// Otherwise, [`None`] is returned.
 impl<'ctx> PartialOrd for NumValue<'ctx> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        if self.is_int() && other.is_int() {
-            if cmp_lt_ints(
-                self.get_raw_int_value(),
-                other.get_raw_int_value(),
-                &self.code_gen
-            )
-            .ok()?
-                == self.make_true()
-            {
-                Some(Ordering::Less)
-            } else if cmp_eq_ints(
-                self.get_raw_int_value(),
-                other.get_raw_int_value(),
-                &self.code_gen
-            )
-            .ok()?
-                == self.make_true()
-            {
-                Some(Ordering::Equal)
-            } else if cmp_gt_ints(
-                self.get_raw_int_value(),
-                other.get_raw_int_value(),
-                &self.code_gen
-            )
-            .ok()?
-                == self.make_true()
-            {
-                Some(Ordering::Greater)
-            } else {
-                None
-            }
+        if self.compare_constants(other, Self::less_than)? {
+            Some(Ordering::Less)
+        } else if self.compare_constants(other, Self::equals)? {
+            Some(Ordering::Equal)
+        } else if self.compare_constants(other, Self::greater_than)? {
+            Some(Ordering::Greater)
         } else {
-            let left = self.to_float().ok()?;
-            let right = other.to_float().ok()?;
-            if cmp_lt_floats(
-                left.get_raw_float_value(),
-                right.get_raw_float_value(),
-                &self.code_gen
-            )
-            .ok()?
-                == self.make_true()
-            {
-                Some(Ordering::Less)
-            } else if cmp_eq_floats(
-                left.get_raw_float_value(),
-                right.get_raw_float_value(),
-                &self.code_gen
-            )
-            .ok()?
-                == self.make_true()
-            {
-                Some(Ordering::Equal)
-            } else if cmp_gt_floats(
-                left.get_raw_float_value(),
-                right.get_raw_float_value(),
-                &self.code_gen
-            )
-            .ok()?
-                == self.make_true()
-            {
-                Some(Ordering::Greater)
-            } else {
-                None
-            }
+            None
         }
     }
 }
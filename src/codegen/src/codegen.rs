@@ -1,21 +1,24 @@
-use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex, MutexGuard}
-};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use inkwell::{
+    basic_block::BasicBlock,
     builder::Builder,
     context::Context,
     execution_engine::ExecutionEngine,
     module::Module,
+    passes::PassManager,
     types::{FloatType, IntType, StructType},
-    values::{BasicValue, BasicValueEnum, FloatValue, IntValue, StructValue}
+    values::{BasicValueEnum, FloatValue, FunctionValue, IntValue, PointerValue, StructValue}
 };
 
 use crate::{
-    builtins::number::{make_number_type, NUM_TYPE_NAME},
+    builtins::{
+        number::{make_number_type, NUM_TYPE_NAME},
+        NumValue
+    },
     error::{Error, ErrorKind, Result},
     int::To64LLVMWord,
+    scope::{NamedValue, ScopeStack},
     traits::IRRepresentableExpression
 };
 
@@ -30,24 +33,94 @@ pub fn create_code_gen<'ctx>(
     code_gen
 }
 
-pub type NamedValues<'ctx> = HashMap<String, Box<dyn BasicValue<'ctx> + 'ctx>>;
+/// Where `return` sends its value inside a function.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ReturnContext<'ctx> {
+    return_slot: PointerValue<'ctx>,
+    exit_block:  BasicBlock<'ctx>
+}
+
+impl<'ctx> ReturnContext<'ctx> {
+    /// Create a new [`ReturnContext`].
+    pub fn new(return_slot: PointerValue<'ctx>, exit_block: BasicBlock<'ctx>) -> Self {
+        Self {
+            return_slot,
+            exit_block
+        }
+    }
+
+    /// Get the stack slot that the returned value is stored in.
+    pub fn get_return_slot(&self) -> PointerValue<'ctx> {
+        self.return_slot
+    }
+
+    /// Get the block that returns the value in the return slot.
+    pub fn get_exit_block(&self) -> BasicBlock<'ctx> {
+        self.exit_block
+    }
+}
+
+/// The names in scope. Mutable variables are stack slots (pointers to nums)
+/// made with [`CodeGen::create_variable`], while immutable values are used
+/// as they are.
+pub type NamedValues<'ctx> = ScopeStack<'ctx>;
+
+/// The blocks that `continue` and `break` branch to inside a loop.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LoopContext<'ctx> {
+    continue_block: BasicBlock<'ctx>,
+    break_block:    BasicBlock<'ctx>
+}
+
+impl<'ctx> LoopContext<'ctx> {
+    /// Create a new [`LoopContext`].
+    pub fn new(continue_block: BasicBlock<'ctx>, break_block: BasicBlock<'ctx>) -> Self {
+        Self {
+            continue_block,
+            break_block
+        }
+    }
+
+    /// Get the block that `continue` branches to.
+    pub fn get_continue_block(&self) -> BasicBlock<'ctx> {
+        self.continue_block
+    }
+
+    /// Get the block that `break` branches to.
+    pub fn get_break_block(&self) -> BasicBlock<'ctx> {
+        self.break_block
+    }
+}
 
 pub struct CodeGenInner<'ctx> {
     context:      &'ctx Context,
     module:       Module<'ctx>,
     builder:      Builder<'ctx>,
     engine:       ExecutionEngine<'ctx>,
-    named_values: NamedValues<'ctx>
+    named_values: NamedValues<'ctx>,
+    loops:        Vec<LoopContext<'ctx>>,
+    function:     Option<ReturnContext<'ctx>>,
+    passes:       PassManager<FunctionValue<'ctx>>,
+    runs:         usize
 }
 
 impl<'ctx> CodeGenInner<'ctx> {
     fn new(context: &'ctx Context, module: Module<'ctx>, engine: ExecutionEngine<'ctx>) -> Self {
+        // Variables are made with `alloca`s, which mem2reg turns back into
+        // registers.
+        let passes = PassManager::create(&module);
+        passes.add_promote_memory_to_register_pass();
+        passes.initialize();
         Self {
             context,
             module,
             builder: context.create_builder(),
             engine,
-            named_values: HashMap::new()
+            named_values: ScopeStack::new(),
+            loops: Vec::new(),
+            function: None,
+            passes,
+            runs: 0
         }
     }
 
@@ -76,6 +149,38 @@ impl<'ctx> CodeGenInner<'ctx> {
     }
 }
 
+fn make_not_in_function_error() -> Error {
+    Error::new(
+        "Control flow can only be generated inside a function".to_string(),
+        ErrorKind::NotInFunctionError,
+        None
+    )
+}
+
+fn make_undefined_variable_error(name: &str) -> Error {
+    Error::new(
+        format!("Could not find variable named '{}'", name),
+        ErrorKind::UndefinedNameError,
+        None
+    )
+}
+
+fn make_immutable_error(name: &str) -> Error {
+    Error::new(
+        format!("Cannot assign to '{}', which is immutable", name),
+        ErrorKind::NotAssignableError,
+        None
+    )
+}
+
+fn make_not_in_loop_error(statement: &str) -> Error {
+    Error::new(
+        format!("'{}' can only be used inside a loop", statement),
+        ErrorKind::NotInLoopError,
+        None
+    )
+}
+
 /// A structure representing an LLVM IR generator.
 #[derive(Clone)]
 pub struct CodeGen<'ctx> {
@@ -132,31 +237,264 @@ impl<'ctx: 'val, 'val> CodeGen<'ctx> {
             .expect(&format!("{} type not initialised yet.", NUM_TYPE_NAME))
     }
 
-    pub fn get_value(&self, name: &str) -> Option<BasicValueEnum<'ctx>> {
+    /// Get the value of `name` in the innermost scope that defines it. If no
+    /// scope defines `name`, it is looked up in the globals of the module.
+    /// Constant globals are replaced by their value, while other globals are
+    /// variables that can be assigned to.
+    pub fn get_value(&self, name: &str) -> Option<NamedValue<'ctx>> {
+        let inner = self.get_inner();
+        if let Some(value) = inner.get_named_values().get(name) {
+            return Some(value);
+        }
+        let global = inner.get_module().get_global(name)?;
+        match global.get_initializer() {
+            Some(value) if global.is_constant() => Some(NamedValue::Value(value)),
+            _ => Some(NamedValue::Variable(global.as_pointer_value()))
+        }
+    }
+
+    /// Define `name` in the innermost scope.
+    pub fn set_value(&self, name: String, value: NamedValue<'ctx>) {
+        self.get_inner().get_mut_named_values().define(name, value);
+    }
+
+    /// Start a new scope. Names defined until [`CodeGen::exit_scope`] is
+    /// called shadow names in outer scopes, and go away when it is called.
+    pub fn enter_scope(&self) {
+        self.get_inner().get_mut_named_values().enter_scope();
+    }
+
+    /// Exit the innermost scope.
+    pub fn exit_scope(&self) {
+        self.get_inner().get_mut_named_values().exit_scope();
+    }
+
+    /// Run `action` in a new scope, which is exited even if `action` fails.
+    pub fn with_scope<T, F: FnOnce() -> Result<T>>(&self, action: F) -> Result<T> {
+        self.enter_scope();
+        let result = action();
+        self.exit_scope();
+        result
+    }
+
+    /// Get the block the builder is currently adding instructions to.
+    pub fn get_current_block(&self) -> Result<BasicBlock<'ctx>> {
         self.get_inner()
-            .get_named_values()
-            .get(name)
-            .map(|v| v.as_basic_value_enum())
+            .get_builder()
+            .get_insert_block()
+            .ok_or_else(make_not_in_function_error)
+    }
+
+    /// Get the function the builder is currently adding instructions to.
+    pub fn get_current_function(&self) -> Result<FunctionValue<'ctx>> {
+        self.get_current_block()?
+            .get_parent()
+            .ok_or_else(make_not_in_function_error)
+    }
+
+    /// Add a new block to the end of the current function. The builder is
+    /// not moved to the new block.
+    pub fn append_block(&self, name: &str) -> Result<BasicBlock<'ctx>> {
+        let function = self.get_current_function()?;
+        Ok(self.get_context().append_basic_block(function, name))
+    }
+
+    /// Start generating the body of a function. Until
+    /// [`CodeGen::exit_function`] is called, `return` stores its value in
+    /// the slot in `context` and branches to its exit block.
+    pub fn enter_function(&self, context: ReturnContext<'ctx>) {
+        self.get_inner().function = Some(context);
+    }
+
+    /// Stop generating the body of a function, returning its
+    /// [`ReturnContext`].
+    pub fn exit_function(&self) -> Option<ReturnContext<'ctx>> {
+        self.get_inner().function.take()
+    }
+
+    /// Get the [`ReturnContext`] of the function being generated.
+    pub fn get_return_context(&self) -> Result<ReturnContext<'ctx>> {
+        self.get_inner().function.ok_or_else(|| {
+            Error::new(
+                "'return' can only be used inside a function".to_string(),
+                ErrorKind::NotInFunctionError,
+                None
+            )
+        })
+    }
+
+    /// Make a stack slot for a variable called `name` in the entry block of
+    /// the current function. Keeping every slot in the entry block lets
+    /// mem2reg promote them to registers. The slot is not given a value.
+    pub fn create_variable(&self, name: &str) -> Result<PointerValue<'ctx>> {
+        let num_type = self.get_num_type();
+        let entry_block = self
+            .get_current_function()?
+            .get_first_basic_block()
+            .ok_or_else(make_not_in_function_error)?;
+        let builder = self.get_context().create_builder();
+        match entry_block.get_first_instruction() {
+            Some(instruction) => builder.position_before(&instruction),
+            None => builder.position_at_end(entry_block)
+        }
+        Ok(builder.build_alloca(num_type, name))
+    }
+
+    /// Get the stack slot of the mutable variable called `name`, so it can
+    /// be assigned to.
+    pub fn get_variable(&self, name: &str) -> Result<PointerValue<'ctx>> {
+        match self.get_value(name) {
+            Some(NamedValue::Variable(slot)) => Ok(slot),
+            Some(NamedValue::Value(_)) => Err(make_immutable_error(name)),
+            None => Err(make_undefined_variable_error(name))
+        }
+    }
+
+    /// Get the current value of `name`, loading it from its stack slot if
+    /// it is a mutable variable.
+    pub fn load_value(&self, name: &str) -> Result<BasicValueEnum<'ctx>> {
+        match self.get_value(name) {
+            Some(NamedValue::Variable(slot)) =>
+                Ok(self.get_inner().get_builder().build_load(slot, name)),
+            Some(NamedValue::Value(value)) => Ok(value),
+            None => Err(make_undefined_variable_error(name))
+        }
+    }
+
+    /// Run the optimisation passes (such as mem2reg) over `function`.
+    /// Returns true if the function was changed.
+    pub fn optimise_function(&self, function: &FunctionValue<'ctx>) -> bool {
+        self.get_inner().passes.run_on(function)
+    }
+
+    /// Call the function called `name`, which must not take any parameters,
+    /// with the JIT and return the number it evaluates to as a float.
+    pub fn run_function(&self, name: &str) -> Result<f64> {
+        let function = self
+            .get_inner()
+            .get_module()
+            .get_function(name)
+            .ok_or_else(|| {
+                Error::new(
+                    format!("Could not find function with name '{}'", name),
+                    ErrorKind::UndefinedNameError,
+                    None
+                )
+            })?;
+        // The function returns a num, which is converted to a float in a
+        // wrapper so that it can be passed back to Rust.
+        // The JIT keeps the code of earlier runs, so every wrapper has its
+        // own name.
+        let wrapper_name = {
+            let mut inner = self.get_inner();
+            inner.runs += 1;
+            format!("__run_{}_{}__", name, inner.runs)
+        };
+        let float_type = self.get_float_type();
+        let wrapper = self.get_inner().get_module().add_function(
+            &wrapper_name,
+            float_type.fn_type(&[], false),
+            None
+        );
+        let entry_block = self.get_context().append_basic_block(wrapper, "entry");
+        self.get_inner().get_builder().position_at_end(entry_block);
+        let result = self
+            .get_inner()
+            .get_builder()
+            .build_call(function, &[], "result")
+            .try_as_basic_value()
+            .left();
+        let result = match result {
+            Some(result) => NumValue::new(result.into_struct_value(), self.clone())
+                .and_then(|result| result.to_float())
+                .map(|result| result.get_raw_float_value()),
+            None => Err(Error::new(
+                format!("'{}' does not return a value", name),
+                ErrorKind::TypeError,
+                None
+            ))
+        };
+        let result = match result {
+            Ok(result) => result,
+            Err(error) => {
+                unsafe { wrapper.delete() };
+                return Err(error);
+            }
+        };
+        self.get_inner().get_builder().build_return(Some(&result));
+
+        // The JIT only compiles the module once, so it is added again to
+        // compile the functions defined since the last time it was run.
+        let inner = self.get_inner();
+        let engine = inner.get_engine();
+        let _ = engine.remove_module(inner.get_module());
+        let _ = engine.add_module(inner.get_module());
+        let value = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn() -> f64>(&wrapper_name)
+                .map(|wrapper| wrapper.call())
+        };
+        unsafe { wrapper.delete() };
+        value.map_err(|error| {
+            Error::new(
+                format!("Could not run '{}': {:?}", name, error),
+                ErrorKind::CouldNotMakeFunctionError,
+                None
+            )
+        })
+    }
+
+    /// Enter a loop. Until [`CodeGen::exit_loop`] is called, `continue` and
+    /// `break` branch to the blocks in `context`. Loops can be nested.
+    pub fn enter_loop(&self, context: LoopContext<'ctx>) {
+        self.get_inner().loops.push(context);
     }
 
-    pub fn set_value(&self, name: String, value: Box<dyn BasicValue<'ctx> + 'ctx>) {
-        self.get_inner().get_mut_named_values().insert(name, value);
+    /// Exit the innermost loop, returning its [`LoopContext`].
+    pub fn exit_loop(&self) -> Option<LoopContext<'ctx>> {
+        self.get_inner().loops.pop()
+    }
+
+    /// Get the innermost loop. `statement` is the name of the statement that
+    /// needs the loop, which is used in the error if there is no loop.
+    pub fn get_current_loop(&self, statement: &str) -> Result<LoopContext<'ctx>> {
+        self.get_inner()
+            .loops
+            .last()
+            .copied()
+            .ok_or_else(|| make_not_in_loop_error(statement))
     }
 
     pub fn int_to_float(&self, integer: IntValue<'val>) -> FloatValue<'val> {
-        integer.const_signed_to_float(self.get_float_type())
+        let float_type = self.get_float_type();
+        self.get_inner().get_builder().build_signed_int_to_float(
+            integer,
+            float_type,
+            "int_to_float_tmp"
+        )
     }
 
     pub fn float_to_int(&self, float: FloatValue<'val>) -> IntValue<'val> {
-        float.const_to_signed_int(self.get_int_type())
+        let int_type = self.get_int_type();
+        self.get_inner().get_builder().build_float_to_signed_int(
+            float,
+            int_type,
+            "float_to_int_tmp"
+        )
     }
 
     pub fn copy_int(&self, integer: IntValue<'val>) -> IntValue<'val> {
-        integer.const_cast(self.get_int_type(), true)
+        let int_type = self.get_int_type();
+        self.get_inner()
+            .get_builder()
+            .build_int_cast(integer, int_type, "copy_tmp_int")
     }
 
     pub fn copy_float(&self, float: FloatValue<'val>) -> FloatValue<'val> {
-        float.const_cast(self.get_float_type())
+        let float_type = self.get_float_type();
+        self.get_inner()
+            .get_builder()
+            .build_float_cast(float, float_type, "copy_tmp_float")
     }
 
     pub fn make_num_from_i128(&self, value: i128) -> StructValue<'val> {
@@ -167,6 +505,31 @@ impl<'ctx: 'val, 'val> CodeGen<'ctx> {
         self.make_num_from_float(self.make_f64(value)).unwrap()
     }
 
+    /// Build a number out of its fields with `insertvalue`, which works for
+    /// runtime values as well as constants. If every field is a constant,
+    /// the number is folded into a constant too.
+    fn build_num(
+        &self,
+        integer: IntValue<'val>,
+        float: FloatValue<'val>,
+        is_int: bool
+    ) -> StructValue<'val> {
+        let undef = self.get_num_type().get_undef();
+        let boolean = self.make_bool(is_int);
+        let inner = self.get_inner();
+        let builder = inner.get_builder();
+        let num = builder
+            .build_insert_value(undef, integer, 0, "num_int_tmp")
+            .unwrap();
+        let num = builder
+            .build_insert_value(num, float, 1, "num_float_tmp")
+            .unwrap();
+        builder
+            .build_insert_value(num, boolean, 2, "num_tmp")
+            .unwrap()
+            .into_struct_value()
+    }
+
     pub fn make_num_from_int(&self, value: IntValue<'val>) -> Result<StructValue<'val>> {
         let expected_bit_width = self.get_int_type().get_bit_width();
         let gotten_bit_width = value.get_type().get_bit_width();
@@ -180,11 +543,7 @@ impl<'ctx: 'val, 'val> CodeGen<'ctx> {
                 None
             ));
         }
-        let integer = value.into();
-        let float = self.make_f64(0.0).into();
-        let boolean = self.make_bool(true).into();
-        let num_type = self.get_num_type();
-        Ok(num_type.const_named_struct(&[integer, float, boolean]))
+        Ok(self.build_num(value, self.make_f64(0.0), true))
     }
 
     pub fn make_num_from_float(&self, value: FloatValue<'val>) -> Result<StructValue<'val>> {
@@ -195,15 +554,11 @@ impl<'ctx: 'val, 'val> CodeGen<'ctx> {
                 None
             ));
         }
-        let integer = self.make_i128(0).into();
-        let float = value.into();
-        let boolean = self.make_bool(false).into();
-        let num_type = self.get_num_type();
-        Ok(num_type.const_named_struct(&[integer, float, boolean]))
+        Ok(self.build_num(self.make_i128(0), value, false))
     }
 
-    /// Generate a [`BasicValue`] from an expression that implements
-    /// [`IRRepresentableExpression`].
+    /// Generate a [`inkwell::values::BasicValue`] from an expression that
+    /// implements [`IRRepresentableExpression`].
     pub fn make_ir_representable_expression(
         &self,
         node: &dyn IRRepresentableExpression
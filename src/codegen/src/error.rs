@@ -11,6 +11,11 @@ pub enum ErrorKind {
     NotBasicValueError,
     CouldNotMakeFunctionError,
     BitWidthError,
+    NotInFunctionError,
+    NotInLoopError,
+    NotAssignableError,
+    NotConstantError,
+    RedefinitionError,
     Other
 }
 
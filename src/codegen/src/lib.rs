@@ -5,12 +5,13 @@ pub mod builtins;
 pub mod codegen;
 pub mod error;
 pub mod int;
+pub mod scope;
 pub mod traits;
 
 #[cfg(test)]
 mod tests;
 
 pub use crate::{
-    codegen::{create_code_gen, CodeGen},
+    codegen::{create_code_gen, CodeGen, LoopContext, ReturnContext},
     traits::{IRRepresentableExpression, IRRepresentableNode}
 };
@@ -0,0 +1,97 @@
+//! The names that are visible while generating IR.
+//!
+//! See [`ScopeStack`].
+
+use std::collections::HashMap;
+
+use inkwell::values::{BasicValueEnum, PointerValue};
+
+/// The value a name stands for.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NamedValue<'ctx> {
+    /// A mutable variable, kept in a stack slot. Using it loads from the
+    /// slot, and assigning to it stores to the slot.
+    Variable(PointerValue<'ctx>),
+    /// An immutable value, such as one bound by `let`. It is used as it
+    /// is, so it never needs a stack slot.
+    Value(BasicValueEnum<'ctx>)
+}
+
+/// A stack of scopes, each mapping names to [`NamedValue`]s. Names in inner
+/// scopes shadow the same names in outer scopes, and go away when their
+/// scope is exited.
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_codegen::scope::ScopeStack;
+///
+/// let mut scopes = ScopeStack::<'static>::new();
+/// assert_eq!(scopes.depth(), 1);
+/// scopes.enter_scope();
+/// assert_eq!(scopes.depth(), 2);
+/// assert!(scopes.get("x").is_none());
+/// scopes.exit_scope();
+/// assert_eq!(scopes.depth(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScopeStack<'ctx> {
+    scopes: Vec<HashMap<String, NamedValue<'ctx>>>
+}
+
+impl<'ctx> ScopeStack<'ctx> {
+    /// Create a stack with one empty scope.
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()]
+        }
+    }
+
+    /// Start a new innermost scope.
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// Exit the innermost scope, forgetting the names defined in it. The
+    /// outermost scope is never exited.
+    pub fn exit_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    /// Get the number of scopes, including the outermost one.
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Define `name` in the innermost scope, replacing any definition of
+    /// `name` already in that scope.
+    pub fn define(&mut self, name: String, value: NamedValue<'ctx>) {
+        self.scopes
+            .last_mut()
+            .expect("the outermost scope is never exited")
+            .insert(name, value);
+    }
+
+    /// Get the value of `name` in the innermost scope that defines it.
+    pub fn get(&self, name: &str) -> Option<NamedValue<'ctx>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+    }
+
+    /// Remove every scope and name, leaving one empty scope.
+    pub fn clear(&mut self) {
+        self.scopes.clear();
+        self.scopes.push(HashMap::new());
+    }
+}
+
+impl<'ctx> Default for ScopeStack<'ctx> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
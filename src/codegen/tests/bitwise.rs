@@ -0,0 +1,45 @@
+mod common;
+
+use inkwell::context::Context;
+
+
+#[test]
+fn test_and_or() {
+    let source = "def f(a, b) (a & b) + (a | b) * 100;\ndef main() f(12, 10);";
+    assert_eq!(common::run(source, "main"), 1408.0);
+}
+
+
+#[test]
+fn test_shifts() {
+    let source = "def f(a) (a << 3) + (a >> 1) * 1000;\ndef main() f(0 - 8);";
+    assert_eq!(common::run(source, "main"), -4064.0);
+}
+
+
+#[test]
+fn test_bitwise_not_and_modulo() {
+    let source = "def f(a, b) ~a * 100 + a % b;\ndef main() f(5, 3) + f(7.5, 2) * 0 + 7.5 % 2;";
+    assert_eq!(common::run(source, "main"), -596.5);
+}
+
+
+#[test]
+fn test_bitwise_operator_on_float_constant() {
+    let context = Context::create();
+    let (_code_gen, errors) = common::try_compile(&context, "def f() 1.5 & 1;");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].to_string().starts_with("TypeError"));
+}
+
+
+#[test]
+fn test_bitwise_operators_truncate_runtime_floats() {
+    // A float is only known to be one at runtime here, so it is truncated
+    // towards zero instead of giving a type error.
+    let source = "def f(a) a & 1;\ndef main() f(1.5);";
+    assert_eq!(common::run(source, "main"), 1.0);
+    let source = "def f(a, b) (a | b) * 1000 + (a << b) * 10 + ~a;\n\
+                  def main() f(0 - 2.75, 1.9);";
+    assert_eq!(common::run(source, "main"), -1000.0 - 40.0 + 1.0);
+}
@@ -0,0 +1,17 @@
+mod common;
+
+
+#[test]
+fn test_block_gives_last_value() {
+    let source = "def f(x) { x + 1; { x * 2; x * 3 } };\ndef main() f(2);";
+    assert_eq!(common::run(source, "main"), 6.0);
+}
+
+
+#[test]
+fn test_block_runs_every_expression() {
+    let source = "global total = 0;\n\
+                  def add(x) total = total + x;\n\
+                  def main() { add(1); add(20); { add(300) }; total };";
+    assert_eq!(common::run(source, "main"), 321.0);
+}
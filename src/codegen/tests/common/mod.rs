@@ -0,0 +1,89 @@
+//! Helpers for tests that compile Kaleidoscope programmes, check them with
+//! LLVM's verifier and run them with the JIT.
+
+#![allow(dead_code)]
+
+use inkwell::{context::Context, values::AnyValue, OptimizationLevel};
+use kaleidoscope_ast::nodes::ProgramItem;
+use kaleidoscope_codegen::{create_code_gen, error::Error, CodeGen, IRRepresentableNode};
+
+
+/// Compile every item in `source` into a new module, carrying on past
+/// items that fail to compile. The errors are returned together with the
+/// module. Top-level expressions are not allowed, since they can only be run
+/// by the REPL.
+pub fn try_compile<'ctx>(context: &'ctx Context, source: &str) -> (CodeGen<'ctx>, Vec<Error>) {
+    let program = kaleidoscope_parser::parse_program(source)
+        .unwrap_or_else(|diagnostics| panic!("{:?}", diagnostics));
+    let module = context.create_module("__test__");
+    let engine = module
+        .create_jit_execution_engine(OptimizationLevel::None)
+        .unwrap();
+    let code_gen = create_code_gen(context, module, engine);
+    let mut errors = Vec::new();
+    for item in program.get_items() {
+        let result = match item {
+            ProgramItem::Extern(node) => node.represent_node(&code_gen),
+            ProgramItem::Function(node) => node.represent_node(&code_gen),
+            ProgramItem::Global(node) => node.represent_node(&code_gen),
+            ProgramItem::Expression(node) =>
+                panic!("Top-level expression in test programme: {}", node),
+        };
+        if let Err(error) = result {
+            errors.push(error);
+        }
+    }
+    (code_gen, errors)
+}
+
+
+/// Compile every item in `source` into a new module, panicking if any of
+/// them fail to compile.
+pub fn compile<'ctx>(context: &'ctx Context, source: &str) -> CodeGen<'ctx> {
+    let (code_gen, errors) = try_compile(context, source);
+    if let Some(error) = errors.first() {
+        panic!("Could not compile programme: {}", error);
+    }
+    code_gen
+}
+
+
+/// Run LLVM's verifier over the whole module.
+pub fn verify(code_gen: &CodeGen<'_>) {
+    if let Err(message) = code_gen.get_inner().get_module().verify() {
+        panic!(
+            "Module failed to verify:\n{}\n{}",
+            message.to_string(),
+            code_gen
+                .get_inner()
+                .get_module()
+                .print_to_string()
+                .to_string()
+        );
+    }
+}
+
+
+/// Get the IR of the function called `name`.
+pub fn get_function_ir(code_gen: &CodeGen<'_>, name: &str) -> String {
+    code_gen
+        .get_inner()
+        .get_module()
+        .get_function(name)
+        .unwrap_or_else(|| panic!("No function called '{}'", name))
+        .print_to_string()
+        .to_string()
+}
+
+
+/// Compile and verify `source`, then call the function `name`, which must
+/// not take any arguments. The number it returns is converted to a float so
+/// that it can be passed back to Rust.
+pub fn run(source: &str, name: &str) -> f64 {
+    let context = Context::create();
+    let code_gen = compile(&context, source);
+    verify(&code_gen);
+    code_gen
+        .run_function(name)
+        .unwrap_or_else(|error| panic!("Could not run '{}': {}", name, error))
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use inkwell::context::Context;
+
+
+#[test]
+fn test_global_variable() {
+    let source = "const PI = 3.5;\n\
+                  global counter = 0;\n\
+                  def f(x) counter = counter + x * PI;\n\
+                  def main() { f(1); f(2); counter };";
+    assert_eq!(common::run(source, "main"), 10.5);
+}
+
+
+#[test]
+fn test_negative_constant() {
+    assert_eq!(
+        common::run("const N = -4;\ndef main() N * N;", "main"),
+        16.0
+    );
+}
+
+
+#[test]
+fn test_constant_is_not_assignable() {
+    let context = Context::create();
+    let (code_gen, errors) = common::try_compile(&context, "const N = 1;\ndef f() N = 2;");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].to_string().starts_with("NotAssignableError"));
+    common::verify(&code_gen);
+}
+
+
+#[test]
+fn test_constant_expression() {
+    let source = "const PI = 3.5;\n\
+                  const TAU = 2 * PI;\n\
+                  const BITS = (1 << 4) | 1;\n\
+                  const BIG = 2 ^ 10 + 0.5 ^ 2;\n\
+                  const BOTH = (PI > 3 && BITS == 17) || 1 / 0;\n\
+                  global counter = -TAU;\n\
+                  def f() { counter = counter + 1; counter };\n\
+                  def main() TAU * 1000 + BITS * 10 + BIG + BOTH + f();";
+    assert_eq!(
+        common::run(source, "main"),
+        7000.0 + 170.0 + 1024.25 + 1.0 - 6.0
+    );
+}
+
+
+#[test]
+fn test_initializer_must_be_constant() {
+    let context = Context::create();
+    let source = "def f() 1;\n\
+                  global counter = 1;\n\
+                  const A = f() * 2;\n\
+                  const B = counter + 1;\n\
+                  const C = -counter;\n\
+                  const D = missing + 1;";
+    let (code_gen, errors) = common::try_compile(&context, source);
+    let errors = errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(errors.len(), 4, "{:?}", errors);
+    for error in &errors[..3] {
+        assert!(error.starts_with("NotConstantError"), "{}", error);
+    }
+    assert!(errors[3].starts_with("UndefinedNameError"), "{}", errors[3]);
+    common::verify(&code_gen);
+    assert!(code_gen.get_inner().get_module().get_global("A").is_none());
+}
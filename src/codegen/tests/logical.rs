@@ -0,0 +1,30 @@
+mod common;
+
+
+#[test]
+fn test_logical_operators_on_runtime_values() {
+    let source = "def both(a, b) a && b;\n\
+                  def either(a, b) a || b;\n\
+                  def main() both(2, 0.5) + either(0, 0) * 10 + either(0.0, 3) * 100;";
+    assert_eq!(common::run(source, "main"), 101.0);
+}
+
+
+#[test]
+fn test_logical_operators_short_circuit() {
+    let source = "global calls = 0;\n\
+                  def bump() { calls = calls + 1; 1 };\n\
+                  def main() { 0 && bump(); 1 && bump(); 1 || bump(); 0 || bump(); calls };";
+    assert_eq!(common::run(source, "main"), 2.0);
+}
+
+
+#[test]
+fn test_logical_and_of_function_call_verifies() {
+    let context = inkwell::context::Context::create();
+    let code_gen = common::compile(&context, "def f(x) x;\ndef g(a) a && f(a);");
+    common::verify(&code_gen);
+    let ir = common::get_function_ir(&code_gen, "g");
+    assert!(ir.contains("logical_rhs"));
+    assert!(ir.contains("phi"));
+}
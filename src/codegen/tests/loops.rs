@@ -0,0 +1,47 @@
+mod common;
+
+
+#[test]
+fn test_for_loop() {
+    let source = "global total = 0;\n\
+                  def add(x) total = total + x;\n\
+                  def main() { for i = 1, i <= 10 in add(i); total };";
+    assert_eq!(common::run(source, "main"), 55.0);
+}
+
+
+#[test]
+fn test_for_loop_with_step() {
+    let source = "global total = 0;\n\
+                  def add(x) total = total + x;\n\
+                  def main() { for i = 0, i < 10, 2.5 in add(i); total };";
+    assert_eq!(common::run(source, "main"), 15.0);
+}
+
+
+#[test]
+fn test_while_loop() {
+    let source = "def main() var i = 1 in { while i < 100 do i = i * 2; i };";
+    assert_eq!(common::run(source, "main"), 128.0);
+}
+
+
+#[test]
+fn test_break_and_continue() {
+    let source = "def main() var total = 0 in {\n\
+                      for i = 0, i < 100 in {\n\
+                          i % 2 == 1 && continue;\n\
+                          i > 10 && break;\n\
+                          total = total + i\n\
+                      };\n\
+                      total\n\
+                  };";
+    assert_eq!(common::run(source, "main"), 30.0);
+}
+
+
+#[test]
+fn test_break_out_of_while_loop() {
+    let source = "def main() var i = 0 in { while 1 do (i = i + 1) == 5 && break; i };";
+    assert_eq!(common::run(source, "main"), 5.0);
+}
@@ -0,0 +1,36 @@
+mod common;
+
+
+#[test]
+fn test_user_defined_binary_operator() {
+    let source = "def binary@ 5 (a, b) a * 10 + b;\n\
+                  def f(a, b) a @ b;\n\
+                  def main() f(1, 2) + (3 @ 4) * 100;";
+    assert_eq!(common::run(source, "main"), 3412.0);
+}
+
+
+#[test]
+fn test_user_defined_unary_operator() {
+    let source = "def unary$ (x) x * x;\ndef main() $3 + $0.5;";
+    assert_eq!(common::run(source, "main"), 9.25);
+}
+
+
+#[test]
+fn test_redefined_builtin_operators() {
+    // `|` as a logical or and `!` as a test for zero, both replacing the
+    // built-in operators.
+    let source = "def binary| 5 (a b) (a != 0) + (b != 0) > 0;\n\
+                  def unary! (x) (x == 0) * 2;\n\
+                  def main() (0 | 0) + (0 | 0.5) * 10 + !0.5 * 100 + !0 * 1000;";
+    assert_eq!(common::run(source, "main"), 2010.0);
+}
+
+
+#[test]
+fn test_operator_in_a_new_position() {
+    let source = "def unary| (x) x * x;\n\
+                  def main() |(0 - 2) | 1;";
+    assert_eq!(common::run(source, "main"), 5.0);
+}
@@ -0,0 +1,35 @@
+mod common;
+
+
+#[test]
+fn test_int_power() {
+    let source = "def power(a, b) a ^ b;\ndef main() power(2, 10) + power(3, 0) + power(0 - 2, 3);";
+    assert_eq!(common::run(source, "main"), 1017.0);
+}
+
+
+#[test]
+fn test_negative_int_exponent() {
+    // A negative exponent gives a float rather than truncating to an
+    // integer.
+    let source = "def power(a, b) a ^ b;\ndef main() power(5, 0 - 1) + power(2, 0 - 2);";
+    assert_eq!(common::run(source, "main"), 0.45);
+    assert_eq!(common::run("def main() 4 ^ (0 - 1);", "main"), 0.25);
+    assert_eq!(
+        common::run("def main() (0 - 2) ^ (0 - 1) * 4;", "main"),
+        -2.0
+    );
+}
+
+
+#[test]
+fn test_float_power() {
+    let source = "def power(a, b) a ^ b;\ndef main() power(4, 0.5) + power(0.5, 2);";
+    assert_eq!(common::run(source, "main"), 2.25);
+}
+
+
+#[test]
+fn test_power_is_right_associative() {
+    assert_eq!(common::run("def main() 2 ^ 3 ^ 2;", "main"), 512.0);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use inkwell::{context::Context, OptimizationLevel};
+use kaleidoscope_ast::nodes::{evaluate_expression, ProgramItem, ANONYMOUS_FUNCTION_NAME};
+use kaleidoscope_codegen::{create_code_gen, IRRepresentableNode};
+
+
+/// Run `source` item by item like the REPL does, returning what each
+/// top-level expression evaluates to.
+fn evaluate(source: &str) -> Vec<f64> {
+    let program = kaleidoscope_parser::parse_program(source)
+        .unwrap_or_else(|diagnostics| panic!("{:?}", diagnostics));
+    let context = Context::create();
+    let module = context.create_module("__repl__");
+    let engine = module
+        .create_jit_execution_engine(OptimizationLevel::None)
+        .unwrap();
+    let code_gen = create_code_gen(&context, module, engine);
+    let mut values = Vec::new();
+    for item in program.get_items() {
+        match item {
+            ProgramItem::Extern(node) => node.represent_node(&code_gen).map(|_| ()),
+            ProgramItem::Function(node) => node.represent_node(&code_gen).map(|_| ()),
+            ProgramItem::Global(node) => node.represent_node(&code_gen).map(|_| ()),
+            ProgramItem::Expression(node) => evaluate_expression(node.expr_node_clone(), &code_gen)
+                .map(|value| {
+                    values.push(value);
+                })
+        }
+        .unwrap_or_else(|error| panic!("Could not evaluate '{}': {}", item, error));
+    }
+    common::verify(&code_gen);
+    assert!(code_gen
+        .get_inner()
+        .get_module()
+        .get_function(ANONYMOUS_FUNCTION_NAME)
+        .is_none());
+    values
+}
+
+
+#[test]
+fn test_constant_expressions() {
+    assert_eq!(evaluate("2 ^ 3;\n1 && 0;\n1.5 * 2;"), vec![8.0, 0.0, 3.0]);
+}
+
+
+#[test]
+fn test_expressions_between_definitions() {
+    let source = "def f(x) x * 2;\n\
+                  f(2) ^ 3;\n\
+                  def g(x) f(x) + 1;\n\
+                  g(1.25) && f(0);\n\
+                  g(f(1)) || 0;\n\
+                  g(3);";
+    assert_eq!(evaluate(source), vec![64.0, 0.0, 1.0, 7.0]);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use inkwell::context::Context;
+
+
+#[test]
+fn test_return_from_loop() {
+    let source = "def g(x) x - 3;\n\
+                  def f(n) for i = 0, i < n in g(i) == 0 && return i;\n\
+                  def main() f(10) * 10 + f(2);";
+    assert_eq!(common::run(source, "main"), 30.0);
+}
+
+
+#[test]
+fn test_return_skips_rest_of_block() {
+    let source = "global calls = 0;\n\
+                  def f(x) { x > 0 && return x; calls = calls + 1; 0 - x };\n\
+                  def main() f(2) + f(0 - 5) * 10 + calls * 100;";
+    assert_eq!(common::run(source, "main"), 152.0);
+}
+
+
+#[test]
+fn test_function_is_deleted_if_body_fails() {
+    let context = Context::create();
+    let (code_gen, errors) = common::try_compile(
+        &context,
+        "def f(n) for i = 0, i < n in g(i) == 0 && return i;"
+    );
+    assert_eq!(errors.len(), 1);
+    assert!(code_gen
+        .get_inner()
+        .get_module()
+        .get_function("f")
+        .is_none());
+    common::verify(&code_gen);
+}
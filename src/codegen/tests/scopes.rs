@@ -0,0 +1,27 @@
+mod common;
+
+use inkwell::context::Context;
+
+
+#[test]
+fn test_let_shadows_outer_binding() {
+    let source = "def f(x) let y = x * 2 in (let y = y * 10 in y) + y;\ndef main() f(1);";
+    assert_eq!(common::run(source, "main"), 22.0);
+}
+
+
+#[test]
+fn test_let_value_uses_outer_binding() {
+    let source = "def f(x) let x = x + 1 in x * 10;\ndef main() f(2.5);";
+    assert_eq!(common::run(source, "main"), 35.0);
+}
+
+
+#[test]
+fn test_let_binding_is_not_assignable() {
+    let context = Context::create();
+    let (code_gen, errors) = common::try_compile(&context, "def f() let x = 1 in x = 2;");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].to_string().starts_with("NotAssignableError"));
+    common::verify(&code_gen);
+}
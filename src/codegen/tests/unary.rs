@@ -0,0 +1,15 @@
+mod common;
+
+
+#[test]
+fn test_negation() {
+    let source = "def neg(x) -x;\ndef main() neg(3) + neg(0.5) * 10;";
+    assert_eq!(common::run(source, "main"), -8.0);
+}
+
+
+#[test]
+fn test_logical_not() {
+    let source = "def not(x) !x;\ndef main() not(0) * 100 + not(7) * 10 + not(0.0);";
+    assert_eq!(common::run(source, "main"), 101.0);
+}
@@ -0,0 +1,35 @@
+mod common;
+
+
+#[test]
+fn test_var_in() {
+    assert_eq!(common::run("def main() var x = 1 in x + 1;", "main"), 2.0);
+}
+
+
+#[test]
+fn test_assignment() {
+    let source = "def main() var x = 1, y in { y = x = x + 2; x * 10 + y };";
+    assert_eq!(common::run(source, "main"), 33.0);
+}
+
+
+#[test]
+fn test_assign_to_parameter() {
+    let source = "def double(x) { x = x * 2; x };\ndef main() double(2.5);";
+    assert_eq!(common::run(source, "main"), 5.0);
+}
+
+
+#[test]
+fn test_variables_are_promoted_to_registers() {
+    let context = inkwell::context::Context::create();
+    let code_gen = common::compile(
+        &context,
+        "def f(n) var total = 0 in { for i = 0, i < n in total = total + i; total };"
+    );
+    common::verify(&code_gen);
+    let ir = common::get_function_ir(&code_gen, "f");
+    assert!(!ir.contains("alloca"), "{}", ir);
+    assert!(ir.contains("phi"), "{}", ir);
+}
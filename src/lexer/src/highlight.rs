@@ -0,0 +1,285 @@
+//! Syntax highlighting for Kaleidoscope source code.
+//!
+//! The tokens produced by the [`Tokenizer`] already know what kind of span
+//! they cover and where it starts, so highlighting a programme is just a
+//! matter of wrapping each span in the original source with some markup.
+//! Everything between tokens (whitespace and comments) is copied over
+//! verbatim, so the output reads exactly like the input.
+//!
+//! See [`highlight`] and [`highlight_source`].
+
+use crate::{
+    error::Result,
+    token::{ColumnEncoding, Token, TokenKind},
+    tokenizer::{FileStream, Tokenizer, TokenizerOptions},
+    utils
+};
+
+/// The ANSI escape sequence that resets the style of the text after it.
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// The markup used to highlight the source code.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HighlightFormat {
+    /// Wrap each token in a `<span>` with a CSS class named after the kind
+    /// of token (see [`css_class`]). The whole programme is wrapped in a
+    /// `<pre class="kld">` block.
+    Html,
+    /// Color each token with ANSI escape sequences for terminals (see
+    /// [`ansi_color`]).
+    Ansi
+}
+
+impl HighlightFormat {
+    /// Convert a string to a [`HighlightFormat`]. If the string is not the
+    /// name of a format, [`None`] is returned.
+    pub fn from_string(string: &str) -> Option<Self> {
+        Some(match string {
+            "html" => HighlightFormat::Html,
+            "ansi" => HighlightFormat::Ansi,
+            _ => return None
+        })
+    }
+}
+
+/// The kind of span that is highlighted, which is either a token or a
+/// comment.
+#[derive(Copy, Clone, Debug)]
+enum Span {
+    Token(TokenKind),
+    Comment
+}
+
+/// Get the CSS class used for a [`TokenKind`]. [`None`] is returned if the
+/// token should not be highlighted.
+pub fn css_class(token_kind: TokenKind) -> Option<&'static str> {
+    Some(match token_kind {
+        TokenKind::Keyword(_) => "kld-keyword",
+        TokenKind::Identifier => "kld-identifier",
+        TokenKind::Integer => "kld-integer",
+        TokenKind::Float => "kld-float",
+        TokenKind::Operator(_) => "kld-operator",
+        TokenKind::Bracket(_) => "kld-bracket",
+        TokenKind::Comma | TokenKind::Dot | TokenKind::Semicolon => "kld-punctuation",
+        TokenKind::DocComment => "kld-doc-comment",
+        TokenKind::Unknown | TokenKind::Eof => return None
+    })
+}
+
+/// Get the ANSI escape sequence used to color a [`TokenKind`]. [`None`] is
+/// returned if the token should not be highlighted.
+pub fn ansi_color(token_kind: TokenKind) -> Option<&'static str> {
+    Some(match token_kind {
+        TokenKind::Keyword(_) => "\x1b[1;35m",
+        TokenKind::Identifier => "\x1b[36m",
+        TokenKind::Integer | TokenKind::Float => "\x1b[33m",
+        TokenKind::Operator(_) => "\x1b[31m",
+        TokenKind::Bracket(_) => "\x1b[1m",
+        TokenKind::DocComment => "\x1b[32m",
+        TokenKind::Comma
+        | TokenKind::Dot
+        | TokenKind::Semicolon
+        | TokenKind::Unknown
+        | TokenKind::Eof => return None
+    })
+}
+
+/// Escape the characters in `text` that have a special meaning in HTML.
+fn escape_html(text: &str, output: &mut String) {
+    for unit in text.chars() {
+        match unit {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            _ => output.push(unit)
+        }
+    }
+}
+
+/// Write `text` to `output`, highlighted according to its kind.
+fn push_span(text: &str, span: Option<Span>, format: HighlightFormat, output: &mut String) {
+    if text.is_empty() {
+        return;
+    }
+    match format {
+        HighlightFormat::Html => {
+            let class = match span {
+                Some(Span::Token(token_kind)) => css_class(token_kind),
+                Some(Span::Comment) => Some("kld-comment"),
+                None => None
+            };
+            match class {
+                Some(class) => {
+                    output.push_str("<span class=\"");
+                    output.push_str(class);
+                    output.push_str("\">");
+                    escape_html(text, output);
+                    output.push_str("</span>");
+                },
+                None => escape_html(text, output)
+            }
+        },
+        HighlightFormat::Ansi => {
+            let color = match span {
+                Some(Span::Token(token_kind)) => ansi_color(token_kind),
+                Some(Span::Comment) => Some("\x1b[2;32m"),
+                None => None
+            };
+            match color {
+                Some(color) => {
+                    output.push_str(color);
+                    output.push_str(text);
+                    output.push_str(ANSI_RESET);
+                },
+                None => output.push_str(text)
+            }
+        }
+    }
+}
+
+/// Write the text between 2 tokens to `output`, highlighting any comments
+/// found inside.
+fn push_gap(text: &str, comment_char: char, format: HighlightFormat, output: &mut String) {
+    let mut rest = text;
+    while let Some(start) = rest.find(comment_char) {
+        push_span(&rest[..start], None, format, output);
+        rest = &rest[start..];
+        let end = rest.find(utils::is_eol).unwrap_or(rest.len());
+        push_span(&rest[..end], Some(Span::Comment), format, output);
+        rest = &rest[end..];
+    }
+    push_span(rest, None, format, output);
+}
+
+/// Get the byte offset of the start of every line in `source`. The lexer
+/// only counts `\n` as the end of a line (a `\r` before it is part of the
+/// line), so the same is done here.
+fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+        .collect()
+}
+
+/// Find where `token` starts in `source` using the line and UTF-8 column of
+/// its start. [`None`] is returned if the span of the token is not found at
+/// that position.
+fn find_token(source: &str, line_starts: &[usize], token: &Token) -> Option<usize> {
+    let first = token.span.chars().next()?;
+    let line_start = *line_starts.get(token.start.get_line_number())?;
+    // The start of a token is the index just after its first character.
+    let offset = (line_start + token.start.get_column_in(ColumnEncoding::Utf8))
+        .checked_sub(first.len_utf8())?;
+    match source.get(offset..) {
+        Some(rest) if rest.starts_with(&token.span[..]) => Some(offset),
+        _ => None
+    }
+}
+
+/// Highlight `source` using `tokens`, which must be the tokens that were
+/// produced from `source` (in order). A token that cannot be found where its
+/// [`Token::start`] says it is is left unhighlighted.
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_lexer::{
+///     highlight::{highlight, HighlightFormat},
+///     tokenizer::{FileStream, Tokenizer}
+/// };
+///
+/// let source = "def f(x) x # x";
+/// let tokens = Tokenizer::new().to_iter(FileStream::from(source));
+/// let html = highlight(source, tokens, HighlightFormat::Html);
+/// assert!(html.starts_with(r#"<pre class="kld"><span class="kld-keyword">def</span> "#));
+/// assert!(html.ends_with(r#" <span class="kld-comment"># x</span></pre>"#));
+/// ```
+pub fn highlight<I>(source: &str, tokens: I, format: HighlightFormat) -> String
+where
+    I: IntoIterator<Item = Token>
+{
+    highlight_with(source, tokens, format, &TokenizerOptions::default())
+}
+
+/// Highlight `source` using `tokens`, which must have been produced by a
+/// [`Tokenizer`] following `options`. See [`highlight`].
+pub fn highlight_with<I>(
+    source: &str,
+    tokens: I,
+    format: HighlightFormat,
+    options: &TokenizerOptions
+) -> String
+where
+    I: IntoIterator<Item = Token>
+{
+    let comment_char = options.get_comment_char();
+    let line_starts = line_starts(source);
+    let mut output = String::with_capacity(source.len() * 2);
+    if let HighlightFormat::Html = format {
+        output.push_str("<pre class=\"kld\">");
+    }
+    let mut cursor = 0;
+    for token in tokens {
+        if token.is_eof() || token.span.is_empty() {
+            continue;
+        }
+        let start = match find_token(source, &line_starts, &token) {
+            Some(start) if start >= cursor => start,
+            _ => continue
+        };
+        push_gap(&source[cursor..start], comment_char, format, &mut output);
+        let end = start + token.span.len();
+        push_span(
+            &source[start..end],
+            Some(Span::Token(token.token_kind)),
+            format,
+            &mut output
+        );
+        cursor = end;
+    }
+    push_gap(&source[cursor..], comment_char, format, &mut output);
+    if let HighlightFormat::Html = format {
+        output.push_str("</pre>");
+    }
+    output
+}
+
+/// Tokenize and highlight `source`.
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_lexer::highlight::{highlight_source, HighlightFormat};
+///
+/// let ansi = highlight_source("extern def sin(x);\n", HighlightFormat::Ansi).unwrap();
+/// assert!(ansi.starts_with("\x1b[1;35mextern\x1b[0m \x1b[1;35mdef\x1b[0m"));
+/// assert!(ansi.ends_with(";\n"));
+/// ```
+pub fn highlight_source(source: &str, format: HighlightFormat) -> Result<String> {
+    highlight_source_with(source, format, TokenizerOptions::default())
+}
+
+/// Tokenize `source` following `options` and highlight it.
+pub fn highlight_source_with(
+    source: &str,
+    format: HighlightFormat,
+    options: TokenizerOptions
+) -> Result<String> {
+    let mut stream = FileStream::from(source);
+    let mut tokenizer = Tokenizer::with_options(options);
+    let mut tokens = Vec::new();
+    loop {
+        let token = tokenizer.next_token(&mut stream)?;
+        if token.is_eof() {
+            break;
+        }
+        tokens.push(token);
+    }
+    Ok(highlight_with(
+        source,
+        tokens,
+        format,
+        tokenizer.get_options()
+    ))
+}
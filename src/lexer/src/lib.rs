@@ -5,6 +5,10 @@
 //! for the first step.
 
 pub mod error;
+pub mod highlight;
 pub mod token;
 pub mod tokenizer;
 pub mod utils;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,75 @@
+use crate::{
+    token::{FileIndex, Token, TokenKind},
+    tokenizer::{FileStream, TokenBuffer, TokenCursor, TokenSource, Tokenizer}
+};
+
+fn spans<S: TokenSource>(cursor: &mut TokenCursor<S>) -> Vec<String> {
+    cursor.map(|token| token.span).collect()
+}
+
+#[test]
+fn test_peek_does_not_consume() {
+    let mut cursor = Tokenizer::new().to_cursor(FileStream::from("a + b;"));
+    assert_eq!(cursor.peek(3).unwrap().unwrap().span, ";");
+    assert_eq!(cursor.peek(0).unwrap().unwrap().span, "a");
+    assert_eq!(cursor.get_position(), 0);
+    assert!(cursor.peek(4).unwrap().unwrap().is_eof());
+    assert!(cursor.peek(5).unwrap().is_none());
+    assert_eq!(spans(&mut cursor), vec!["a", "+", "b", ";", ""]);
+    assert!(cursor.is_done());
+}
+
+#[test]
+fn test_peek_buffered() {
+    let mut cursor = Tokenizer::new().to_cursor(FileStream::from("a b"));
+    assert!(cursor.peek_buffered(0).is_none());
+    cursor.peek(1).unwrap();
+    assert_eq!(cursor.peek_buffered(1).unwrap().span, "b");
+    assert!(cursor.peek_buffered(2).is_none());
+    assert!(!cursor.eof_reached());
+    cursor.peek(2).unwrap();
+    assert!(cursor.eof_reached());
+}
+
+#[test]
+fn test_mark_reset_release() {
+    let mut cursor = Tokenizer::new().to_cursor(FileStream::from("f(1, 2)"));
+    let outer = cursor.mark();
+    cursor.next_token().unwrap();
+    let inner = cursor.mark();
+    cursor.next_token().unwrap();
+    cursor.next_token().unwrap();
+    cursor.reset(inner);
+    assert_eq!(cursor.next_token().unwrap().unwrap().span, "(");
+    cursor.reset(outer);
+    assert_eq!(cursor.get_position(), 0);
+
+    let mark = cursor.mark();
+    cursor.next_token().unwrap();
+    cursor.release(mark);
+    assert_eq!(spans(&mut cursor), vec!["(", "1", ",", "2", ")", ""]);
+}
+
+#[test]
+#[should_panic]
+fn test_reset_with_foreign_mark() {
+    let mut first = Tokenizer::new().to_cursor(FileStream::from("a b"));
+    let mut second = Tokenizer::new().to_cursor(FileStream::from("c"));
+    first.next_token().unwrap();
+    let mark = first.mark();
+    second.reset(mark);
+}
+
+#[test]
+fn test_cursor_over_buffer() {
+    let index = FileIndex::new(Some(0), 0);
+    let tokens = vec![
+        Token::new(TokenKind::Identifier, "x".to_string(), index, index),
+        Token::new(TokenKind::Semicolon, ";".to_string(), index, index),
+    ];
+    let mut cursor = TokenCursor::new(TokenBuffer::from(tokens));
+    assert_eq!(cursor.peek(1).unwrap().unwrap().span, ";");
+    assert!(cursor.peek(2).unwrap().unwrap().is_eof());
+    assert_eq!(spans(&mut cursor), vec!["x", ";", ""]);
+    assert!(TokenSource::next_token(&mut cursor).unwrap().is_eof());
+}
@@ -0,0 +1,132 @@
+use crate::{
+    token::{ColumnEncoding, FileIndex, Token, DEFAULT_TAB_WIDTH},
+    tokenizer::{FileStream, IdentifierRules, Tokenizer, TokenizerOptions}
+};
+
+fn tokenize(stream: FileStream) -> Vec<Token> {
+    Tokenizer::new().to_iter(stream).collect()
+}
+
+fn index_after(text: &str, tab_width: usize) -> FileIndex {
+    let mut index = FileIndex::new(Some(0), 0);
+    for unit in text.chars() {
+        index.advance(unit, tab_width);
+    }
+    index
+}
+
+#[test]
+fn test_utf16_columns() {
+    // 'é' is 2 bytes in UTF-8 and 1 code unit in UTF-16, while '😀' is 4
+    // bytes in UTF-8 and a surrogate pair in UTF-16.
+    let index = index_after("é😀x", DEFAULT_TAB_WIDTH);
+    assert_eq!(index.get_column(), 3);
+    assert_eq!(index.get_column_in(ColumnEncoding::Utf32), 3);
+    assert_eq!(index.get_column_in(ColumnEncoding::Utf8), 7);
+    assert_eq!(index.get_column_in(ColumnEncoding::Utf16), 4);
+    assert_eq!(index.get_display_column(), 3);
+}
+
+#[test]
+fn test_token_columns_in_every_encoding() {
+    let tokens = tokenize(FileStream::from("# 😀é\n  a\nb"));
+    // The columns of a token point just past its first character.
+    let a = &tokens[0];
+    assert_eq!(a.span, "a");
+    assert_eq!(a.start.get_line(), Some(1));
+    assert_eq!(a.start.get_column_in(ColumnEncoding::Utf8), 3);
+    assert_eq!(a.start.get_column_in(ColumnEncoding::Utf16), 3);
+
+    // '𝒜' is a letter outside of the Basic Multilingual Plane.
+    let mut stream = FileStream::from("𝒜 + é + x");
+    stream.set_column_encoding(ColumnEncoding::Utf16);
+    let options = TokenizerOptions::new().with_identifier_rules(IdentifierRules::Unicode);
+    let mut iterator = options.build().to_iter(stream);
+    let mut columns = Vec::new();
+    loop {
+        let token = iterator.next_token().unwrap();
+        if token.is_eof() {
+            break;
+        }
+        let stream = iterator.get_stream();
+        columns.push((
+            token.start.get_column(),
+            stream.get_column(&token.start),
+            token.start.get_column_in(ColumnEncoding::Utf8)
+        ));
+    }
+    assert_eq!(columns, vec![
+        (1, 2, 4),
+        (3, 4, 6),
+        (5, 6, 9),
+        (7, 8, 11),
+        (9, 10, 13)
+    ]);
+}
+
+#[test]
+fn test_tab_columns() {
+    let index = index_after("\tx", 4);
+    assert_eq!(index.get_column(), 2);
+    assert_eq!(index.get_display_column(), 5);
+
+    // A tab advances to the next tab stop rather than by a fixed width.
+    assert_eq!(index_after("ab\t", 4).get_display_column(), 4);
+    assert_eq!(index_after("abcd\t", 4).get_display_column(), 8);
+    assert_eq!(index_after("a\t\t", 8).get_display_column(), 16);
+    // A tab width of 0 treats tabs like any other character.
+    assert_eq!(index_after("\t\t", 0).get_display_column(), 2);
+    // Only the display column is affected by tabs.
+    let index = index_after("\t\t", 8);
+    assert_eq!(index.get_column_in(ColumnEncoding::Utf8), 2);
+    assert_eq!(index.get_column_in(ColumnEncoding::Utf16), 2);
+}
+
+#[test]
+fn test_stream_tab_width() {
+    for tab_width in [2, 4, DEFAULT_TAB_WIDTH] {
+        let mut stream = FileStream::from("\tx\ty");
+        stream.set_tab_width(tab_width);
+        let tokens = tokenize(stream);
+        assert_eq!(tokens[0].start.get_column(), 2);
+        assert_eq!(tokens[1].start.get_column(), 4);
+        assert_eq!(tokens[0].start.get_display_column(), tab_width + 1);
+        assert_eq!(tokens[1].start.get_display_column(), 2 * tab_width + 1);
+    }
+
+    // The tokenizer leaves the tab width of the stream alone.
+    let mut stream = FileStream::from("\tx");
+    stream.set_tab_width(3);
+    let mut tokenizer = Tokenizer::new();
+    tokenizer.next_token(&mut stream).unwrap();
+    assert_eq!(stream.get_tab_width(), 3);
+}
+
+#[test]
+fn test_deserialize_missing_columns() {
+    let index: FileIndex = serde_json::from_str(r#"{"line": 2, "column": 5}"#).unwrap();
+    assert_eq!(index.get_line(), Some(2));
+    assert_eq!(index.get_column(), 5);
+    assert_eq!(index.get_column_in(ColumnEncoding::Utf8), 5);
+    assert_eq!(index.get_column_in(ColumnEncoding::Utf16), 5);
+    assert_eq!(index.get_display_column(), 5);
+    assert_eq!(index.get_source(), 0);
+    assert_eq!(index, FileIndex::new(Some(2), 5));
+
+    // Indices serialized with a column encoding can still be read.
+    let index: FileIndex = serde_json::from_str(
+        r#"{"line": null, "column": 3, "utf8_column": 6, "encoding": "Utf16", "source": 1}"#
+    )
+    .unwrap();
+    assert_eq!(index.get_line(), None);
+    assert_eq!(index.get_column_in(ColumnEncoding::Utf8), 6);
+    assert_eq!(index.get_column_in(ColumnEncoding::Utf16), 3);
+    assert_eq!(index.get_source(), 1);
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let index = index_after("\té😀", 4).with_source(2);
+    let json = serde_json::to_string(&index).unwrap();
+    assert_eq!(serde_json::from_str::<FileIndex>(&json).unwrap(), index);
+}
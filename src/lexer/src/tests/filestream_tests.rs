@@ -0,0 +1,196 @@
+use crate::{
+    token::{Token, TokenKind},
+    tokenizer::{FileStream, LineEnding, RawLines, Tokenizer}
+};
+
+fn tokenize(stream: FileStream) -> Vec<Token> {
+    Tokenizer::new().to_iter(stream).collect()
+}
+
+#[test]
+fn test_chain_sources() {
+    let stream = FileStream::from_reader("a\nb".as_bytes(), "first.kld")
+        .chain("c".as_bytes(), "second.kld")
+        .chain("d\n".as_bytes(), "third.kld");
+    let tokens = tokenize(stream);
+    let spans = tokens
+        .iter()
+        .map(|token| &token.span[..])
+        .collect::<Vec<_>>();
+    assert_eq!(spans, vec!["a", "b", "c", "d", ""]);
+    let sources = tokens
+        .iter()
+        .map(|token| token.start.get_source())
+        .collect::<Vec<_>>();
+    assert_eq!(sources, vec![0, 0, 1, 2, 2]);
+    // Line numbers restart at the beginning of each source.
+    assert_eq!(tokens[1].start.get_line(), Some(1));
+    assert_eq!(tokens[2].start.get_line(), Some(0));
+}
+
+#[test]
+fn test_separator_between_sources() {
+    let mut stream = FileStream::from_reader("ab".as_bytes(), "first.kld")
+        .chain("".as_bytes(), "empty.kld")
+        .chain("c".as_bytes(), "second.kld");
+    let units = (&mut stream).take(3).collect::<String>();
+    assert_eq!(units, "ab\n");
+    // The newline ends the last line of the first source.
+    assert_eq!(stream.get_source(), 0);
+    assert_eq!(stream.get_index().get_source(), 0);
+    assert_eq!(stream.get_index().get_line(), Some(0));
+    assert_eq!(stream.get_index().get_column(), 3);
+    assert_eq!(stream.next(), Some('c'));
+    assert_eq!(stream.get_source(), 2);
+    assert_eq!(stream.get_index().get_source(), 2);
+    assert_eq!(stream.next(), None);
+    assert!(stream.eof_reached());
+}
+
+#[test]
+fn test_push_reader_after_eof() {
+    let mut stream = FileStream::from_reader("a".as_bytes(), "first.kld");
+    assert_eq!((&mut stream).collect::<String>(), "a");
+    assert!(stream.eof_reached());
+
+    stream.push_reader("b".as_bytes(), "second.kld");
+    assert!(!stream.eof_reached());
+    assert_eq!((&mut stream).collect::<String>(), "\nb");
+    assert_eq!(stream.get_source(), 1);
+    assert_eq!(stream.get_current_source_name(), "second.kld");
+    assert_eq!(stream.get_index().get_source(), 1);
+}
+
+#[test]
+fn test_token_sources() {
+    let stream = FileStream::from_reader("extern def sin(x);".as_bytes(), "prelude.kld")
+        .chain("sin(1)".as_bytes(), "main.kld");
+    let mut iterator = Tokenizer::new().to_iter(stream);
+    let tokens = (&mut iterator).collect::<Vec<_>>();
+    let names = tokens
+        .iter()
+        .map(|token| {
+            iterator
+                .get_stream()
+                .get_source_name(token.start.get_source())
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(names[0], "prelude.kld");
+    assert_eq!(names[6], "prelude.kld");
+    assert_eq!(names[7], "main.kld");
+    assert_eq!(tokens[7].end.get_source(), 1);
+}
+
+#[test]
+fn test_line_ending_lengths() {
+    assert_eq!(LineEnding::Lf.len(), 1);
+    assert_eq!(LineEnding::CrLf.len(), 2);
+    assert_eq!(LineEnding::None.len(), 0);
+    assert!(LineEnding::None.is_empty());
+    assert!(!LineEnding::CrLf.is_empty());
+    for line_ending in [LineEnding::Lf, LineEnding::CrLf, LineEnding::None] {
+        assert_eq!(line_ending.len(), line_ending.as_str().chars().count());
+    }
+}
+
+#[test]
+fn test_raw_lines() {
+    let lines = RawLines::new("a\r\nb\nc\rd\n\ne".as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(lines, vec!["a\r\n", "b\n", "c\rd\n", "\n", "e"]);
+    let endings = lines
+        .iter()
+        .map(|line| LineEnding::of(line))
+        .collect::<Vec<_>>();
+    assert_eq!(endings, vec![
+        LineEnding::CrLf,
+        LineEnding::Lf,
+        LineEnding::Lf,
+        LineEnding::Lf,
+        LineEnding::None
+    ]);
+    assert_eq!(RawLines::new("".as_bytes()).count(), 0);
+}
+
+#[test]
+fn test_crlf_stream() {
+    let source = "a\r\nb\r\n\r\nc";
+    let mut stream = FileStream::from(source);
+    // Line endings are passed through unchanged.
+    assert_eq!((&mut stream).collect::<String>(), source);
+    assert_eq!(stream.get_line_endings(), &[
+        LineEnding::CrLf,
+        LineEnding::CrLf,
+        LineEnding::CrLf,
+        LineEnding::None
+    ]);
+
+    let tokens = tokenize(FileStream::from(source));
+    let positions = tokens
+        .iter()
+        .map(|token| {
+            (
+                &token.span[..],
+                token.start.get_line(),
+                token.start.get_column()
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(positions, vec![
+        ("a", Some(0), 1),
+        ("b", Some(1), 1),
+        ("c", Some(3), 1),
+        ("", Some(3), 1)
+    ]);
+}
+
+#[test]
+fn test_lone_cr() {
+    // A carriage return on its own is whitespace inside a line.
+    let source = "a\rb\n\rc";
+    let mut stream = FileStream::from(source);
+    assert_eq!((&mut stream).collect::<String>(), source);
+    assert_eq!(stream.get_line_endings(), &[
+        LineEnding::Lf,
+        LineEnding::None
+    ]);
+
+    let tokens = tokenize(FileStream::from(source));
+    let positions = tokens
+        .iter()
+        .map(|token| {
+            (
+                &token.span[..],
+                token.start.get_line(),
+                token.start.get_column()
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(positions, vec![
+        ("a", Some(0), 1),
+        ("b", Some(0), 3),
+        ("c", Some(1), 2),
+        ("", Some(1), 2)
+    ]);
+}
+
+#[test]
+fn test_doc_comment_trims_cr() {
+    let tokens = tokenize(FileStream::from(
+        "## Add 1.\r\n## \r\n## a\rb\r\ndef f(x) x + 1"
+    ));
+    let doc_comments = tokens
+        .iter()
+        .filter(|token| token.token_kind == TokenKind::DocComment)
+        .map(|token| &token.span[..])
+        .collect::<Vec<_>>();
+    // Only the '\r' of the line ending is dropped.
+    assert_eq!(doc_comments, vec!["## Add 1.", "## ", "## a\rb"]);
+
+    // The last line may not have a line ending at all.
+    let tokens = tokenize(FileStream::from("## Add 1.\r"));
+    assert_eq!(tokens[0].token_kind, TokenKind::DocComment);
+    assert_eq!(tokens[0].span, "## Add 1.");
+}
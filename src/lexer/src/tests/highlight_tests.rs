@@ -0,0 +1,80 @@
+use crate::{
+    highlight::{highlight, highlight_source, highlight_source_with, HighlightFormat},
+    token::{FileIndex, Token},
+    tokenizer::{FileStream, IdentifierRules, Tokenizer, TokenizerOptions}
+};
+
+fn tokenize(source: &str) -> Vec<Token> {
+    Tokenizer::new().to_iter(FileStream::from(source)).collect()
+}
+
+/// Remove the HTML markup added by the highlighter.
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for unit in html.chars() {
+        match unit {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(unit),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[test]
+fn test_highlight_html() {
+    let html = highlight_source("def f(x) x < 1; # x\n", HighlightFormat::Html).unwrap();
+    assert_eq!(
+        html,
+        "<pre class=\"kld\"><span class=\"kld-keyword\">def</span> \
+         <span class=\"kld-identifier\">f</span><span class=\"kld-bracket\">(</span>\
+         <span class=\"kld-identifier\">x</span><span class=\"kld-bracket\">)</span> \
+         <span class=\"kld-identifier\">x</span> <span class=\"kld-operator\">&lt;</span> \
+         <span class=\"kld-integer\">1</span><span class=\"kld-punctuation\">;</span> \
+         <span class=\"kld-comment\"># x</span>\n</pre>"
+    );
+}
+
+#[test]
+fn test_highlight_keeps_source() {
+    let source = "## Dōc 🎉\r\ndef f(x)\r\n\tx * 2; # cömment 🎉\n\n  extern g(a, b);\r\n";
+    let html = highlight_source(source, HighlightFormat::Html).unwrap();
+    assert_eq!(strip_html(&html), source);
+    assert!(html.contains("<span class=\"kld-doc-comment\">## Dōc 🎉</span>\r\n"));
+    assert!(html.contains("<span class=\"kld-keyword\">extern</span>"));
+}
+
+#[test]
+fn test_highlight_unicode_positions() {
+    let source = "größe + ñ # ß\ngröße";
+    let options = TokenizerOptions::new().with_identifier_rules(IdentifierRules::Unicode);
+    let ansi = highlight_source_with(source, HighlightFormat::Ansi, options).unwrap();
+    assert_eq!(
+        ansi,
+        "\x1b[36mgröße\x1b[0m \x1b[31m+\x1b[0m \x1b[36mñ\x1b[0m \x1b[2;32m# ß\x1b[0m\n\
+         \x1b[36mgröße\x1b[0m"
+    );
+}
+
+#[test]
+fn test_highlight_after_missing_token() {
+    let source = "a + b;\nc;";
+    let mut tokens = tokenize(source);
+    // Move `+` somewhere it cannot be found.
+    tokens[1].start = FileIndex::new(Some(5), 1);
+    let html = highlight(source, tokens, HighlightFormat::Html);
+    assert_eq!(strip_html(&html), source);
+    assert!(html.contains(" + <span class=\"kld-identifier\">b</span>"));
+    assert!(html.contains("<span class=\"kld-identifier\">c</span>"));
+}
+
+#[test]
+fn test_highlight_invalid_source() {
+    assert!(highlight_source("a ` b", HighlightFormat::Html).is_err());
+}
@@ -0,0 +1,12 @@
+#[cfg(test)]
+mod cursor_tests;
+#[cfg(test)]
+mod fileindex_tests;
+#[cfg(test)]
+mod filestream_tests;
+#[cfg(test)]
+mod highlight_tests;
+#[cfg(test)]
+mod options_tests;
+#[cfg(test)]
+mod serde_tests;
@@ -0,0 +1,152 @@
+use crate::{
+    token::{Keyword, Operator, Token, TokenKind},
+    tokenizer::{FileStream, IdentifierRules, TokenizerOptions, DEFAULT_OPERATOR_CHARS},
+    utils
+};
+
+fn tokenize(source: &str, options: TokenizerOptions) -> Vec<Token> {
+    options
+        .build()
+        .to_iter(FileStream::from(source))
+        .collect::<Vec<_>>()
+}
+
+fn kinds(source: &str, options: TokenizerOptions) -> Vec<TokenKind> {
+    tokenize(source, options)
+        .into_iter()
+        .map(|token| token.token_kind)
+        .collect()
+}
+
+#[test]
+fn test_default_options_follow_utils() {
+    let options = TokenizerOptions::default();
+    for unit in (0..=0x7f_u8).map(char::from) {
+        assert_eq!(
+            options.is_opchar(unit),
+            utils::is_opchar(unit),
+            "{:?}",
+            unit
+        );
+        assert_eq!(
+            options.is_comment(unit),
+            utils::is_comment(unit),
+            "{:?}",
+            unit
+        );
+        assert_eq!(
+            options.is_identifier_start(unit),
+            utils::is_identifier_start(unit),
+            "{:?}",
+            unit
+        );
+        assert_eq!(
+            options.is_identifier(unit),
+            utils::is_identifier(unit),
+            "{:?}",
+            unit
+        );
+    }
+    assert_eq!(options.get_operator_chars(), DEFAULT_OPERATOR_CHARS);
+    assert_eq!(options.get_identifier_rules(), IdentifierRules::Ascii);
+}
+
+#[test]
+fn test_comment_char() {
+    let options = TokenizerOptions::new().with_comment_char('%');
+    let tokens = kinds("a % b\nc", options.clone());
+    assert_eq!(tokens, vec![
+        TokenKind::Identifier,
+        TokenKind::Identifier,
+        TokenKind::Eof
+    ]);
+
+    // The default comment character is no longer special.
+    let mut tokenizer = options.build();
+    let mut stream = FileStream::from("# c");
+    assert!(tokenizer.next_token(&mut stream).is_err());
+}
+
+#[test]
+fn test_operator_chars() {
+    let options = TokenizerOptions::new().with_operator_chars(&['+', '=']);
+    assert!(options.is_opchar('+'));
+    assert!(!options.is_opchar('-'));
+    let tokens = tokenize("a += b", options);
+    assert_eq!(tokens[1].span, "+");
+    assert_eq!(tokens[2].span, "=");
+}
+
+#[test]
+fn test_custom_operator_chars() {
+    let mut operator_chars = DEFAULT_OPERATOR_CHARS.to_vec();
+    operator_chars.push('£');
+    let options = TokenizerOptions::new().with_operator_chars(&operator_chars);
+    assert_eq!(kinds("a £ b ££ |", options), vec![
+        TokenKind::Identifier,
+        TokenKind::Operator(Operator::Custom('£')),
+        TokenKind::Identifier,
+        TokenKind::Operator(Operator::Custom('£')),
+        TokenKind::Operator(Operator::Custom('£')),
+        TokenKind::Operator(Operator::BitwiseOr),
+        TokenKind::Eof
+    ]);
+    assert_eq!(Operator::Custom('£').to_string(), "£");
+    assert_eq!(format!("{:>2}", Operator::Custom('£')), " £");
+}
+
+#[test]
+fn test_identifier_rules() {
+    let tokens = tokenize("größe", TokenizerOptions::new());
+    assert_eq!(tokens[0].span, "gr");
+
+    let options = TokenizerOptions::new().with_identifier_rules(IdentifierRules::Unicode);
+    let tokens = tokenize("größe _x1", options);
+    assert_eq!(tokens[0].span, "größe");
+    assert_eq!(tokens[0].token_kind, TokenKind::Identifier);
+    assert_eq!(tokens[1].span, "_x1");
+}
+
+#[test]
+fn test_keywords() {
+    let options = TokenizerOptions::new()
+        .with_keyword("fn", Keyword::Def)
+        .without_keyword("def");
+    assert_eq!(options.get_keyword("fn"), Some(Keyword::Def));
+    assert_eq!(options.get_keyword("def"), None);
+    let tokens = kinds("fn def", options);
+    assert_eq!(tokens[0], TokenKind::Keyword(Keyword::Def));
+    assert_eq!(tokens[1], TokenKind::Identifier);
+
+    let options = TokenizerOptions::new().with_keywords(&[("extern", Keyword::Extern)]);
+    assert_eq!(options.get_keywords().len(), 1);
+    let tokens = kinds("extern def", options);
+    assert_eq!(tokens[0], TokenKind::Keyword(Keyword::Extern));
+    assert_eq!(tokens[1], TokenKind::Identifier);
+}
+
+#[test]
+fn test_tab_width() {
+    let options = TokenizerOptions::new().with_tab_width(4);
+    assert_eq!(options.get_tab_width(), Some(4));
+    let narrow = tokenize("\tx\ty", options);
+    let wide = tokenize("\tx\ty", TokenizerOptions::new().with_tab_width(8));
+    for (narrow, wide) in narrow.iter().zip(wide.iter()) {
+        assert_eq!(narrow.start.get_column(), wide.start.get_column());
+    }
+    assert_eq!(narrow[0].start.get_display_column(), 5);
+    assert_eq!(narrow[1].start.get_display_column(), 9);
+    assert_eq!(wide[1].start.get_display_column(), 17);
+
+    // Without a tab width in the options, the stream keeps its own.
+    assert_eq!(TokenizerOptions::new().get_tab_width(), None);
+    let mut stream = FileStream::from("\tx");
+    stream.set_tab_width(2);
+    let iterator = TokenizerOptions::new().build().to_iter(stream);
+    assert_eq!(iterator.get_stream().get_tab_width(), 2);
+    let iterator = TokenizerOptions::new()
+        .with_tab_width(3)
+        .build()
+        .to_iter(iterator.into_stream());
+    assert_eq!(iterator.get_stream().get_tab_width(), 3);
+}
@@ -0,0 +1,100 @@
+use std::convert::TryFrom;
+
+use crate::{
+    token::{FileIndex, Keyword, Token, TokenKind},
+    tokenizer::{FileStream, LexerSerializer, TokenTable, Tokenizer, TokenizerOptions}
+};
+
+/// A programme that produces every kind of token the tokenizer can produce.
+const SOURCE: &str = "## Doc comment with\tünïcödé 🎉\r\n\
+                      def binary@ 5 (a b) a.b + 1.5;\r\n\
+                      (a, b) [c] {d} <e>\n\
+                      + - * / < > == <= >= ! ^ % != & | ~ << >> && || = @ : $ ? \\\n";
+
+fn keyword_source() -> String {
+    Keyword::all()
+        .iter()
+        .map(|keyword| keyword.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Get the position of `kind` in the list of covered token kinds. The match
+/// is exhaustive, so a new [`TokenKind`] variant fails to compile here until
+/// it is covered by the round-trip tests.
+fn kind_index(kind: &TokenKind) -> usize {
+    match kind {
+        TokenKind::Unknown => 0,
+        TokenKind::Eof => 1,
+        TokenKind::Keyword(_) => 2,
+        TokenKind::Identifier => 3,
+        TokenKind::Integer => 4,
+        TokenKind::Float => 5,
+        TokenKind::Operator(_) => 6,
+        TokenKind::Bracket(_) => 7,
+        TokenKind::Comma => 8,
+        TokenKind::Dot => 9,
+        TokenKind::Semicolon => 10,
+        TokenKind::DocComment => 11
+    }
+}
+
+fn table() -> TokenTable {
+    let stream = FileStream::from_reader(SOURCE.as_bytes(), "first.kld")
+        .chain(std::io::Cursor::new(keyword_source()), "second.kld");
+    let mut table = TokenTable::try_from(Tokenizer::new().to_iter(stream)).unwrap();
+    let start = FileIndex::new(Some(2), 3).with_source(1);
+    table
+        .tokens
+        .push(Token::new(TokenKind::Unknown, "?".to_owned(), start, start));
+    table
+}
+
+#[test]
+fn test_table_covers_every_token_kind() {
+    let table = table();
+    let mut covered = [false; 12];
+    for token in table.tokens.iter() {
+        covered[kind_index(&token.token_kind)] = true;
+    }
+    assert!(covered.iter().all(|&covered| covered), "{:?}", covered);
+    for keyword in Keyword::all() {
+        assert!(table
+            .tokens
+            .iter()
+            .any(|token| token.token_kind == TokenKind::Keyword(*keyword)));
+    }
+    assert!(table
+        .tokens
+        .iter()
+        .any(|token| token.start.get_source() == 1));
+}
+
+#[test]
+fn test_toml_round_trip() {
+    let table = table();
+    let value = toml::Value::try_from(&table).unwrap();
+    let text = toml::to_string_pretty(&value).unwrap();
+    let read: TokenTable = toml::from_str(&text).unwrap();
+    assert_eq!(read.tokens, table.tokens);
+}
+
+#[test]
+fn test_json_round_trip() {
+    let table = table();
+    let text = serde_json::to_string(&table.tokens).unwrap();
+    let read: Vec<Token> = serde_json::from_str(&text).unwrap();
+    assert_eq!(read, table.tokens);
+}
+
+#[test]
+fn test_lexer_errors_are_returned() {
+    // '#' is not an operator character, so it cannot be tokenized once it is
+    // no longer the comment character.
+    let options = TokenizerOptions::new().with_comment_char('%');
+    let iterator = options.clone().build().to_iter(FileStream::from("a # b"));
+    assert!(TokenTable::try_from(iterator).is_err());
+
+    let iterator = options.build().to_iter(FileStream::from("a # b"));
+    assert!(serde_json::to_string(&LexerSerializer::new(iterator)).is_err());
+}
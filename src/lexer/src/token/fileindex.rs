@@ -8,6 +8,33 @@ use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 use serde::{Deserialize, Serialize};
 
+/// The unit in which the column of a [`FileIndex`] is measured.
+///
+/// Different consumers of a [`FileIndex`] expect columns to be counted in
+/// different ways. Rust strings are indexed by UTF-8 bytes, editors and the
+/// Language Server Protocol count UTF-16 code units and the lexer itself
+/// counts Unicode scalar values (UTF-32 code units).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnEncoding {
+    /// Count the number of bytes the characters take up in UTF-8.
+    Utf8,
+    /// Count the number of UTF-16 code units. Characters outside of the
+    /// Basic Multilingual Plane take up 2 code units.
+    Utf16,
+    /// Count the number of Unicode scalar values. This is the default.
+    Utf32
+}
+
+impl Default for ColumnEncoding {
+    fn default() -> Self {
+        ColumnEncoding::Utf32
+    }
+}
+
+/// The default number of columns a tab character advances to when columns
+/// are displayed to a human.
+pub const DEFAULT_TAB_WIDTH: usize = 8;
+
 /// Represents the location of a character
 /// (i.e. displayed glyphs, diacritics are counted as separate symbols)
 /// in a file or stream.
@@ -17,6 +44,13 @@ use serde::{Deserialize, Serialize};
 /// column of the characters (i.e. how many characters there are before it).
 /// If the character is the first of many in a line, then its column will be 0.
 ///
+/// The column is tracked in every [`ColumnEncoding`] at once, as well as
+/// in a "display" column where tabs are expanded to the next tab stop.
+/// [`FileIndex::get_column`] reports the column in
+/// [`ColumnEncoding::Utf32`], and [`FileIndex::get_column_in`] in any other
+/// encoding. Which encoding a consumer wants is up to the consumer (see
+/// [`crate::tokenizer::FileStream::get_column`]).
+///
 /// If you don't know how many lines have passed, or if that data is
 /// irrelevant, you can set [`FileIndex::line`] to `None` in
 /// [`FileIndex::new`].
@@ -24,18 +58,72 @@ use serde::{Deserialize, Serialize};
 /// You can also change the column of a `FileIndex` object using the
 /// add (`+`), add_assign (`+=`), sub (`-`) and sub_assign (`-=`) operators
 /// since I've so handily implemented the traits that code for these
-/// operators.
+/// operators. These operators assume that every character skipped over is
+/// a single-byte character that is not a tab.
 #[allow(rustdoc::private_intra_doc_links)]
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "FileIndexData")]
 pub struct FileIndex {
-    line:   Option<usize>,
-    column: usize
+    line:           Option<usize>,
+    column:         usize,
+    utf8_column:    usize,
+    utf16_column:   usize,
+    display_column: usize,
+    source:         usize
+}
+
+/// The serialized form of a [`FileIndex`]. The columns that were not always
+/// serialized are optional, and default to the (UTF-32) column since every
+/// encoding agrees with it on ASCII text without tabs.
+#[derive(Deserialize)]
+struct FileIndexData {
+    line:           Option<usize>,
+    column:         usize,
+    utf8_column:    Option<usize>,
+    utf16_column:   Option<usize>,
+    display_column: Option<usize>,
+    #[serde(default)]
+    source:         usize
+}
+
+impl From<FileIndexData> for FileIndex {
+    fn from(data: FileIndexData) -> Self {
+        Self {
+            line:           data.line,
+            column:         data.column,
+            utf8_column:    data.utf8_column.unwrap_or(data.column),
+            utf16_column:   data.utf16_column.unwrap_or(data.column),
+            display_column: data.display_column.unwrap_or(data.column),
+            source:         data.source
+        }
+    }
 }
 
 impl FileIndex {
     /// Create a new [`FileIndex`] object.
     pub fn new(line: Option<usize>, column: usize) -> Self {
-        Self { line, column }
+        Self {
+            line,
+            column,
+            utf8_column: column,
+            utf16_column: column,
+            display_column: column,
+            source: 0
+        }
+    }
+
+    /// Set the ID of the source this index points into, returning the
+    /// modified index.
+    pub fn with_source(mut self, source: usize) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Get the ID of the source (e.g. a file) this index points into. The
+    /// name of the source can be retrieved from the
+    /// [`crate::tokenizer::FileStream`] that produced this index.
+    pub fn get_source(&self) -> usize {
+        self.source
     }
 
     /// Get the line of the represented by this object.
@@ -49,11 +137,59 @@ impl FileIndex {
         self.line.unwrap_or(0)
     }
 
-    /// Get the column of the represented by this index.
+    /// Get the column of the represented by this index, counted in Unicode
+    /// scalar values ([`ColumnEncoding::Utf32`]).
     pub fn get_column(&self) -> usize {
         self.column
     }
 
+    /// Get the column of the represented by this index in a specific
+    /// encoding.
+    pub fn get_column_in(&self, encoding: ColumnEncoding) -> usize {
+        match encoding {
+            ColumnEncoding::Utf8 => self.utf8_column,
+            ColumnEncoding::Utf16 => self.utf16_column,
+            ColumnEncoding::Utf32 => self.column
+        }
+    }
+
+    /// Get the column as it would appear to a human, with every tab
+    /// expanded to the next tab stop. The width of each tab stop is the
+    /// `tab_width` passed to [`FileIndex::advance`].
+    pub fn get_display_column(&self) -> usize {
+        self.display_column
+    }
+
+    /// Move the index past `unit`, expanding tabs to the next multiple of
+    /// `tab_width` in the display column. If `tab_width` is 0, a tab is
+    /// treated like any other character.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kaleidoscope_lexer::token::{ColumnEncoding, FileIndex};
+    ///
+    /// let mut index = FileIndex::new(Some(0), 0);
+    /// for unit in "\t\u{1F600}x".chars() {
+    ///     index.advance(unit, 4);
+    /// }
+    /// assert_eq!(index.get_column(), 3);
+    /// assert_eq!(index.get_column_in(ColumnEncoding::Utf8), 6);
+    /// assert_eq!(index.get_column_in(ColumnEncoding::Utf16), 4);
+    /// assert_eq!(index.get_display_column(), 6);
+    /// ```
+    pub fn advance(&mut self, unit: char, tab_width: usize) -> &mut Self {
+        self.column += 1;
+        self.utf8_column += unit.len_utf8();
+        self.utf16_column += unit.len_utf16();
+        self.display_column = if unit == '\t' && tab_width > 0 {
+            (self.display_column / tab_width + 1) * tab_width
+        } else {
+            self.display_column + 1
+        };
+        self
+    }
+
     /// Get the position of a character after a newline.
     ///
     /// If the number of lines passed has been specified, then the line
@@ -82,11 +218,10 @@ impl FileIndex {
     /// assert!(fi_2.get_column() == 7);
     /// ```
     pub fn newline(&self, newline_length: usize) -> Self {
-        let (line, column) = match self.line {
-            Some(l) => (Some(l + 1), 0),
-            None => (None, self.column + newline_length)
-        };
-        Self { line, column }
+        match self.line {
+            Some(l) => Self::new(Some(l + 1), 0).with_source(self.source),
+            None => *self + newline_length
+        }
     }
 }
 
@@ -103,41 +238,40 @@ impl std::fmt::Display for FileIndex {
 
 impl Default for FileIndex {
     fn default() -> Self {
-        Self {
-            line:   None,
-            column: 0
-        }
+        Self::new(None, 0)
     }
 }
 
 impl Add<usize> for FileIndex {
     type Output = Self;
-    fn add(self, rhs: usize) -> Self::Output {
-        Self {
-            line:   self.get_line(),
-            column: self.get_column() + rhs
-        }
+    fn add(mut self, rhs: usize) -> Self::Output {
+        self += rhs;
+        self
     }
 }
 
 impl AddAssign<usize> for FileIndex {
     fn add_assign(&mut self, rhs: usize) {
         self.column += rhs;
+        self.utf8_column += rhs;
+        self.utf16_column += rhs;
+        self.display_column += rhs;
     }
 }
 
 impl Sub<usize> for FileIndex {
     type Output = Self;
-    fn sub(self, rhs: usize) -> Self::Output {
-        Self {
-            line:   self.get_line(),
-            column: self.get_column() - rhs
-        }
+    fn sub(mut self, rhs: usize) -> Self::Output {
+        self -= rhs;
+        self
     }
 }
 
 impl SubAssign<usize> for FileIndex {
     fn sub_assign(&mut self, rhs: usize) {
         self.column -= rhs;
+        self.utf8_column -= rhs;
+        self.utf16_column -= rhs;
+        self.display_column -= rhs;
     }
 }
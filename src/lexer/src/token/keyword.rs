@@ -20,7 +20,38 @@ pub enum Keyword {
     /// `else` keyword. Control flow.
     Else,
     /// `then` keyword. Control flow.
-    Then
+    Then,
+    /// `binary` keyword. Define a binary operator.
+    Binary,
+    /// `unary` keyword. Define a unary operator.
+    Unary,
+    /// `and` keyword. Another spelling of the `&&` operator.
+    And,
+    /// `or` keyword. Another spelling of the `||` operator.
+    Or,
+    /// `for` keyword. Loop over a range of numbers.
+    For,
+    /// `in` keyword. Separates the header of a loop from its body.
+    In,
+    /// `while` keyword. Loop for as long as a condition is true.
+    While,
+    /// `do` keyword. Separates the condition of a `while` loop from its
+    /// body.
+    Do,
+    /// `break` keyword. Leave the innermost loop.
+    Break,
+    /// `continue` keyword. Start the next iteration of the innermost loop.
+    Continue,
+    /// `var` keyword. Declare mutable local variables.
+    Var,
+    /// `let` keyword. Bind a name to a value that cannot change.
+    Let,
+    /// `const` keyword. Declare a global constant.
+    Const,
+    /// `global` keyword. Declare a global variable.
+    Global,
+    /// `return` keyword. Return from a function early.
+    Return
 }
 
 impl Keyword {
@@ -47,10 +78,51 @@ impl Keyword {
             "if" => Keyword::If,
             "else" => Keyword::Else,
             "then" => Keyword::Then,
+            "binary" => Keyword::Binary,
+            "unary" => Keyword::Unary,
+            "and" => Keyword::And,
+            "or" => Keyword::Or,
+            "for" => Keyword::For,
+            "in" => Keyword::In,
+            "while" => Keyword::While,
+            "do" => Keyword::Do,
+            "break" => Keyword::Break,
+            "continue" => Keyword::Continue,
+            "var" => Keyword::Var,
+            "let" => Keyword::Let,
+            "const" => Keyword::Const,
+            "global" => Keyword::Global,
+            "return" => Keyword::Return,
             _ => return None
         })
     }
 
+    /// Get every keyword.
+    pub fn all() -> &'static [Keyword] {
+        &[
+            Keyword::Def,
+            Keyword::Extern,
+            Keyword::If,
+            Keyword::Else,
+            Keyword::Then,
+            Keyword::Binary,
+            Keyword::Unary,
+            Keyword::And,
+            Keyword::Or,
+            Keyword::For,
+            Keyword::In,
+            Keyword::While,
+            Keyword::Do,
+            Keyword::Break,
+            Keyword::Continue,
+            Keyword::Var,
+            Keyword::Let,
+            Keyword::Const,
+            Keyword::Global,
+            Keyword::Return
+        ]
+    }
+
     /// Convert the keyword enum into a string representation of itself.
     ///
     /// # Example
@@ -66,7 +138,22 @@ impl Keyword {
             Keyword::Extern => "extern",
             Keyword::If => "if",
             Keyword::Else => "else",
-            Keyword::Then => "then"
+            Keyword::Then => "then",
+            Keyword::Binary => "binary",
+            Keyword::Unary => "unary",
+            Keyword::And => "and",
+            Keyword::Or => "or",
+            Keyword::For => "for",
+            Keyword::In => "in",
+            Keyword::While => "while",
+            Keyword::Do => "do",
+            Keyword::Break => "break",
+            Keyword::Continue => "continue",
+            Keyword::Var => "var",
+            Keyword::Let => "let",
+            Keyword::Const => "const",
+            Keyword::Global => "global",
+            Keyword::Return => "return"
         }
     }
 }
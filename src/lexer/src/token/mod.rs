@@ -15,8 +15,8 @@ pub use bracket::{
     BracketKind,
     BracketSide
 };
-pub use fileindex::FileIndex;
+pub use fileindex::{ColumnEncoding, FileIndex, DEFAULT_TAB_WIDTH};
 pub use keyword::Keyword;
-pub use operator::Operator;
+pub use operator::{Operator, CUSTOM_OPERATOR_CHARS};
 pub use token::Token;
 pub use tokenkind::TokenKind;
@@ -4,12 +4,23 @@
 //! syntax. Due to its significance, operators are given a special
 //! representation in code.
 
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
 use serde::{Deserialize, Serialize};
 
+/// The operator characters that do not have a built-in meaning by default, but
+/// can be given one by defining a user-defined operator (e.g.
+/// `def binary@ 5 (a, b)`). Any other character added with
+/// [`crate::tokenizer::TokenizerOptions::with_operator_chars`] can be used in
+/// the same way.
+///
+/// Built-in operators can be redefined as well (such as `def binary| 5 (a b)`
+/// or `def unary! (x)`), or be given a meaning in a position they do not have
+/// one in (such as `def unary| (x)`). Only `=` cannot be redefined.
+pub const CUSTOM_OPERATOR_CHARS: &[char] = &[':', '@', '$', '?', '\\'];
+
 /// A token representing an operator.
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Operator {
     /// A separate variant for inputs that is not a valid operator
     Unknown,
@@ -30,12 +41,47 @@ pub enum Operator {
     /// <=
     LessThanEqual,
     /// >=
-    GreaterThanEqual
+    GreaterThanEqual,
+    /// !
+    Not,
+    /// ^
+    Power,
+    /// %
+    Modulo,
+    /// !=
+    NotEquals,
+    /// &
+    ///
+    /// This and the other bitwise operators (`|`, `~`, `<<` and `>>`) only
+    /// work on integers. Using them on a number known to be a float is a
+    /// type error, while a float only known to be one at runtime (such as a
+    /// parameter) is truncated to an integer first.
+    BitwiseAnd,
+    /// |
+    BitwiseOr,
+    /// ~
+    BitwiseNot,
+    /// <<
+    ShiftLeft,
+    /// \>>
+    ShiftRight,
+    /// && (also spelt `and`)
+    LogicalAnd,
+    /// || (also spelt `or`)
+    LogicalOr,
+    /// =
+    Assign,
+    /// A single operator character without a built-in meaning (such as one
+    /// from [`CUSTOM_OPERATOR_CHARS`]), which only has a meaning once a
+    /// user-defined operator is defined for it. It is serialized as the
+    /// character by itself.
+    #[serde(untagged)]
+    Custom(char)
 }
 
 impl fmt::Display for Operator {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.pad(self.to_string())
+        f.pad(&self.to_string())
     }
 }
 
@@ -66,7 +112,25 @@ impl Operator {
             "==" => Equals,
             "<=" => LessThanEqual,
             ">=" => GreaterThanEqual,
-            _ => Unknown
+            "!" => Not,
+            "^" => Power,
+            "%" => Modulo,
+            "!=" => NotEquals,
+            "&" => BitwiseAnd,
+            "|" => BitwiseOr,
+            "~" => BitwiseNot,
+            "<<" => ShiftLeft,
+            ">>" => ShiftRight,
+            "&&" => LogicalAnd,
+            "||" => LogicalOr,
+            "=" => Assign,
+            _ => {
+                let mut units = slice.chars();
+                match (units.next(), units.next()) {
+                    (Some(unit), None) if CUSTOM_OPERATOR_CHARS.contains(&unit) => Custom(unit),
+                    _ => Unknown
+                }
+            }
         }
     }
 
@@ -79,10 +143,13 @@ impl Operator {
     ///
     /// let minus = Operator::Minus;
     /// assert_eq!(minus.to_string(), "-");
+    ///
+    /// let custom = Operator::Custom('£');
+    /// assert_eq!(custom.to_string(), "£");
     /// ```
-    pub fn to_string(&self) -> &'static str {
+    pub fn to_string(&self) -> Cow<'static, str> {
         use Operator::*;
-        match self {
+        Cow::Borrowed(match self {
             Plus => "+",
             Minus => "-",
             Multiply => "*",
@@ -92,7 +159,36 @@ impl Operator {
             Equals => "==",
             LessThanEqual => "<=",
             GreaterThanEqual => ">=",
+            Not => "!",
+            Power => "^",
+            Modulo => "%",
+            NotEquals => "!=",
+            BitwiseAnd => "&",
+            BitwiseOr => "|",
+            BitwiseNot => "~",
+            ShiftLeft => "<<",
+            ShiftRight => ">>",
+            LogicalAnd => "&&",
+            LogicalOr => "||",
+            Assign => "=",
+            Custom(unit) => return Cow::Owned(unit.to_string()),
             Unknown => "???"
-        }
+        })
+    }
+
+    /// Check if this operator has a built-in meaning, as opposed to one that
+    /// can only be given by a user-defined operator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kaleidoscope_lexer::token::Operator;
+    ///
+    /// assert!(Operator::from_string("+").is_builtin());
+    /// assert_eq!(Operator::from_string("@"), Operator::Custom('@'));
+    /// assert!(!Operator::Custom('@').is_builtin());
+    /// ```
+    pub fn is_builtin(&self) -> bool {
+        !matches!(self, Operator::Custom(_) | Operator::Unknown)
     }
 }
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use super::{Bracket, BracketKind, FileIndex, Keyword, Operator, TokenKind};
 use crate::{
     error::{Error, ErrorKind, Result},
+    tokenizer::TokenizerOptions,
     utils
 };
 
@@ -17,7 +18,7 @@ use crate::{
 /// possible `TokenKind` of the token,
 /// the token as a string (stored as `span`),
 /// as well as the start and end indices of the token.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Token {
     pub token_kind: TokenKind,
     pub span:       String,
@@ -70,19 +71,66 @@ impl Token {
     }
 
     /// Check whether this token is allowed to occur at the end of an
-    /// expression.
+    /// expression. Besides punctuation, this includes the `in` and `do`
+    /// keywords that follow the header of a loop.
     pub fn denotes_end_of_expression(&self) -> bool {
         match self.token_kind {
             TokenKind::Eof
             | TokenKind::Dot
             | TokenKind::Comma
             | TokenKind::Semicolon
-            | TokenKind::Operator { .. } => true,
+            | TokenKind::Operator { .. }
+            | TokenKind::Keyword(Keyword::In)
+            | TokenKind::Keyword(Keyword::Do) => true,
             TokenKind::Bracket(bracket) => bracket.side.is_right(),
             _ => false
         }
     }
 
+    /// Check if this token is a documentation comment.
+    pub fn is_doc_comment(&self) -> bool {
+        matches!(self.token_kind, TokenKind::DocComment)
+    }
+
+    /// Get the text in a documentation comment, without the leading comment
+    /// characters (`##`) and the space after it. If this token is not a
+    /// documentation comment, [`None`] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kaleidoscope_lexer::tokenizer::{FileStream, Tokenizer};
+    ///
+    /// let stream = FileStream::from("## Add 2 numbers.\ndef add(a, b) a + b");
+    /// let mut tokens = Tokenizer::new().to_iter(stream);
+    /// let doc = tokens.next().unwrap();
+    /// assert_eq!(doc.get_doc_comment(), Some("Add 2 numbers."));
+    /// assert_eq!(tokens.next().unwrap().span, "def");
+    /// ```
+    pub fn get_doc_comment(&self) -> Option<&str> {
+        if !self.is_doc_comment() {
+            return None;
+        }
+        let comment_char = self.span.chars().next()?;
+        let text = self.span.trim_start_matches(comment_char);
+        Some(text.strip_prefix(' ').unwrap_or(text))
+    }
+
+    /// Turn a comment collected by the tokenizer into a documentation comment
+    /// if it starts with 2 comment characters (`##` by default), returning
+    /// whether it is one.
+    pub(crate) fn resolve_doc_comment(&mut self, index: FileIndex, comment_char: char) -> bool {
+        let mut units = self.span.chars();
+        if units.next() != Some(comment_char) || units.next() != Some(comment_char) {
+            return false;
+        }
+        let trimmed = self.span.trim_end_matches('\r').len();
+        self.span.truncate(trimmed);
+        self.token_kind = TokenKind::DocComment;
+        self.end = index;
+        true
+    }
+
     /// Check if the span in the token is empty or not.
     pub fn is_empty(&self) -> bool {
         self.span.is_empty()
@@ -95,26 +143,45 @@ impl Token {
     /// this token cannot accept, so this token is complete.
     /// If `false` is returned, it means that `unit` has been added to the
     /// token's span and can continue to collect more characters.
+    ///
+    /// The default lexical rules are used. See [`Token::add_unit_with`] to
+    /// use a different set of rules.
     pub fn add_unit(&mut self, unit: char, index: FileIndex) -> Result<bool> {
+        self.add_unit_with(unit, index, &TokenizerOptions::default())
+    }
+
+    /// Add a character into the token, following the lexical rules in
+    /// `options`. See [`Token::add_unit`].
+    pub fn add_unit_with(
+        &mut self,
+        unit: char,
+        index: FileIndex,
+        options: &TokenizerOptions
+    ) -> Result<bool> {
         if self.is_empty() {
-            self.add_unit_when_empty(unit, index)
+            self.add_unit_when_empty(unit, index, options)
         } else {
-            self.add_unit_when_not_empty(unit, index)
+            self.add_unit_when_not_empty(unit, index, options)
         }
     }
 
-    fn add_unit_when_empty(&mut self, unit: char, index: FileIndex) -> Result<bool> {
+    fn add_unit_when_empty(
+        &mut self,
+        unit: char,
+        index: FileIndex,
+        options: &TokenizerOptions
+    ) -> Result<bool> {
         self.start = index;
         if utils::is_whitespace(unit) {
             return Ok(false);
         }
         self.span.push(unit);
-        if utils::is_identifier_start(unit) {
+        if options.is_identifier_start(unit) {
             self.token_kind = TokenKind::Identifier;
         } else if utils::is_decimal_digit(unit) {
             self.token_kind = TokenKind::Integer;
-        } else if utils::is_opchar(unit) {
-            let operator = Operator::from_string(self.borrow_span());
+        } else if options.is_opchar(unit) {
+            let operator = operator_from(self.borrow_span(), options);
             self.token_kind = TokenKind::Operator(operator);
         } else if utils::is_bracket(unit) {
             let bracket = Bracket::from_string(self.borrow_span());
@@ -135,15 +202,20 @@ impl Token {
         Ok(false)
     }
 
-    fn add_unit_when_not_empty(&mut self, unit: char, index: FileIndex) -> Result<bool> {
+    fn add_unit_when_not_empty(
+        &mut self,
+        unit: char,
+        index: FileIndex,
+        options: &TokenizerOptions
+    ) -> Result<bool> {
         if utils::is_whitespace(unit) {
-            return self.resolve(index);
+            return self.resolve_with(index, options);
         }
         match self.token_kind {
-            TokenKind::Identifier => self.add_unit_if_identifier(unit, index),
+            TokenKind::Identifier => self.add_unit_if_identifier(unit, index, options),
             TokenKind::Integer => self.add_unit_if_integer(unit, index),
             TokenKind::Float => self.add_unit_if_float(unit, index),
-            TokenKind::Operator { .. } => self.add_unit_if_operator(unit, index),
+            TokenKind::Operator { .. } => self.add_unit_if_operator(unit, index, options),
             TokenKind::Bracket { .. } => self.add_unit_if_bracket(unit, index),
             TokenKind::Comma | TokenKind::Dot | TokenKind::Semicolon => Ok(true),
             _ => Err(Error::new(
@@ -154,12 +226,17 @@ impl Token {
         }
     }
 
-    fn add_unit_if_identifier(&mut self, unit: char, index: FileIndex) -> Result<bool> {
-        if utils::is_identifier(unit) {
+    fn add_unit_if_identifier(
+        &mut self,
+        unit: char,
+        index: FileIndex,
+        options: &TokenizerOptions
+    ) -> Result<bool> {
+        if options.is_identifier(unit) {
             self.span.push(unit);
             Ok(false)
         } else {
-            self.resolve(index)
+            self.resolve_with(index, options)
         }
     }
 
@@ -185,13 +262,26 @@ impl Token {
         }
     }
 
-    fn add_unit_if_operator(&mut self, unit: char, _index: FileIndex) -> Result<bool> {
-        if utils::is_opchar(unit) {
-            self.span.push(unit);
-            Ok(false)
-        } else {
-            Ok(true)
+    fn add_unit_if_operator(
+        &mut self,
+        unit: char,
+        index: FileIndex,
+        options: &TokenizerOptions
+    ) -> Result<bool> {
+        if options.is_opchar(unit) {
+            // Only grow the operator if it stays valid, so that `1+-2` is
+            // read as `+` followed by `-` instead of an unknown `+-`.
+            let mut span = self.span.clone();
+            span.push(unit);
+            let extended = operator_from(&span[..], options);
+            let current = operator_from(self.borrow_span(), options);
+            if extended != Operator::Unknown || current == Operator::Unknown {
+                self.span = span;
+                self.token_kind = TokenKind::Operator(extended);
+                return Ok(false);
+            }
         }
+        self.resolve_operator(index, options)
     }
 
     fn add_unit_if_bracket(&mut self, _unit: char, _index: FileIndex) -> Result<bool> {
@@ -199,8 +289,12 @@ impl Token {
         Ok(true)
     }
 
-    fn resolve_identifier(&mut self, _index: FileIndex) -> Result<bool> {
-        if let Some(keyword) = Keyword::from_string(self.borrow_span()) {
+    fn resolve_identifier(
+        &mut self,
+        _index: FileIndex,
+        options: &TokenizerOptions
+    ) -> Result<bool> {
+        if let Some(keyword) = options.get_keyword(self.borrow_span()) {
             self.token_kind = TokenKind::Keyword(keyword);
         }
         Ok(true)
@@ -226,8 +320,8 @@ impl Token {
         }
     }
 
-    fn resolve_operator(&mut self, index: FileIndex) -> Result<bool> {
-        match Operator::from_string(self.borrow_span()) {
+    fn resolve_operator(&mut self, index: FileIndex, options: &TokenizerOptions) -> Result<bool> {
+        match operator_from(self.borrow_span(), options) {
             Operator::Unknown => Err(Error::new(
                 format!(
                     "Could not guess operator from span '{}' at index {}",
@@ -265,7 +359,16 @@ impl Token {
 
     /// Resolve the value of the token when the end of the sequence happens.
     /// If this function is successful, it will always return `Ok(true)`.
+    ///
+    /// The default lexical rules are used. See [`Token::resolve_with`] to
+    /// use a different set of rules.
     pub fn resolve(&mut self, index: FileIndex) -> Result<bool> {
+        self.resolve_with(index, &TokenizerOptions::default())
+    }
+
+    /// Resolve the value of the token when the end of the sequence happens,
+    /// following the lexical rules in `options`. See [`Token::resolve`].
+    pub fn resolve_with(&mut self, index: FileIndex, options: &TokenizerOptions) -> Result<bool> {
         self.end = index;
         match self.token_kind {
             TokenKind::Unknown => Err(Error::new(
@@ -276,9 +379,9 @@ impl Token {
                 ErrorKind::InvalidToken,
                 None
             )),
-            TokenKind::Identifier => self.resolve_identifier(index),
+            TokenKind::Identifier => self.resolve_identifier(index, options),
             TokenKind::Float => self.resolve_float(index),
-            TokenKind::Operator { .. } => self.resolve_operator(index),
+            TokenKind::Operator { .. } => self.resolve_operator(index, options),
             TokenKind::Bracket { .. } => self.resolve_bracket(index),
             _ => Ok(true)
         }
@@ -306,3 +409,15 @@ impl AsRef<str> for Token {
         self.borrow_span()
     }
 }
+
+/// Convert the span of an operator token to an [`Operator`]. On top of the
+/// spans accepted by [`Operator::from_string`], any single operator character
+/// in `options` without a built-in meaning is read as an
+/// [`Operator::Custom`].
+fn operator_from(span: &str, options: &TokenizerOptions) -> Operator {
+    let mut units = span.chars();
+    match (Operator::from_string(span), units.next(), units.next()) {
+        (Operator::Unknown, Some(unit), None) if options.is_opchar(unit) => Operator::Custom(unit),
+        (operator, ..) => operator
+    }
+}
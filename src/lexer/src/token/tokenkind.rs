@@ -11,7 +11,7 @@ use super::{Bracket, Keyword, Operator};
 ///
 /// That's the worst explanation I have ever written in my life.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(tag = "type")]
+#[serde(tag = "type", content = "value")]
 pub enum TokenKind {
     /// Default value for [`TokenKind`].
     /// Reserved for situations where the token kind cannot be guessed yet.
@@ -35,7 +35,10 @@ pub enum TokenKind {
     /// The token kind representing a period, used in attribute accessors.
     Dot,
     /// A semicolon denotes the end of a statement/expression
-    Semicolon
+    Semicolon,
+    /// A documentation comment (a comment starting with `##`) describing the
+    /// function or extern function after it.
+    DocComment
 }
 
 impl TokenKind {
@@ -5,21 +5,111 @@
 //! See [`FileStream`].
 
 use std::{
+    collections::VecDeque,
     convert::TryFrom,
     fs::OpenOptions,
-    io::{stdin, BufRead, BufReader, Lines, Read},
+    io::{self, stdin, BufRead, BufReader, Read},
     iter::{Enumerate, Iterator},
     path::Path
 };
 
 use crate::{
     error::{Error, ErrorKind, Result},
-    token::FileIndex
+    token::{ColumnEncoding, FileIndex, DEFAULT_TAB_WIDTH}
 };
 
 /// An iterator that outputs each line in a Kaleidoscope programme one at a
 /// time.
-type BufferIterator<'a> = Enumerate<Lines<BufReader<Box<dyn Read + 'a>>>>;
+type BufferIterator<'a> = Enumerate<RawLines<BufReader<Box<dyn Read + 'a>>>>;
+
+/// The name given to a source that was not given a name.
+pub const ANONYMOUS_SOURCE_NAME: &str = "<anonymous>";
+
+/// The name given to the source when reading from [`stdin`].
+pub const STDIN_SOURCE_NAME: &str = "<stdin>";
+
+/// The sequence of characters that terminated a line. A carriage return
+/// (`\r`) on its own does not end a line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    /// A line feed (`\n`), used on Unix-like systems.
+    Lf,
+    /// A carriage return followed by a line feed (`\r\n`), used on Windows.
+    CrLf,
+    /// The line was not terminated. This only happens on the last line of a
+    /// file that does not end with a newline.
+    None
+}
+
+impl LineEnding {
+    /// Get the line ending at the end of `line`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kaleidoscope_lexer::tokenizer::LineEnding;
+    ///
+    /// assert_eq!(LineEnding::of("def f(x)\r\n"), LineEnding::CrLf);
+    /// assert_eq!(LineEnding::of("def f(x)\n"), LineEnding::Lf);
+    /// assert_eq!(LineEnding::of("def f(x)"), LineEnding::None);
+    /// ```
+    pub fn of(line: &str) -> Self {
+        if line.ends_with("\r\n") {
+            LineEnding::CrLf
+        } else if line.ends_with('\n') {
+            LineEnding::Lf
+        } else {
+            LineEnding::None
+        }
+    }
+
+    /// Get the line ending as a string.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::None => ""
+        }
+    }
+
+    /// Get the length of the line ending in bytes. Line endings are made of
+    /// ASCII characters, so this is also the number of characters, and can
+    /// be passed to [`FileIndex::newline`].
+    pub fn len(&self) -> usize {
+        self.as_str().len()
+    }
+
+    /// Check if there is no line ending.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, LineEnding::None)
+    }
+}
+
+/// An iterator over the lines of a buffered reader. Unlike
+/// [`std::io::Lines`], the line ending of each line is kept at the end of
+/// the string so that it can be reproduced exactly.
+pub struct RawLines<B> {
+    reader: B
+}
+
+impl<B: BufRead> RawLines<B> {
+    /// Create a new iterator over the lines of `reader`.
+    pub fn new(reader: B) -> Self {
+        Self { reader }
+    }
+}
+
+impl<B: BufRead> Iterator for RawLines<B> {
+    type Item = io::Result<String>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(line)),
+            Err(e) => Some(Err(e))
+        }
+    }
+}
 
 /// A file stream which returns a unicode codepoint one at a time.
 /// This is in contrast to a normal [`std::fs::File`] which can only read
@@ -28,27 +118,66 @@ type BufferIterator<'a> = Enumerate<Lines<BufReader<Box<dyn Read + 'a>>>>;
 /// An object of this struct also stores the index of the current character.
 /// See [`FileIndex`] for implementation details. This index stores the
 /// current line and character column, and can be retrieved by calling
-/// [`FileStream::get_index`].
+/// [`FileStream::get_index`]. The encoding consumers of this stream should
+/// report columns in can be changed with [`FileStream::set_column_encoding`]
+/// (see [`FileStream::get_column`]) and the width of a tab used for
+/// human-facing columns with [`FileStream::set_tab_width`].
+///
+/// Line endings are passed through to the caller unchanged, so a line
+/// terminated by `\r\n` yields both a `'\r'` and a `'\n'`, and the last line
+/// of a file that does not end with a newline yields no newline at all. The
+/// line ending of each line read so far can be retrieved with
+/// [`FileStream::get_line_endings`].
+///
+/// A [`FileStream`] can read from several sources one after another (see
+/// [`FileStream::chain`]), such as a prelude followed by the user's file.
+/// Each source has a name, and every [`FileIndex`] produced by the stream
+/// stores the ID of the source it came from (see [`FileIndex::get_source`]),
+/// which can be converted back into a name with
+/// [`FileStream::get_source_name`]. Line numbers restart from 0 at the
+/// beginning of each source. If a source does not end with a newline, a
+/// `'\n'` is yielded between it and the next source so that tokens never
+/// span 2 sources.
 pub struct FileStream<'a> {
-    buffer:      BufferIterator<'a>,
-    line:        Vec<char>,
-    cursor:      usize,
-    index:       FileIndex,
-    error:       Option<Error>,
-    eof_reached: bool
+    buffers:      VecDeque<BufferIterator<'a>>,
+    source_names: Vec<String>,
+    source:       usize,
+    line:         Vec<char>,
+    line_endings: Vec<LineEnding>,
+    cursor:       usize,
+    index:        FileIndex,
+    encoding:     ColumnEncoding,
+    tab_width:    usize,
+    error:        Option<Error>,
+    eof_reached:  bool,
+    unterminated: bool
 }
 
 impl<'a> FileStream<'a> {
     /// Create a new `FileStream` from an iterator over the lines of a buffered
-    /// reader.
+    /// reader. The source is named [`ANONYMOUS_SOURCE_NAME`].
     pub fn new(buffer: BufferIterator<'a>) -> Self {
+        Self::new_named(buffer, ANONYMOUS_SOURCE_NAME)
+    }
+
+    /// Create a new `FileStream` from an iterator over the lines of a buffered
+    /// reader, giving the source a name.
+    pub fn new_named(buffer: BufferIterator<'a>, name: &str) -> Self {
+        let mut buffers = VecDeque::new();
+        buffers.push_back(buffer);
         let mut this = FileStream {
-            buffer,
+            buffers,
+            source_names: vec![name.to_string()],
+            source: 0,
             cursor: 0,
             line: Vec::new(),
+            line_endings: Vec::new(),
             index: Default::default(),
+            encoding: Default::default(),
+            tab_width: DEFAULT_TAB_WIDTH,
             error: None,
-            eof_reached: false
+            eof_reached: false,
+            unterminated: false
         };
         this.init();
         this
@@ -76,6 +205,103 @@ impl<'a> FileStream<'a> {
         &mut self.index
     }
 
+    /// Get the ID of the source currently being read.
+    pub fn get_source(&self) -> usize {
+        self.source
+    }
+
+    /// Get the name of the source with the ID `source`.
+    pub fn get_source_name(&self, source: usize) -> Option<&str> {
+        self.source_names.get(source).map(|name| &name[..])
+    }
+
+    /// Get the name of the source currently being read.
+    pub fn get_current_source_name(&self) -> &str {
+        &self.source_names[self.source][..]
+    }
+
+    /// Get the names of all the sources in this stream, indexed by their
+    /// IDs.
+    pub fn get_source_names(&self) -> &[String] {
+        &self.source_names[..]
+    }
+
+    /// Get the line ending of the line currently being read. If no line has
+    /// been read yet, [`LineEnding::None`] is returned.
+    pub fn get_line_ending(&self) -> LineEnding {
+        self.line_endings
+            .last()
+            .copied()
+            .unwrap_or(LineEnding::None)
+    }
+
+    /// Get the line endings of every line that has been read so far, in
+    /// order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kaleidoscope_lexer::tokenizer::{FileStream, LineEnding};
+    ///
+    /// let mut stream = FileStream::from("extern def sin(x);\r\nsin(1)");
+    /// let units: String = (&mut stream).collect();
+    /// assert_eq!(units, "extern def sin(x);\r\nsin(1)");
+    /// assert_eq!(stream.get_line_endings(), [
+    ///     LineEnding::CrLf,
+    ///     LineEnding::None
+    /// ]);
+    /// ```
+    pub fn get_line_endings(&self) -> &[LineEnding] {
+        &self.line_endings[..]
+    }
+
+    /// Get the encoding the columns of the indices produced by this stream
+    /// are reported in by [`FileStream::get_column`].
+    pub fn get_column_encoding(&self) -> ColumnEncoding {
+        self.encoding
+    }
+
+    /// Set the encoding the columns of the indices produced by this stream
+    /// are reported in by [`FileStream::get_column`].
+    pub fn set_column_encoding(&mut self, encoding: ColumnEncoding) -> &mut Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Get the column of `index` (which should have been produced by this
+    /// stream) in the encoding returned by
+    /// [`FileStream::get_column_encoding`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kaleidoscope_lexer::{token::ColumnEncoding, tokenizer::FileStream};
+    ///
+    /// let mut stream = FileStream::from("\u{1F600}x");
+    /// stream.set_column_encoding(ColumnEncoding::Utf16);
+    /// stream.next();
+    /// let index = stream.get_index();
+    /// assert_eq!(index.get_column(), 1);
+    /// assert_eq!(stream.get_column(&index), 2);
+    /// ```
+    pub fn get_column(&self, index: &FileIndex) -> usize {
+        index.get_column_in(self.encoding)
+    }
+
+    /// Get the number of columns a tab advances to in
+    /// [`FileIndex::get_display_column`].
+    pub fn get_tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    /// Set the number of columns a tab advances to in
+    /// [`FileIndex::get_display_column`]. Only characters read after this
+    /// call are affected.
+    pub fn set_tab_width(&mut self, tab_width: usize) -> &mut Self {
+        self.tab_width = tab_width;
+        self
+    }
+
     /// Get the error currently being stored.
     pub fn get_err(&self) -> Option<Error> {
         self.error.clone()
@@ -96,18 +322,49 @@ impl<'a> FileStream<'a> {
     /// Otherwise, if the file/stream has ended or an error has happened,
     /// [`false`] is returned.
     pub fn next_line(&mut self) -> bool {
-        if let Some((line_no, line)) = self.buffer.next() {
+        let next = loop {
+            if self.buffers.is_empty() {
+                break None;
+            }
+            // Every source that has been read to the end is dropped, so the
+            // ID of the first buffer is the number of sources before it.
+            let source = self.source_names.len() - self.buffers.len();
+            if source != self.source && self.unterminated {
+                // Separate the last token of the previous source from the
+                // first token of the next one. The newline belongs to the
+                // last line of the previous source, so the index is left
+                // there until the next line is read.
+                self.unterminated = false;
+                self.line = vec!['\n'];
+                self.cursor = 0;
+                self.eof_reached = false;
+                return true;
+            }
+            match self.buffers.front_mut().and_then(|buffer| buffer.next()) {
+                Some(line) => {
+                    self.source = source;
+                    break Some(line);
+                },
+                None => {
+                    self.buffers.pop_front();
+                }
+            }
+        };
+        if let Some((line_no, line)) = next {
             match line {
                 Ok(l) => {
+                    // The line ending is kept in the line so that the
+                    // tokeniser knows that the end of the line has been
+                    // reached, which is useful for getting to the end of a
+                    // comment or statement. The last line may not have one,
+                    // in which case the EOF does the same job.
+                    let line_ending = LineEnding::of(&l);
+                    self.line_endings.push(line_ending);
+                    self.unterminated = line_ending.is_empty();
                     self.line = l.chars().collect();
-                    // Mandatory extra new line character so that
-                    // the tokeniser knows that the end of the line has
-                    // been reached.
-                    // Useful for getting to the end of a comment or statement
-                    self.line.push('\n');
                     self.cursor = 0;
                     self.eof_reached = false;
-                    self.index = FileIndex::new(Some(line_no), 0);
+                    self.index = FileIndex::new(Some(line_no), 0).with_source(self.source);
                     true
                 },
                 Err(e) => {
@@ -135,35 +392,102 @@ impl<'a> FileStream<'a> {
             } else {
                 let unit = self.get_unit();
                 self.cursor += 1;
-                self.index += 1;
+                if let Some(u) = unit {
+                    self.index.advance(u, self.tab_width);
+                }
                 break unit;
             }
         }
     }
 
+    /// Append a source to the end of this stream. The characters in this
+    /// source will be read after every source that came before it.
+    pub fn push_reader<R: Read + 'a>(&mut self, reader: R, name: &str) -> &mut Self {
+        self.buffers.push_back(Self::make_buffer(reader));
+        self.source_names.push(name.to_string());
+        if self.eof_reached {
+            // Restart the stream at the new source.
+            self.line.clear();
+            self.next_line();
+        }
+        self
+    }
+
+    /// Append a source to the end of this stream, returning the stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kaleidoscope_lexer::tokenizer::{FileStream, Tokenizer};
+    ///
+    /// let prelude = "extern def sin(x);";
+    /// let stream = FileStream::from_reader(prelude.as_bytes(), "prelude.kld")
+    ///     .chain("sin(1)".as_bytes(), "main.kld");
+    /// let tokens = Tokenizer::new().to_iter(stream).collect::<Vec<_>>();
+    /// // `;` is the last token in the prelude and `sin` is the first in
+    /// // main.kld.
+    /// assert_eq!(tokens[6].span, ";");
+    /// assert_eq!(tokens[6].start.get_source(), 0);
+    /// assert_eq!(tokens[7].span, "sin");
+    /// assert_eq!(tokens[7].start.get_source(), 1);
+    /// ```
+    pub fn chain<R: Read + 'a>(mut self, reader: R, name: &str) -> Self {
+        self.push_reader(reader, name);
+        self
+    }
+
+    fn make_buffer<R: Read + 'a>(reader: R) -> BufferIterator<'a> {
+        let read: Box<dyn Read + 'a> = Box::new(reader);
+        RawLines::new(BufReader::new(read)).enumerate()
+    }
+
+    /// Create a new [`FileStream`] from anything that implements [`Read`],
+    /// giving the source a name.
+    pub fn from_reader<R: Read + 'a>(reader: R, name: &str) -> Self {
+        Self::new_named(Self::make_buffer(reader), name)
+    }
+
     /// Create a [`FileStream`] from the [`stdin`] stream.
     /// By [`Default`], [`FileStream`] reads from stdin.
     pub fn from_stdin() -> Self {
-        let stdin: Box<dyn Read> = Box::new(stdin());
-        let buffer = BufReader::new(stdin).lines().enumerate();
-        Self::new(buffer)
+        Self::from_reader(stdin(), STDIN_SOURCE_NAME)
     }
 
-    /// Create a new [`FileStream`] from a path.
+    /// Create a new [`FileStream`] from a path. The source is named after the
+    /// path.
     pub fn from_path(path: &Path) -> Result<Self> {
-        let file: Box<dyn Read> = match OpenOptions::new().read(true).open(path) {
-            Ok(f) => Box::new(f),
+        let file = match OpenOptions::new().read(true).open(path) {
+            Ok(f) => f,
             Err(e) => return Err(Error::from_err(Box::new(e), ErrorKind::FileIOError))
         };
-        let buffer = BufReader::new(file).lines().enumerate();
-        Ok(Self::new(buffer))
+        Ok(Self::from_reader(file, &path.to_string_lossy()))
+    }
+
+    /// Create a new [`FileStream`] that reads each file in `paths` one after
+    /// another.
+    pub fn from_paths<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let path = path.as_ref();
+            match OpenOptions::new().read(true).open(path) {
+                Ok(f) => files.push((f, path.to_string_lossy().to_string())),
+                Err(e) => return Err(Error::from_err(Box::new(e), ErrorKind::FileIOError))
+            }
+        }
+        let mut files = files.into_iter();
+        let mut stream = match files.next() {
+            Some((file, name)) => Self::from_reader(file, &name),
+            None => Self::from_bytes(&[])
+        };
+        for (file, name) in files {
+            stream.push_reader(file, &name);
+        }
+        Ok(stream)
     }
 
     /// Create a new [`FileStream`] from a slice of bytes.
     pub fn from_bytes(byte_array: &'a [u8]) -> Self {
-        let read: Box<dyn Read + 'a> = Box::new(byte_array);
-        let buffer = BufReader::new(read).lines().enumerate();
-        Self::new(buffer)
+        Self::from_reader(byte_array, ANONYMOUS_SOURCE_NAME)
     }
 }
 
@@ -0,0 +1,138 @@
+//! A module for deserialising a list of tokens that was previously
+//! serialised by [`super::LexerSerializer`], so that it can be fed back into
+//! the parser.
+
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use super::{TokenBuffer, TokenIterator};
+use crate::{
+    error::{Error, Result},
+    token::Token
+};
+
+/// Deserialises a list of tokens written by [`super::LexerSerializer`].
+///
+/// Like [`super::LexerSerializer`], this struct is not tied to any format,
+/// so it can be used with any deserialiser (such as `serde_json`) which
+/// supports sequences. The tokens can then be turned into a [`TokenBuffer`]
+/// so that they can be read by the parser.
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_lexer::tokenizer::{
+///     FileStream,
+///     LexerDeserializer,
+///     LexerSerializer,
+///     Tokenizer
+/// };
+///
+/// let iterator = Tokenizer::new().to_iter(FileStream::from("extern def sin(x);"));
+/// let json = serde_json::to_string(&LexerSerializer::new(iterator)).unwrap();
+/// let deserializer: LexerDeserializer = serde_json::from_str(&json).unwrap();
+/// let spans = deserializer
+///     .get_tokens()
+///     .iter()
+///     .map(|token| &token.span[..])
+///     .collect::<Vec<_>>();
+/// assert_eq!(spans, ["extern", "def", "sin", "(", "x", ")", ";", ""]);
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LexerDeserializer {
+    tokens: Vec<Token>
+}
+
+impl LexerDeserializer {
+    /// Create a new deserialiser from a list of tokens.
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens }
+    }
+
+    /// Get the deserialised tokens.
+    pub fn get_tokens(&self) -> &[Token] {
+        &self.tokens[..]
+    }
+
+    /// Take the deserialised tokens out of the deserialiser.
+    pub fn into_tokens(self) -> Vec<Token> {
+        self.tokens
+    }
+
+    /// Convert the deserialised tokens into a [`TokenBuffer`].
+    pub fn into_buffer(self) -> TokenBuffer {
+        TokenBuffer::new(self.tokens)
+    }
+}
+
+impl From<LexerDeserializer> for TokenBuffer {
+    fn from(deserializer: LexerDeserializer) -> Self {
+        deserializer.into_buffer()
+    }
+}
+
+/// A list of tokens stored under the key `tokens`.
+///
+/// Formats like TOML cannot have a list at the top level of a document, so
+/// the list of tokens has to be wrapped in a table. This struct can be
+/// deserialised back into a list of tokens through [`Token`]'s
+/// [`Deserialize`] implementation.
+///
+/// The TOML serialiser requires every plain value in a table to come before
+/// the tables nested inside it, so a [`TokenTable`] should be converted into
+/// a `toml::Value` (which sorts its keys) before it is written out.
+///
+/// # Example
+///
+/// ```
+/// use std::convert::TryFrom;
+///
+/// use kaleidoscope_lexer::tokenizer::{FileStream, TokenTable, Tokenizer};
+///
+/// let iterator = Tokenizer::new().to_iter(FileStream::from("def f(x) x + 1"));
+/// let table = TokenTable::try_from(iterator).unwrap();
+/// let value = toml::Value::try_from(&table).unwrap();
+/// let text = toml::to_string_pretty(&value).unwrap();
+///
+/// let read: TokenTable = toml::from_str(&text).unwrap();
+/// assert_eq!(read.tokens, table.tokens);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenTable {
+    pub tokens: Vec<Token>
+}
+
+impl TokenTable {
+    /// Create a new [`TokenTable`] from a list of tokens.
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens }
+    }
+}
+
+impl<'a> TryFrom<TokenIterator<'a>> for TokenTable {
+    type Error = Error;
+
+    /// Read every token (including the EOF) from `token_iter`, failing on
+    /// the first lexer error.
+    fn try_from(mut token_iter: TokenIterator<'a>) -> Result<Self> {
+        let mut tokens = Vec::new();
+        while !token_iter.eof_reached() {
+            tokens.push(token_iter.next_token()?);
+        }
+        Ok(Self::new(tokens))
+    }
+}
+
+impl From<TokenTable> for LexerDeserializer {
+    fn from(table: TokenTable) -> Self {
+        Self::new(table.tokens)
+    }
+}
+
+impl From<TokenTable> for TokenBuffer {
+    fn from(table: TokenTable) -> Self {
+        Self::new(table.tokens)
+    }
+}
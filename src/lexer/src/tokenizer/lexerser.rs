@@ -3,13 +3,18 @@
 
 use std::cell::RefCell;
 
-use serde::{ser::SerializeSeq, Serialize, Serializer};
+use serde::{
+    ser::{Error, SerializeSeq},
+    Serialize,
+    Serializer
+};
 
 use super::TokenIterator;
 
 /// Serialises a [`TokenIterator`] into a list of tokens.
 ///
-/// Can be used to store tokens into a different format for later use.
+/// Can be used to store tokens into a different format for later use. If the
+/// tokeniser fails, serialisation fails with the tokeniser's error message.
 pub struct LexerSerializer<'a> {
     token_iter: RefCell<TokenIterator<'a>>
 }
@@ -29,7 +34,9 @@ impl<'a> Serialize for LexerSerializer<'a> {
         S: Serializer
     {
         let mut state = serializer.serialize_seq(None)?;
-        while let Some(token) = self.token_iter.borrow_mut().next() {
+        let mut token_iter = self.token_iter.borrow_mut();
+        while !token_iter.eof_reached() {
+            let token = token_iter.next_token().map_err(S::Error::custom)?;
             state.serialize_element(&token)?;
         }
         state.end()
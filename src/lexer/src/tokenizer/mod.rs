@@ -3,13 +3,26 @@
 //! See also [`crate::tokenizer::Tokenizer`]
 
 mod filestream;
+mod lexerde;
 mod lexerser;
 mod lexertuple;
+mod options;
+mod tokcursor;
 mod tokenizer;
 mod tokiter;
+mod toksource;
 
-pub use filestream::FileStream;
+pub use filestream::{FileStream, LineEnding, RawLines, ANONYMOUS_SOURCE_NAME, STDIN_SOURCE_NAME};
+pub use lexerde::{LexerDeserializer, TokenTable};
 pub use lexerser::LexerSerializer;
 pub use lexertuple::{LexerTupleMut, LexerTupleRef};
+pub use options::{
+    IdentifierRules,
+    TokenizerOptions,
+    DEFAULT_COMMENT_CHAR,
+    DEFAULT_OPERATOR_CHARS
+};
+pub use tokcursor::{Mark, TokenCursor};
 pub use tokenizer::Tokenizer;
 pub use tokiter::TokenIterator;
+pub use toksource::{TokenBuffer, TokenSource};
@@ -0,0 +1,220 @@
+//! The options that control the lexical rules followed by a [`Tokenizer`].
+//!
+//! See [`TokenizerOptions`].
+
+use super::{FileStream, Tokenizer};
+use crate::{token::Keyword, utils};
+
+/// The characters that can be used in operators by default. This includes
+/// [`crate::token::CUSTOM_OPERATOR_CHARS`].
+pub const DEFAULT_OPERATOR_CHARS: &[char] = &[
+    '+', '-', '*', '/', '<', '>', '=', '!', '|', '&', '^', '%', '~', ':', '@', '$', '?', '\\'
+];
+
+/// The character that starts a comment by default.
+pub const DEFAULT_COMMENT_CHAR: char = '#';
+
+/// The rules deciding which characters can be used in an identifier.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum IdentifierRules {
+    /// Identifiers start with an ASCII letter or `_`, followed by ASCII
+    /// letters, `_` and ASCII digits.
+    #[default]
+    Ascii,
+    /// Identifiers start with any alphabetic character or `_`, followed by
+    /// alphabetic characters, `_` and numeric characters (as defined by
+    /// Unicode).
+    Unicode
+}
+
+impl IdentifierRules {
+    /// True if `unit` can be the first character of an identifier.
+    pub fn is_identifier_start(&self, unit: char) -> bool {
+        match self {
+            IdentifierRules::Ascii => utils::is_identifier_start(unit),
+            IdentifierRules::Unicode => unit.is_alphabetic() || unit == '_'
+        }
+    }
+
+    /// True if `unit` can be part of an identifier after the first character.
+    pub fn is_identifier(&self, unit: char) -> bool {
+        match self {
+            IdentifierRules::Ascii => utils::is_identifier(unit),
+            IdentifierRules::Unicode => unit.is_alphanumeric() || unit == '_'
+        }
+    }
+}
+
+/// The options that control the lexical rules followed by a [`Tokenizer`].
+///
+/// By default, these options follow the rules described in
+/// [`crate::utils`]. Each option can be changed with the `with_*` methods,
+/// which allows dialects of Kaleidoscope to be tokenized without writing a
+/// new tokenizer.
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_lexer::{
+///     token::{Keyword, TokenKind},
+///     tokenizer::{FileStream, IdentifierRules, TokenizerOptions}
+/// };
+///
+/// let options = TokenizerOptions::new()
+///     .with_comment_char('%')
+///     .with_identifier_rules(IdentifierRules::Unicode)
+///     .with_keyword("fn", Keyword::Def);
+/// let stream = FileStream::from("fn größe(x) x % comment");
+/// let tokens = options.build().to_iter(stream).collect::<Vec<_>>();
+/// assert_eq!(tokens[0].token_kind, TokenKind::Keyword(Keyword::Def));
+/// assert_eq!(tokens[1].span, "größe");
+/// assert_eq!(tokens.len(), 7);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TokenizerOptions {
+    comment_char:     char,
+    operator_chars:   Vec<char>,
+    identifier_rules: IdentifierRules,
+    keywords:         Vec<(String, Keyword)>,
+    tab_width:        Option<usize>
+}
+
+impl TokenizerOptions {
+    /// Create a new set of options with the default lexical rules.
+    pub fn new() -> Self {
+        Self {
+            comment_char:     DEFAULT_COMMENT_CHAR,
+            operator_chars:   DEFAULT_OPERATOR_CHARS.to_vec(),
+            identifier_rules: IdentifierRules::default(),
+            keywords:         Keyword::all()
+                .iter()
+                .map(|keyword| (keyword.to_string().to_string(), *keyword))
+                .collect(),
+            tab_width:        None
+        }
+    }
+
+    /// Set the character that starts a comment. A documentation comment
+    /// starts with 2 of these characters.
+    pub fn with_comment_char(mut self, comment_char: char) -> Self {
+        self.comment_char = comment_char;
+        self
+    }
+
+    /// Set the characters that operators can be made out of.
+    pub fn with_operator_chars(mut self, operator_chars: &[char]) -> Self {
+        self.operator_chars = operator_chars.to_vec();
+        self
+    }
+
+    /// Set the rules for which characters can be used in identifiers.
+    pub fn with_identifier_rules(mut self, identifier_rules: IdentifierRules) -> Self {
+        self.identifier_rules = identifier_rules;
+        self
+    }
+
+    /// Make `spelling` a keyword that is read as `keyword`. If `spelling`
+    /// was already a keyword, it is replaced.
+    pub fn with_keyword(mut self, spelling: &str, keyword: Keyword) -> Self {
+        self.keywords.retain(|(s, _)| s != spelling);
+        self.keywords.push((spelling.to_string(), keyword));
+        self
+    }
+
+    /// Stop `spelling` from being a keyword, so it is read as an identifier
+    /// instead.
+    pub fn without_keyword(mut self, spelling: &str) -> Self {
+        self.keywords.retain(|(s, _)| s != spelling);
+        self
+    }
+
+    /// Replace the whole keyword set.
+    pub fn with_keywords(mut self, keywords: &[(&str, Keyword)]) -> Self {
+        self.keywords = keywords
+            .iter()
+            .map(|(spelling, keyword)| (spelling.to_string(), *keyword))
+            .collect();
+        self
+    }
+
+    /// Set the number of columns a tab advances to in the display columns of
+    /// the tokens. The tab width is applied to every stream read with these
+    /// options, see [`FileStream::set_tab_width`].
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = Some(tab_width);
+        self
+    }
+
+    /// Create a [`Tokenizer`] that follows these options.
+    pub fn build(self) -> Tokenizer {
+        Tokenizer::with_options(self)
+    }
+
+    /// Get the character that starts a comment.
+    pub fn get_comment_char(&self) -> char {
+        self.comment_char
+    }
+
+    /// Get the characters that operators can be made out of.
+    pub fn get_operator_chars(&self) -> &[char] {
+        &self.operator_chars[..]
+    }
+
+    /// Get the rules for which characters can be used in identifiers.
+    pub fn get_identifier_rules(&self) -> IdentifierRules {
+        self.identifier_rules
+    }
+
+    /// Get the keywords, along with how they are spelt.
+    pub fn get_keywords(&self) -> &[(String, Keyword)] {
+        &self.keywords[..]
+    }
+
+    /// Get the tab width applied to the streams read with these options, if
+    /// one was set. Otherwise, streams keep their own tab width.
+    pub fn get_tab_width(&self) -> Option<usize> {
+        self.tab_width
+    }
+
+    /// Set the tab width of `stream` to the one in these options, if one was
+    /// set.
+    pub fn apply_tab_width(&self, stream: &mut FileStream) {
+        if let Some(tab_width) = self.tab_width {
+            stream.set_tab_width(tab_width);
+        }
+    }
+
+    /// See if `unit` starts a comment.
+    pub fn is_comment(&self, unit: char) -> bool {
+        unit == self.comment_char
+    }
+
+    /// See if `unit` can be used in an operator.
+    pub fn is_opchar(&self, unit: char) -> bool {
+        self.operator_chars.contains(&unit)
+    }
+
+    /// True if `unit` can be the first character of an identifier.
+    pub fn is_identifier_start(&self, unit: char) -> bool {
+        self.identifier_rules.is_identifier_start(unit)
+    }
+
+    /// True if `unit` can be part of an identifier after the first character.
+    pub fn is_identifier(&self, unit: char) -> bool {
+        self.identifier_rules.is_identifier(unit)
+    }
+
+    /// Get the keyword spelt as `spelling`, if there is one.
+    pub fn get_keyword(&self, spelling: &str) -> Option<Keyword> {
+        self.keywords
+            .iter()
+            .find(|(s, _)| s == spelling)
+            .map(|(_, keyword)| *keyword)
+    }
+}
+
+impl Default for TokenizerOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
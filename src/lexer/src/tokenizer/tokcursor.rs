@@ -0,0 +1,214 @@
+//! A module defining a [`TokenCursor`], which buffers the tokens produced by
+//! a [`TokenSource`] so that they can be looked ahead of and backtracked
+//! over.
+
+use std::{collections::VecDeque, iter::Iterator};
+
+use super::{TokenIterator, TokenSource};
+use crate::{
+    error::Result,
+    token::{FileIndex, Token}
+};
+
+/// A saved position in a [`TokenCursor`]. Pass it to [`TokenCursor::reset`]
+/// to rewind the cursor back to this position, or to
+/// [`TokenCursor::release`] if backtracking is no longer needed.
+///
+/// A mark can neither be copied nor cloned, so it can only be given back to
+/// the cursor once.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Mark(usize);
+
+/// A cursor over the tokens produced by a [`TokenSource`], such as a
+/// [`TokenIterator`].
+///
+/// Unlike the source, the cursor can look at any number of tokens ahead of
+/// its position without consuming them (see [`TokenCursor::peek`]), and can
+/// save its position with [`TokenCursor::mark`] so that it can return there
+/// later with [`TokenCursor::reset`]. Tokens are only kept in memory for as
+/// long as they are ahead of the cursor or there is a [`Mark`] pointing at or
+/// before them.
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_lexer::tokenizer::{FileStream, TokenCursor, Tokenizer};
+///
+/// let stream = FileStream::from("foo(1, 2);");
+/// let mut cursor = TokenCursor::new(Tokenizer::new().to_iter(stream));
+///
+/// // Look ahead without consuming anything.
+/// assert_eq!(cursor.peek(0).unwrap().unwrap().span, "foo");
+/// assert_eq!(cursor.peek(2).unwrap().unwrap().span, "1");
+///
+/// let mark = cursor.mark();
+/// assert_eq!(cursor.next_token().unwrap().unwrap().span, "foo");
+/// assert_eq!(cursor.next_token().unwrap().unwrap().span, "(");
+///
+/// // Go back to where the mark was made.
+/// cursor.reset(mark);
+/// assert_eq!(cursor.next_token().unwrap().unwrap().span, "foo");
+/// ```
+pub struct TokenCursor<S> {
+    source:      S,
+    buffer:      VecDeque<Token>,
+    offset:      usize,
+    position:    usize,
+    marks:       Vec<usize>,
+    eof_reached: bool
+}
+
+impl<S: TokenSource> TokenCursor<S> {
+    /// Create a new [`TokenCursor`] reading from a [`TokenSource`].
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            buffer: VecDeque::new(),
+            offset: 0,
+            position: 0,
+            marks: Vec::new(),
+            eof_reached: false
+        }
+    }
+
+    /// Get the number of tokens the cursor has moved past since it was
+    /// created.
+    pub fn get_position(&self) -> usize {
+        self.position
+    }
+
+    /// Get the position in the file of the token under the cursor. If that
+    /// token has not been read yet, the position the source is reading from
+    /// is returned instead.
+    pub fn get_index(&self) -> FileIndex {
+        match self.peek_buffered(0) {
+            Some(token) => token.start,
+            None => self.source.get_index()
+        }
+    }
+
+    /// Read tokens from the source until the token `n` places ahead of the
+    /// cursor is in the buffer, or until the source runs out of tokens.
+    fn fill(&mut self, n: usize) -> Result<()> {
+        let wanted = self.position - self.offset + n;
+        while self.buffer.len() <= wanted && !self.eof_reached {
+            let token = self.source.next_token()?;
+            self.eof_reached = token.is_eof();
+            self.buffer.push_back(token);
+        }
+        Ok(())
+    }
+
+    /// Drop the tokens that are behind both the cursor and every [`Mark`].
+    fn discard(&mut self) {
+        let keep_from = self.marks.iter().copied().min().unwrap_or(self.position);
+        let keep_from = keep_from.min(self.position);
+        while self.offset < keep_from && self.buffer.pop_front().is_some() {
+            self.offset += 1;
+        }
+    }
+
+    /// Peek at the token `n` places ahead of the cursor without consuming
+    /// it, so `peek(0)` is the token that would be returned by
+    /// [`TokenCursor::next_token`]. [`None`] is returned if the token stream
+    /// ends (after the EOF token) before that token.
+    pub fn peek(&mut self, n: usize) -> Result<Option<&Token>> {
+        self.fill(n)?;
+        Ok(self.buffer.get(self.position - self.offset + n))
+    }
+
+    /// Peek at the token `n` places ahead of the cursor like
+    /// [`TokenCursor::peek`], but only if it has already been read from the
+    /// source. This never reads from the source, so it cannot block when the
+    /// source is waiting for input (such as in an interactive session).
+    pub fn peek_buffered(&self, n: usize) -> Option<&Token> {
+        self.buffer.get(self.position - self.offset + n)
+    }
+
+    /// Consume the token under the cursor and move the cursor forward by
+    /// one token.
+    pub fn next_token(&mut self) -> Result<Option<Token>> {
+        self.fill(0)?;
+        let token = match self.buffer.get(self.position - self.offset) {
+            Some(token) => token.clone(),
+            None => return Ok(None)
+        };
+        self.position += 1;
+        self.discard();
+        Ok(Some(token))
+    }
+
+    /// Save the position of the cursor. The tokens after this position are
+    /// kept in memory until the [`Mark`] is given back to the cursor through
+    /// [`TokenCursor::reset`] or [`TokenCursor::release`].
+    pub fn mark(&mut self) -> Mark {
+        self.marks.push(self.position);
+        Mark(self.position)
+    }
+
+    /// Move the cursor back (or forward) to the position saved in `mark`,
+    /// releasing the mark in the process.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mark` was made by another cursor.
+    pub fn reset(&mut self, mark: Mark) {
+        assert!(
+            self.marks.contains(&mark.0),
+            "the mark does not belong to this cursor"
+        );
+        self.position = mark.0;
+        self.release(mark);
+    }
+
+    /// Give up a [`Mark`] without moving the cursor, allowing the tokens it
+    /// was holding on to to be dropped.
+    pub fn release(&mut self, mark: Mark) {
+        if let Some(i) = self.marks.iter().position(|&m| m == mark.0) {
+            self.marks.swap_remove(i);
+        }
+        self.discard();
+    }
+
+    /// Check if every token has been consumed.
+    pub fn is_done(&mut self) -> bool {
+        matches!(self.peek(0), Ok(None))
+    }
+
+    /// Check if the EOF token has been read from the source. The EOF token
+    /// may still be ahead of the cursor.
+    pub fn eof_reached(&self) -> bool {
+        self.eof_reached
+    }
+
+    /// Get a reference to the [`TokenSource`] the tokens are read from.
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+
+    /// Get a mutable reference to the [`TokenSource`] the tokens are read
+    /// from. Tokens that have already been read into the cursor are not
+    /// affected by changes to the source.
+    pub fn get_mut_source(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Take the [`TokenSource`] out of the cursor, dropping the tokens that
+    /// have been read but not consumed.
+    pub fn into_source(self) -> S {
+        self.source
+    }
+}
+
+impl<'a> From<TokenIterator<'a>> for TokenCursor<TokenIterator<'a>> {
+    fn from(source: TokenIterator<'a>) -> Self {
+        Self::new(source)
+    }
+}
+
+impl<S: TokenSource> Iterator for TokenCursor<S> {
+    type Item = Token;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_token().ok().flatten()
+    }
+}
@@ -4,19 +4,71 @@
 
 use std::iter::Iterator;
 
-use super::{FileStream, LexerTupleMut, LexerTupleRef, TokenIterator};
-use crate::{error::Result, token::Token, utils};
+use super::{
+    FileStream,
+    LexerTupleMut,
+    LexerTupleRef,
+    TokenCursor,
+    TokenIterator,
+    TokenizerOptions
+};
+use crate::{
+    error::Result,
+    token::{Token, TokenKind},
+    utils
+};
 
 /// The tokeniser which iterates over the characters in a file stream and
 /// yields a stream of tokens.
+///
+/// Operator characters next to each other are read as one operator for as
+/// long as they make up a valid operator.
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_lexer::{
+///     token::{Operator, TokenKind},
+///     tokenizer::{FileStream, Tokenizer}
+/// };
+///
+/// let tokens = Tokenizer::new()
+///     .to_iter(FileStream::from("a<=-!b"))
+///     .map(|token| token.token_kind)
+///     .collect::<Vec<_>>();
+/// assert_eq!(tokens[1], TokenKind::Operator(Operator::LessThanEqual));
+/// assert_eq!(tokens[2], TokenKind::Operator(Operator::Minus));
+/// assert_eq!(tokens[3], TokenKind::Operator(Operator::Not));
+/// ```
 pub struct Tokenizer {
-    pub last_unit: Option<char>
+    pub last_unit: Option<char>,
+    options:       TokenizerOptions
 }
 
 impl Tokenizer {
     /// Create a new [`Tokenizer`].
     pub fn new() -> Self {
-        Self { last_unit: None }
+        Self::with_options(TokenizerOptions::default())
+    }
+
+    /// Create a new [`Tokenizer`] which follows the lexical rules in
+    /// `options`.
+    pub fn with_options(options: TokenizerOptions) -> Self {
+        Self {
+            last_unit: None,
+            options
+        }
+    }
+
+    /// Get the lexical rules followed by this tokenizer.
+    pub fn get_options(&self) -> &TokenizerOptions {
+        &self.options
+    }
+
+    /// Change the lexical rules followed by this tokenizer.
+    pub fn set_options(&mut self, options: TokenizerOptions) -> &mut Self {
+        self.options = options;
+        self
     }
 
     /// See if any more tokens are available.
@@ -30,6 +82,12 @@ impl Tokenizer {
         TokenIterator::new(stream, self)
     }
 
+    /// Convert this tokenizer into a [`TokenCursor`], which supports
+    /// lookahead and backtracking.
+    pub fn to_cursor(self, stream: FileStream) -> TokenCursor<TokenIterator> {
+        TokenCursor::new(self.to_iter(stream))
+    }
+
     /// Package this tokeniser with a filestream.
     pub fn to_tuple_ref<'a, 'b: 'a>(&'a self, stream: &'a FileStream<'b>) -> LexerTupleRef<'a, 'b> {
         LexerTupleRef(stream, self)
@@ -61,6 +119,7 @@ impl Tokenizer {
         }
         let mut token = Token::default();
         let mut is_comment = false;
+        let mut comment = Token::default();
         'stream: loop {
             let index = stream.get_index();
             // None case already handled above.
@@ -68,11 +127,23 @@ impl Tokenizer {
             if is_comment {
                 if utils::is_eol(unit) {
                     is_comment = false;
+                    if comment.resolve_doc_comment(index, self.options.get_comment_char()) {
+                        token = comment;
+                        break 'stream;
+                    }
+                } else {
+                    comment.span.push(unit);
+                }
+            } else if self.options.is_comment(unit) {
+                if !token.is_empty() {
+                    // Finish the current token before reading the comment.
+                    token.resolve_with(index, &self.options)?;
+                    break 'stream;
                 }
-            } else if utils::is_comment(unit) {
                 is_comment = true;
+                comment = Token::new(TokenKind::Unknown, unit.to_string(), index, index);
             } else {
-                match token.add_unit(unit, index) {
+                match token.add_unit_with(unit, index, &self.options) {
                     Ok(true) => {
                         token.end = index;
                         break 'stream;
@@ -85,10 +156,14 @@ impl Tokenizer {
                 Some(u) => Some(u),
                 None => match stream.get_err() {
                     None => {
-                        if token.is_empty() && stream.eof_reached() {
+                        if is_comment
+                            && comment.resolve_doc_comment(index, self.options.get_comment_char())
+                        {
+                            token = comment;
+                        } else if token.is_empty() && stream.eof_reached() {
                             token = Token::new_eof(index);
                         } else {
-                            token.resolve(index)?;
+                            token.resolve_with(index, &self.options)?;
                         }
                         break 'stream;
                     },
@@ -15,8 +15,10 @@ pub struct TokenIterator<'a> {
 }
 
 impl<'a> TokenIterator<'a> {
-    /// Create a new [`TokenIterator`].
-    pub fn new(stream: FileStream<'a>, tokenizer: Tokenizer) -> Self {
+    /// Create a new [`TokenIterator`]. The tab width in the options of
+    /// `tokenizer` is applied to `stream`, if there is one.
+    pub fn new(mut stream: FileStream<'a>, tokenizer: Tokenizer) -> Self {
+        tokenizer.get_options().apply_tab_width(&mut stream);
         Self {
             stream,
             tokenizer,
@@ -24,6 +26,27 @@ impl<'a> TokenIterator<'a> {
         }
     }
 
+    /// Get a reference to the [`FileStream`] the tokens are read from.
+    pub fn get_stream(&self) -> &FileStream<'a> {
+        &self.stream
+    }
+
+    /// Get a mutable reference to the [`FileStream`] the tokens are read
+    /// from.
+    pub fn get_mut_stream(&mut self) -> &mut FileStream<'a> {
+        &mut self.stream
+    }
+
+    /// Take the [`FileStream`] the tokens are read from out of the iterator.
+    pub fn into_stream(self) -> FileStream<'a> {
+        self.stream
+    }
+
+    /// Get a mutable reference to the [`Tokenizer`] used by this iterator.
+    pub fn get_mut_tokenizer(&mut self) -> &mut Tokenizer {
+        &mut self.tokenizer
+    }
+
     /// Check if this iterator can produce more tokens.
     pub fn is_done(&self) -> bool {
         self.tokenizer.is_done(&self.stream)
@@ -0,0 +1,140 @@
+//! A module defining [`TokenSource`], the trait implemented by everything
+//! that can feed tokens to a parser, as well as [`TokenBuffer`], a
+//! [`TokenSource`] that reads tokens from memory.
+
+use std::collections::VecDeque;
+
+use super::{LexerTupleMut, TokenCursor, TokenIterator};
+use crate::{
+    error::Result,
+    token::{FileIndex, Token}
+};
+
+/// A source of tokens.
+///
+/// This can either be a live tokenizer reading from a [`super::FileStream`]
+/// (such as [`LexerTupleMut`], [`TokenIterator`] or [`TokenCursor`]) or a
+/// list of tokens that have already been produced (see [`TokenBuffer`]).
+pub trait TokenSource {
+    /// Get the next token from the source. Once the source runs out of
+    /// tokens, an EOF token is returned (see [`Token::is_eof`]).
+    fn next_token(&mut self) -> Result<Token>;
+
+    /// Get the position in the file the source is currently reading from.
+    fn get_index(&self) -> FileIndex;
+}
+
+impl<'a, 'b: 'a> TokenSource for LexerTupleMut<'a, 'b> {
+    fn next_token(&mut self) -> Result<Token> {
+        self.1.next_token(self.0)
+    }
+
+    fn get_index(&self) -> FileIndex {
+        self.0.get_index()
+    }
+}
+
+impl<'a> TokenSource for TokenIterator<'a> {
+    fn next_token(&mut self) -> Result<Token> {
+        TokenIterator::next_token(self)
+    }
+
+    fn get_index(&self) -> FileIndex {
+        self.get_stream().get_index()
+    }
+}
+
+impl<S: TokenSource> TokenSource for TokenCursor<S> {
+    fn next_token(&mut self) -> Result<Token> {
+        let index = TokenCursor::get_index(self);
+        Ok(TokenCursor::next_token(self)?.unwrap_or_else(|| Token::new_eof(index)))
+    }
+
+    fn get_index(&self) -> FileIndex {
+        TokenCursor::get_index(self)
+    }
+}
+
+/// A [`TokenSource`] that yields tokens stored in memory, such as tokens
+/// crafted by hand or read back from a file with
+/// [`super::LexerDeserializer`].
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_lexer::{
+///     token::{FileIndex, Token, TokenKind},
+///     tokenizer::{TokenBuffer, TokenSource}
+/// };
+///
+/// let start = FileIndex::new(Some(0), 0);
+/// let end = FileIndex::new(Some(0), 1);
+/// let mut buffer = TokenBuffer::from(vec![Token::new(
+///     TokenKind::Integer,
+///     "1".to_string(),
+///     start,
+///     end
+/// )]);
+/// assert_eq!(buffer.next_token().unwrap().span, "1");
+/// assert!(buffer.next_token().unwrap().is_eof());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TokenBuffer {
+    tokens: VecDeque<Token>,
+    index:  FileIndex
+}
+
+impl TokenBuffer {
+    /// Create a new [`TokenBuffer`] from a list of tokens.
+    pub fn new(tokens: Vec<Token>) -> Self {
+        let index = match tokens.first() {
+            Some(token) => token.start,
+            None => Default::default()
+        };
+        Self {
+            tokens: tokens.into(),
+            index
+        }
+    }
+
+    /// Get the number of tokens left in the buffer.
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Check if there are no tokens left in the buffer.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+impl From<Vec<Token>> for TokenBuffer {
+    fn from(tokens: Vec<Token>) -> Self {
+        Self::new(tokens)
+    }
+}
+
+impl TokenSource for TokenBuffer {
+    fn next_token(&mut self) -> Result<Token> {
+        Ok(match self.tokens.pop_front() {
+            Some(token) => {
+                self.index = token.end;
+                token
+            },
+            None => Token::new_eof(self.index)
+        })
+    }
+
+    fn get_index(&self) -> FileIndex {
+        self.index
+    }
+}
+
+impl Iterator for TokenBuffer {
+    type Item = Token;
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.pop_front()?;
+        self.index = token.end;
+        Some(token)
+    }
+}
@@ -48,6 +48,8 @@
 //! You can tests for brackets, operators, numbers and punctuation, which play
 //! an important role in the grammar of the language.
 
+use crate::tokenizer::{DEFAULT_COMMENT_CHAR, DEFAULT_OPERATOR_CHARS};
+
 /// Rudimentary check to see if the end of the line has been reached.
 pub fn is_eol(unit: char) -> bool {
     matches!(unit, '\n')
@@ -91,18 +93,16 @@ pub fn is_fullstop(unit: char) -> bool {
 
 /// True if `unit` is a character used in one of the operator symbols.
 ///
-/// For now this is restricted to the following characters:
-/// 1. +
-/// 2. -
-/// 3. *
-/// 4. /
+/// These are the characters in [`DEFAULT_OPERATOR_CHARS`], which includes
+/// the characters in [`crate::token::CUSTOM_OPERATOR_CHARS`] (which are used
+/// in user-defined operators).
 pub fn is_opchar(unit: char) -> bool {
-    matches!(unit, '+' | '-' | '*' | '/' | '<' | '>' | '=')
+    DEFAULT_OPERATOR_CHARS.contains(&unit)
 }
 
 /// See if `unit` is the character that denotes the start of a comment.
 pub fn is_comment(unit: char) -> bool {
-    unit == '#'
+    unit == DEFAULT_COMMENT_CHAR
 }
 
 /// See if `unit` is a bracket character.
@@ -0,0 +1,114 @@
+//! The errors collected while parsing a whole programme.
+//!
+//! Unlike [`Driver::parse_one`](crate::driver::Driver::parse_one), which
+//! stops at the first error, [`Driver::parse_program`] (and so
+//! [`crate::parse_program`]) carries on parsing after a statement fails, so
+//! that every error in the programme can be reported at once.
+//!
+//! [`Driver::parse_program`]: crate::driver::Driver::parse_program
+
+use std::{fmt, slice::Iter};
+
+use kaleidoscope_lexer::token::FileIndex;
+
+use crate::error::Error;
+
+/// An error in a programme, along with the position of the top-level
+/// statement it was found in.
+#[derive(Debug)]
+pub struct Diagnostic {
+    start: FileIndex,
+    end:   FileIndex,
+    error: Error
+}
+
+impl Diagnostic {
+    /// Create a new [`Diagnostic`] for an error in the statement between
+    /// `start` and `end`.
+    pub fn new(start: FileIndex, end: FileIndex, error: Error) -> Self {
+        Self { start, end, error }
+    }
+
+    /// Get the position of the start of the statement the error was found
+    /// in.
+    pub fn get_start(&self) -> FileIndex {
+        self.start
+    }
+
+    /// Get the position of the end of the statement the error was found in.
+    pub fn get_end(&self) -> FileIndex {
+        self.end
+    }
+
+    /// Get the error.
+    pub fn get_error(&self) -> &Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.start, self.error)
+    }
+}
+
+/// All the errors found in a programme, in the order they appear.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>
+}
+
+impl Diagnostics {
+    /// Create an empty set of diagnostics.
+    pub fn new() -> Self {
+        Self {
+            diagnostics: Vec::new()
+        }
+    }
+
+    /// Add a diagnostic to the end of the list.
+    pub fn push(&mut self, diagnostic: Diagnostic) -> &mut Self {
+        self.diagnostics.push(diagnostic);
+        self
+    }
+
+    /// Get the diagnostics.
+    pub fn get_diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics[..]
+    }
+
+    /// Iterate over the diagnostics.
+    pub fn iter(&self) -> Iter<'_, Diagnostic> {
+        self.diagnostics.iter()
+    }
+
+    /// Get the number of diagnostics.
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    /// Check if there are no diagnostics.
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in self.diagnostics.iter() {
+            writeln!(f, "{}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type IntoIter = Iter<'a, Diagnostic>;
+    type Item = &'a Diagnostic;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -4,23 +4,23 @@
 use std::{
     fmt::Display,
     io::{stdout, Write},
-    iter::Iterator,
-    thread::sleep,
-    time::Duration
+    iter::Iterator
 };
 
 use kaleidoscope_ast::{
     node::{ExprNode, NodeEnum},
-    nodes::{ExternFunctionNode, FunctionNode}
+    nodes::{ExternFunctionNode, FunctionNode, GlobalNode, ProgramItem, ProgramNode}
 };
 use kaleidoscope_lexer::{
-    ltuplemut,
-    tokenizer::{FileStream, Tokenizer}
+    token::{FileIndex, TokenKind, LEFT_CURLY_BRACKET, RIGHT_CURLY_BRACKET},
+    tokenizer::{FileStream, TokenCursor, TokenIterator, TokenSource, Tokenizer, TokenizerOptions}
 };
 
 use crate::{
+    diagnostics::{Diagnostic, Diagnostics},
     error::{Error, ErrorKind, Result},
-    parser::{ParseResult, Parser}
+    parser::{ParseResult, Parser},
+    precedence::PrecedenceTable
 };
 
 
@@ -43,6 +43,44 @@ where
 }
 
 
+/// Move the cursor past a statement that could not be parsed, up to and
+/// including the next `;` that is not in a block. The positions of the
+/// start and the end of the statement are returned, or [`None`] if the
+/// lexer failed before the end of the statement.
+fn skip_statement<S: TokenSource>(cursor: &mut TokenCursor<S>) -> Option<(FileIndex, FileIndex)> {
+    // Skip the ';' that ended the statement before, like
+    // `Driver::parse_one` does.
+    if let Some(token) = cursor.peek_buffered(0) {
+        if token.is_terminating() && !token.is_eof() {
+            cursor.next_token().ok()?;
+        }
+    }
+    let mut start = None;
+    let mut end = cursor.get_index();
+    let mut depth = 0usize;
+    loop {
+        let token = match cursor.peek(0).ok()? {
+            Some(token) if !token.is_eof() => token.clone(),
+            _ => break
+        };
+        cursor.next_token().ok()?;
+        start.get_or_insert(token.start);
+        end = token.end;
+        match token.token_kind {
+            TokenKind::Bracket(LEFT_CURLY_BRACKET) => depth += 1,
+            TokenKind::Bracket(RIGHT_CURLY_BRACKET) => depth = depth.saturating_sub(1),
+            TokenKind::Semicolon if depth == 0 => break,
+            _ => {}
+        }
+    }
+    Some((start.unwrap_or(end), end))
+}
+
+
+/// All the top-level nodes in a programme, in the order they appear.
+pub type Program = ProgramNode;
+
+
 /// The driver that brings input from a file stream to the parser.
 #[derive(Clone, Debug)]
 pub struct Driver {
@@ -63,6 +101,14 @@ impl Driver {
         }
     }
 
+    /// Create a driver for parsing a whole programme at once, such as in a
+    /// compiler or a test. The driver does not show a prompt and does not
+    /// print anything to stdout.
+    #[inline]
+    pub fn batch() -> Self {
+        Self::new(false, String::new(), 0)
+    }
+
     /// Check whether the driver is an interactive REPL. This means that the
     /// driver prompts the user for input after each expression.
     #[inline]
@@ -84,57 +130,70 @@ impl Driver {
     }
 
     /// The handler function which deals with function definitions.
-    pub fn handle_function_definition(
+    pub fn handle_function_definition<S: TokenSource>(
         &self,
-        istream: &mut FileStream,
-        tokenizer: &mut Tokenizer,
+        cursor: &mut TokenCursor<S>,
         parser: &mut Parser
     ) -> ParseResult<FunctionNode> {
-        let result = parser.parse_function(ltuplemut!(istream, tokenizer));
+        let result = parser.parse_function(cursor);
         log::debug!("{:?}", parser_output_to_str(&result));
         result
     }
 
     /// The handler function which deals with external function definitions.
-    pub fn handle_extern_function(
+    pub fn handle_extern_function<S: TokenSource>(
         &self,
-        istream: &mut FileStream,
-        tokenizer: &mut Tokenizer,
+        cursor: &mut TokenCursor<S>,
         parser: &mut Parser
     ) -> ParseResult<ExternFunctionNode> {
-        let result = parser.parse_extern_function(ltuplemut!(istream, tokenizer));
+        let result = parser.parse_extern_function(cursor);
+        log::debug!("{:?}", parser_output_to_str(&result));
+        result
+    }
+
+    /// The handler function which deals with global constants and
+    /// variables.
+    pub fn handle_global_declaration<S: TokenSource>(
+        &self,
+        cursor: &mut TokenCursor<S>,
+        parser: &mut Parser
+    ) -> ParseResult<GlobalNode> {
+        let result = parser.parse_global_declaration(cursor);
         log::debug!("{:?}", parser_output_to_str(&result));
         result
     }
 
     /// The handler function which deals with expressions.
-    pub fn handle_expression(
+    pub fn handle_expression<S: TokenSource>(
         &self,
-        istream: &mut FileStream,
-        tokenizer: &mut Tokenizer,
+        cursor: &mut TokenCursor<S>,
         parser: &mut Parser
     ) -> ParseResult<dyn ExprNode> {
-        let result = parser.parse_top_level_expression(ltuplemut!(istream, tokenizer));
+        let result = parser.parse_top_level_expression(cursor);
         log::debug!("{:?}", parser_output_to_str(&result));
         result
     }
 
     /// Parse one statement.
-    pub fn parse_one(
+    pub fn parse_one<S: TokenSource>(
         &self,
-        istream: &mut FileStream,
-        tokenizer: &mut Tokenizer,
+        cursor: &mut TokenCursor<S>,
         parser: &mut Parser
     ) -> Result<Option<NodeEnum>> {
-        if istream.eof_reached() {
+        // Skip the ';' that ended the statement before. Only the tokens that
+        // have already been read are looked at, so that the prompt is shown
+        // before waiting for more input.
+        if let Some(token) = cursor.peek_buffered(0) {
+            if token.is_terminating() && !token.is_eof() {
+                parser.next_token(cursor)?;
+            }
+        }
+        if cursor.eof_reached()
+            && !matches!(cursor.peek_buffered(0), Some(token) if !token.is_eof())
+        {
             log::debug!("eof reached");
             return Ok(None);
         }
-        if let Some(token) = parser.peek_current_token() {
-            if token.is_terminating() {
-                parser.mark_used();
-            }
-        }
         if self.is_interactive() {
             print!("{}", self.prompt);
             stdout()
@@ -142,8 +201,6 @@ impl Driver {
                 .map_err(|e| Error::from_err(Box::new(e), ErrorKind::Other))?;
         }
 
-        sleep(Duration::from_millis(1000));
-
         macro_rules! do_node {
             ($action: expr) => {{
                 if self.verbosity() >= 1 {
@@ -152,13 +209,16 @@ impl Driver {
             }};
         }
 
-        if let Some(node) = self.handle_extern_function(istream, tokenizer, parser)? {
+        if let Some(node) = self.handle_extern_function(cursor, parser)? {
             do_node!(println!("External Function:\n{}", node));
             Ok(Some(NodeEnum::AnyNode(node)))
-        } else if let Some(node) = self.handle_function_definition(istream, tokenizer, parser)? {
+        } else if let Some(node) = self.handle_function_definition(cursor, parser)? {
             do_node!(println!("Function Definition:\n{}", node.get_prototype()));
             Ok(Some(NodeEnum::AnyNode(node)))
-        } else if let Some(node) = self.handle_expression(istream, tokenizer, parser)? {
+        } else if let Some(node) = self.handle_global_declaration(cursor, parser)? {
+            do_node!(println!("Global:\n{}", node));
+            Ok(Some(NodeEnum::AnyNode(node)))
+        } else if let Some(node) = self.handle_expression(cursor, parser)? {
             do_node!(println!("Expression:\n{}", node));
             Ok(Some(NodeEnum::ExprNode(node)))
         } else {
@@ -167,16 +227,71 @@ impl Driver {
         }
     }
 
+    /// Parse every statement in a programme until an EOF is reached,
+    /// returning all of the nodes. Parsing carries on past a statement that
+    /// could not be parsed, so that every error in the programme is returned
+    /// together.
+    pub fn parse_program<S: TokenSource>(
+        &self,
+        cursor: &mut TokenCursor<S>,
+        parser: &mut Parser
+    ) -> std::result::Result<Program, Diagnostics> {
+        let mut program = Program::default();
+        let mut diagnostics = Diagnostics::new();
+        loop {
+            let mark = cursor.mark();
+            let error = match self.parse_one(cursor, parser) {
+                Ok(Some(node)) => {
+                    cursor.release(mark);
+                    match ProgramItem::from_node_enum(node) {
+                        Ok(item) => program.push(item),
+                        Err(_) => unreachable!("the driver only parses top-level items")
+                    };
+                    continue;
+                },
+                Ok(None) => match parser.peek_token(cursor) {
+                    Ok(Some(token)) if !token.is_eof() => Error::new(
+                        format!("Unexpected token '{}' at {}", token, token.start),
+                        ErrorKind::SyntaxError,
+                        None
+                    ),
+                    Ok(_) => {
+                        cursor.release(mark);
+                        break;
+                    },
+                    Err(error) => error
+                },
+                Err(error) => error
+            };
+            cursor.reset(mark);
+            let skipped = skip_statement(cursor);
+            let (start, end) = skipped.unwrap_or_else(|| {
+                let index = cursor.get_index();
+                (index, index)
+            });
+            diagnostics.push(Diagnostic::new(start, end, error));
+            if skipped.is_none() {
+                // The lexer cannot get past the error, so nothing after it can
+                // be parsed.
+                break;
+            }
+        }
+        if diagnostics.is_empty() {
+            Ok(program)
+        } else {
+            Err(diagnostics)
+        }
+    }
+
     /// Parse all the statements in a program until an EOF or an error occurs.
-    pub fn main_loop(
+    pub fn main_loop<S: TokenSource>(
         &self,
-        istream: &mut FileStream,
-        tokenizer: &mut Tokenizer,
+        cursor: &mut TokenCursor<S>,
         parser: &mut Parser
     ) -> Result<usize> {
         let mut statements_parsed: usize = 0;
         'main: loop {
-            let result = self.parse_one(istream, tokenizer, parser);
+            let result = self.parse_one(cursor, parser);
             if let Err(error) = result {
                 log::error!("{}", error);
                 return Err(error);
@@ -202,8 +317,7 @@ impl Default for Driver {
 /// A full interpreter that can parse a programme by itself.
 pub struct Interpreter<'a> {
     driver:                Driver,
-    istream:               FileStream<'a>,
-    tokenizer:             Tokenizer,
+    cursor:                TokenCursor<TokenIterator<'a>>,
     parser:                Parser,
     proceed_even_if_error: bool,
     can_proceed:           bool,
@@ -215,24 +329,42 @@ impl<'a> Interpreter<'a> {
     /// Create a new interpreter.
     pub fn new(interactive: bool, istream: FileStream<'a>, verbosity: u32) -> Self {
         Self {
-            driver: Driver::new(interactive, DEFAULT_PROMPT.to_string(), verbosity),
-            istream,
-            tokenizer: Tokenizer::new(),
-            parser: Parser::new(),
+            driver:                Driver::new(interactive, DEFAULT_PROMPT.to_string(), verbosity),
+            cursor:                Tokenizer::new().to_cursor(istream),
+            parser:                Parser::new(),
             proceed_even_if_error: false,
-            can_proceed: true,
-            last_error: None
+            can_proceed:           true,
+            last_error:            None
+        }
+    }
+
+    /// Create a new interpreter that parses a whole programme at once. See
+    /// [`Driver::batch`].
+    pub fn batch(istream: FileStream<'a>) -> Self {
+        Self {
+            driver: Driver::batch(),
+            ..Self::new(false, istream, 0)
         }
     }
 
+    /// Parse the rest of the programme, returning all of its nodes or every
+    /// error found in it. See [`Driver::parse_program`].
+    pub fn parse_program(&mut self) -> std::result::Result<Program, Diagnostics> {
+        let result = self
+            .driver
+            .parse_program(&mut self.cursor, &mut self.parser);
+        self.can_proceed = false;
+        result
+    }
+
     /// Check if there are anymore tokens in the programme.
     pub fn is_done(&self) -> bool {
-        self.istream.eof_reached()
+        self.cursor.get_source().get_stream().eof_reached()
     }
 
     /// Get the [`FileStream`] used by the interpreter.
     pub fn relinquish_istream(self) -> FileStream<'a> {
-        self.istream
+        self.cursor.into_source().into_stream()
     }
 
     /// Get a reference to the last error encountered during the parsing of the
@@ -247,6 +379,23 @@ impl<'a> Interpreter<'a> {
         self.last_error.take()
     }
 
+    /// Set the lexical rules followed by the tokenizer used by this
+    /// interpreter. The tab width in `options` is applied to the stream read
+    /// by this interpreter, if there is one.
+    pub fn set_tokenizer_options(&mut self, options: TokenizerOptions) -> &mut Self {
+        let source = self.cursor.get_mut_source();
+        options.apply_tab_width(source.get_mut_stream());
+        source.get_mut_tokenizer().set_options(options);
+        self
+    }
+
+    /// Set the operators known to the parser used by this interpreter, along
+    /// with their precedence and associativity.
+    pub fn set_precedence_table(&mut self, precedence: PrecedenceTable) -> &mut Self {
+        *self.parser.get_mut_precedence_table() = precedence;
+        self
+    }
+
     /// Set the policy of this interpreter to continue parsing the programme
     /// even if an error was encountered.
     pub fn proceed_even_if_error(&mut self) -> &mut Self {
@@ -267,10 +416,7 @@ impl<'a> Interpreter<'a> {
         &mut self,
         proceed_even_if_error: bool
     ) -> std::result::Result<NodeEnum, bool> {
-        match self
-            .driver
-            .parse_one(&mut self.istream, &mut self.tokenizer, &mut self.parser)
-        {
+        match self.driver.parse_one(&mut self.cursor, &mut self.parser) {
             Ok(node) => {
                 self.can_proceed = node.is_some();
                 log::trace!(
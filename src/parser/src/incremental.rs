@@ -0,0 +1,382 @@
+//! Incremental relexing and reparsing of a Kaleidoscope programme.
+//!
+//! An [`IncrementalParser`] splits a programme into top-level items (an
+//! extern function, a function definition or an expression), each ending
+//! with a semicolon. When the programme is edited with a [`TextEdit`], only
+//! the items touched by the edit are lexed and parsed again. Every other
+//! item (along with its tokens and node) is kept as is, which makes it cheap
+//! to keep an AST up to date while a file is being edited.
+
+use std::ops::Range;
+
+use kaleidoscope_ast::node::NodeEnum;
+use kaleidoscope_lexer::{
+    token::Token,
+    tokenizer::{FileStream, TokenBuffer, TokenCursor, Tokenizer, TokenizerOptions}
+};
+
+use crate::{
+    error::{Error, ErrorKind, Result},
+    parser::Parser,
+    precedence::PrecedenceTable
+};
+
+/// A change to the source code of a programme. The text in `range` (in
+/// bytes) is replaced with `replacement`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextEdit {
+    pub range:       Range<usize>,
+    pub replacement: String
+}
+
+impl TextEdit {
+    /// Create a new [`TextEdit`].
+    pub fn new(range: Range<usize>, replacement: &str) -> Self {
+        Self {
+            range,
+            replacement: replacement.to_string()
+        }
+    }
+
+    /// Create a [`TextEdit`] that inserts `text` at `offset`.
+    pub fn insert(offset: usize, text: &str) -> Self {
+        Self::new(offset..offset, text)
+    }
+
+    /// Create a [`TextEdit`] that deletes the text in `range`.
+    pub fn delete(range: Range<usize>) -> Self {
+        Self::new(range, "")
+    }
+}
+
+/// A top-level item in a programme, which is either an extern function, a
+/// function definition or an expression (or nothing, if the item only
+/// contains whitespace and comments).
+#[derive(Debug)]
+pub struct TopLevelItem {
+    range:      Range<usize>,
+    terminated: bool,
+    tokens:     Vec<Token>,
+    node:       Result<Option<NodeEnum>>,
+    precedence: PrecedenceTable
+}
+
+impl TopLevelItem {
+    /// Lex and parse the text of an item, with the operators in
+    /// `precedence` (which were defined before this item).
+    fn new(
+        source: &str,
+        range: Range<usize>,
+        terminated: bool,
+        options: &TokenizerOptions,
+        precedence: &PrecedenceTable
+    ) -> Self {
+        let mut tokens = Vec::new();
+        let mut parser = Parser::with_precedence_table(precedence.clone());
+        let node = match lex_item(&source[range.clone()], options, &mut tokens) {
+            Ok(()) => parse_item(&tokens, &mut parser),
+            Err(error) => Err(error)
+        };
+        Self {
+            range,
+            terminated,
+            tokens,
+            node,
+            precedence: parser.get_precedence_table().clone()
+        }
+    }
+
+    /// Get the range (in bytes) of the source code occupied by this item.
+    pub fn get_range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Check if this item ends with a semicolon.
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Get the tokens in this item. The indices of the tokens are relative
+    /// to the beginning of the item, so that they stay valid when the text
+    /// before the item is edited.
+    pub fn get_tokens(&self) -> &[Token] {
+        &self.tokens[..]
+    }
+
+    /// Get the node parsed from this item, or the error encountered while
+    /// lexing or parsing it.
+    pub fn get_node(&self) -> std::result::Result<Option<&NodeEnum>, &Error> {
+        match &self.node {
+            Ok(node) => Ok(node.as_ref()),
+            Err(error) => Err(error)
+        }
+    }
+
+    /// Move the item by `delta` bytes.
+    fn shift(&mut self, delta: isize) {
+        let start = (self.range.start as isize + delta) as usize;
+        let end = (self.range.end as isize + delta) as usize;
+        self.range = start..end;
+    }
+}
+
+/// Lex the text of an item, writing the tokens (excluding the EOF) to
+/// `tokens`.
+fn lex_item(text: &str, options: &TokenizerOptions, tokens: &mut Vec<Token>) -> Result<()> {
+    let mut stream = FileStream::from(text);
+    options.apply_tab_width(&mut stream);
+    let mut tokenizer = Tokenizer::with_options(options.clone());
+    loop {
+        let token = tokenizer
+            .next_token(&mut stream)
+            .map_err(|e| Error::from_err(Box::new(e), ErrorKind::LexerError))?;
+        if token.is_eof() {
+            return Ok(());
+        }
+        tokens.push(token);
+    }
+}
+
+/// Parse the tokens of an item.
+fn parse_item(tokens: &[Token], parser: &mut Parser) -> Result<Option<NodeEnum>> {
+    let mut cursor = TokenCursor::new(TokenBuffer::from(tokens.to_vec()));
+    if let Some(node) = parser.parse_extern_function(&mut cursor)? {
+        Ok(Some(NodeEnum::AnyNode(node)))
+    } else if let Some(node) = parser.parse_function(&mut cursor)? {
+        Ok(Some(NodeEnum::AnyNode(node)))
+    } else if let Some(node) = parser.parse_global_declaration(&mut cursor)? {
+        Ok(Some(NodeEnum::AnyNode(node)))
+    } else if let Some(node) = parser.parse_top_level_expression(&mut cursor)? {
+        Ok(Some(NodeEnum::ExprNode(node)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Split `text` into items, each ending after a semicolon that is not in a
+/// comment or a block (`{ ... }`). The last item may not end with a
+/// semicolon, in which case it is marked as unterminated. Nothing is
+/// returned for an empty string.
+fn split_items(text: &str, comment_char: char) -> Vec<(Range<usize>, bool)> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut in_comment = false;
+    let mut block_depth = 0usize;
+    for (offset, unit) in text.char_indices() {
+        if in_comment {
+            in_comment = unit != '\n';
+        } else if unit == comment_char {
+            in_comment = true;
+        } else if unit == '{' {
+            block_depth += 1;
+        } else if unit == '}' {
+            block_depth = block_depth.saturating_sub(1);
+        } else if unit == ';' && block_depth == 0 {
+            items.push((start..offset + 1, true));
+            start = offset + 1;
+        }
+    }
+    if start < text.len() {
+        items.push((start..text.len(), false));
+    }
+    items
+}
+
+/// A parser that keeps the tokens and nodes of a programme, and only lexes
+/// and parses the parts of the programme that change when it is edited.
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_parser::incremental::{IncrementalParser, TextEdit};
+///
+/// let mut parser = IncrementalParser::new("def f(x) x + 1;\ndef g(x) x * 2;");
+/// assert_eq!(parser.get_items().len(), 2);
+///
+/// // Change `x * 2` to `x * 3`, which only touches the second item.
+/// let reparsed = parser.apply_edit(&TextEdit::new(29..30, "3")).unwrap();
+/// assert_eq!(reparsed, 1..2);
+/// assert_eq!(parser.get_source(), "def f(x) x + 1;\ndef g(x) x * 3;");
+/// ```
+#[derive(Debug)]
+pub struct IncrementalParser {
+    source:     String,
+    options:    TokenizerOptions,
+    precedence: PrecedenceTable,
+    items:      Vec<TopLevelItem>
+}
+
+impl IncrementalParser {
+    /// Lex and parse `source`.
+    pub fn new(source: &str) -> Self {
+        Self::with_options(source, TokenizerOptions::default())
+    }
+
+    /// Lex `source` following the lexical rules in `options`, then parse it.
+    pub fn with_options(source: &str, options: TokenizerOptions) -> Self {
+        Self::with_precedence_table(source, options, PrecedenceTable::new())
+    }
+
+    /// Lex `source` following the lexical rules in `options`, then parse it
+    /// starting with the operators in `precedence`.
+    pub fn with_precedence_table(
+        source: &str,
+        options: TokenizerOptions,
+        precedence: PrecedenceTable
+    ) -> Self {
+        let items = Self::build_items(source, 0..source.len(), &options, &precedence);
+        Self {
+            source: source.to_string(),
+            options,
+            precedence,
+            items
+        }
+    }
+
+    /// Lex and parse the items in `range`, starting with the operators in
+    /// `precedence`. Operators defined in one item can be used in the items
+    /// after it.
+    fn build_items(
+        source: &str,
+        range: Range<usize>,
+        options: &TokenizerOptions,
+        precedence: &PrecedenceTable
+    ) -> Vec<TopLevelItem> {
+        let offset = range.start;
+        let mut items: Vec<TopLevelItem> = Vec::new();
+        for (item, terminated) in split_items(&source[range], options.get_comment_char()) {
+            let item = (item.start + offset)..(item.end + offset);
+            let precedence = match items.last() {
+                Some(previous) => &previous.precedence,
+                None => precedence
+            };
+            let item = TopLevelItem::new(source, item, terminated, options, precedence);
+            items.push(item);
+        }
+        items
+    }
+
+    /// Get the operators defined before the item at `index`.
+    fn precedence_before(&self, index: usize) -> PrecedenceTable {
+        match index.checked_sub(1).and_then(|i| self.items.get(i)) {
+            Some(item) => item.precedence.clone(),
+            None => self.precedence.clone()
+        }
+    }
+
+    /// Get the current source code of the programme.
+    pub fn get_source(&self) -> &str {
+        &self.source[..]
+    }
+
+    /// Get the top-level items in the programme.
+    pub fn get_items(&self) -> &[TopLevelItem] {
+        &self.items[..]
+    }
+
+    /// Get the nodes in the programme, skipping items without a node and
+    /// items that could not be parsed.
+    pub fn get_nodes(&self) -> impl Iterator<Item = &NodeEnum> {
+        self.items
+            .iter()
+            .filter_map(|item| item.get_node().ok().flatten())
+    }
+
+    /// Get the errors encountered when lexing or parsing the programme.
+    pub fn get_errors(&self) -> impl Iterator<Item = &Error> {
+        self.items.iter().filter_map(|item| item.get_node().err())
+    }
+
+    /// Apply `edit` to the source code, then lex and parse the items touched
+    /// by the edit again. The indices of the new items in
+    /// [`IncrementalParser::get_items`] are returned. All other items are
+    /// reused without being lexed or parsed again.
+    pub fn apply_edit(&mut self, edit: &TextEdit) -> Result<Range<usize>> {
+        let Range { start, end } = edit.range;
+        if start > end
+            || end > self.source.len()
+            || !self.source.is_char_boundary(start)
+            || !self.source.is_char_boundary(end)
+        {
+            return Err(Error::new(
+                format!("Invalid range {}..{} for text edit", start, end),
+                ErrorKind::Other,
+                None
+            ));
+        }
+
+        // The first item touched by the edit. Text inserted right after an
+        // item that does not end with a semicolon is part of that item.
+        let first = self
+            .items
+            .iter()
+            .position(|item| {
+                start < item.range.end || (start == item.range.end && !item.terminated)
+            })
+            .unwrap_or(self.items.len());
+        let mut last = first;
+        while last < self.items.len() && (last == first || self.items[last].range.start < end) {
+            last += 1;
+        }
+        let region_start = match self.items.get(first) {
+            Some(item) => item.range.start,
+            None => start
+        };
+        let region_end = if last > first {
+            self.items[last - 1].range.end.max(end)
+        } else {
+            end
+        };
+
+        self.source.replace_range(start..end, &edit.replacement);
+        let delta = edit.replacement.len() as isize - (end - start) as isize;
+        let mut region_end = (region_end as isize + delta) as usize;
+
+        // If the edit removed the semicolon at the end of the region, the
+        // region runs into the items after it.
+        let comment_char = self.options.get_comment_char();
+        while last < self.items.len() {
+            let pieces = split_items(&self.source[region_start..region_end], comment_char);
+            match pieces.last() {
+                Some((_, false)) => {
+                    let next = &self.items[last];
+                    region_end += next.range.end - next.range.start;
+                    last += 1;
+                },
+                _ => break
+            }
+        }
+
+        let precedence = self.precedence_before(first);
+        let old_precedence = self.precedence_before(last);
+        let mut new_items = Self::build_items(
+            &self.source,
+            region_start..region_end,
+            &self.options,
+            &precedence
+        );
+
+        // If the edit changed the operators that are defined, the items after
+        // the region may be parsed differently, so they are parsed again.
+        let new_precedence = match new_items.last() {
+            Some(item) => &item.precedence,
+            None => &precedence
+        };
+        if last < self.items.len() && *new_precedence != old_precedence {
+            last = self.items.len();
+            region_end = self.source.len();
+            new_items = Self::build_items(
+                &self.source,
+                region_start..region_end,
+                &self.options,
+                &precedence
+            );
+        }
+        let count = new_items.len();
+        for item in &mut self.items[last..] {
+            item.shift(delta);
+        }
+        self.items.splice(first..last, new_items);
+        Ok(first..first + count)
+    }
+}
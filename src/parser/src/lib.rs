@@ -2,10 +2,60 @@
 //! [`kaleidoscope_lexer`] to build an abstract syntax tree with nodes defined
 //! in [`kaleidoscope_ast`].
 
+pub mod diagnostics;
 pub mod driver;
 pub mod error;
+pub mod incremental;
 pub mod parser;
 pub mod precedence;
 
 #[cfg(test)]
 mod tests;
+
+use kaleidoscope_ast::nodes::ProgramNode;
+use kaleidoscope_lexer::tokenizer::{FileStream, TokenizerOptions};
+
+use crate::{diagnostics::Diagnostics, driver::Interpreter};
+
+/// Parse a whole Kaleidoscope programme. Parsing carries on past any
+/// statement that fails to parse, so that every error in the programme is
+/// returned together. See [`driver::Driver::parse_program`].
+///
+/// # Example
+///
+/// ```
+/// let program =
+///     kaleidoscope_parser::parse_program("extern def sin(x);\ndef f(x) sin(x);\nf(1);").unwrap();
+/// assert_eq!(program.externs().count(), 1);
+/// assert_eq!(program.functions().count(), 1);
+/// assert_eq!(program.expressions().count(), 1);
+///
+/// let diagnostics = kaleidoscope_parser::parse_program("def f(x x;\n1 +;\n2;").unwrap_err();
+/// assert_eq!(diagnostics.len(), 2);
+/// ```
+pub fn parse_program(source: &str) -> std::result::Result<ProgramNode, Diagnostics> {
+    parse_program_with_options(source, TokenizerOptions::default())
+}
+
+/// Parse a whole Kaleidoscope programme like [`parse_program`], but with the
+/// lexical rules in `options`.
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_lexer::tokenizer::TokenizerOptions;
+///
+/// let options = TokenizerOptions::new().with_comment_char('%');
+/// let program =
+///     kaleidoscope_parser::parse_program_with_options("def f(x) x; % comment\nf(1);", options)
+///         .unwrap();
+/// assert_eq!(program.len(), 2);
+/// ```
+pub fn parse_program_with_options(
+    source: &str,
+    options: TokenizerOptions
+) -> std::result::Result<ProgramNode, Diagnostics> {
+    let mut interpreter = Interpreter::batch(FileStream::from(source));
+    interpreter.set_tokenizer_options(options);
+    interpreter.parse_program()
+}
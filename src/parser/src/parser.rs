@@ -2,264 +2,237 @@
 //! parser can be used to generate Abstract Syntax Trees (ASTs), from which
 //! LLVM IR code can be created.
 
-use std::ops;
-
 use kaleidoscope_ast::{
     node::ExprNode,
     nodes::{
         BinaryOperatorNode,
+        BlockNode,
         ExternFunctionNode,
         FloatNode,
         FloatType,
+        ForLoopNode,
         FunctionCallNode,
         FunctionNode,
         FunctionPrototypeNode,
+        GlobalKind,
+        GlobalNode,
         IdentifierNode,
         IntegerNode,
         IntegerType,
+        LetNode,
+        LoopControl,
+        LoopControlNode,
         Operator,
-        VariableExpressionNode
+        PrototypeKind,
+        ReturnNode,
+        UnaryOperatorNode,
+        VarInNode,
+        VariableExpressionNode,
+        WhileLoopNode,
+        DEFAULT_BINARY_PRECEDENCE
     }
 };
 use kaleidoscope_lexer::{
-    ltuplemut,
-    token::{Bracket, BracketKind, FileIndex, Keyword, Token, TokenKind, LEFT_ROUND_BRACKET},
-    tokenizer::LexerTupleMut
+    error::Error as LexerError,
+    token::{
+        Bracket,
+        BracketKind,
+        Keyword,
+        Token,
+        TokenKind,
+        LEFT_CURLY_BRACKET,
+        LEFT_ROUND_BRACKET,
+        RIGHT_CURLY_BRACKET
+    },
+    tokenizer::{TokenCursor, TokenSource}
 };
 use kaleidoscope_macro::{ok_none, return_ok_some};
 
 use crate::{
     error::{Error, ErrorKind, Result},
-    precedence::BinaryOperatorPrecedence
+    precedence::{Associativity, PrecedenceTable}
 };
 
 /// The return type of most parser functions in [`Parser`].
 pub type ParseResult<T> = Result<Option<Box<T>>>;
 
-/// The token manager struct that determines when a [`Token`] should be released
-/// for the parser's use or when a new token should be read from the stream.
-/// This struct is marked private because the functionality of this struct
-/// is only useful when applied in conjunction with [`Parser`]'s methods.
-#[derive(Clone, Debug, Default)]
-struct ParserToken {
-    pub token: Option<Token>,
-    pub uses:  usize
-}
-
-#[allow(unused)]
-impl ParserToken {
-    /// Create a new [`ParserToken`] manager.
-    pub fn new(token: Token) -> Self {
-        Self {
-            token: Some(token),
-            uses:  0
-        }
-    }
-
-    /// Check if the [`Token`] stored inside has been used at least once or
-    /// not, if there is a token inside at all. The reason I added the second
-    /// condition is because the manager object only knows that the token
-    /// should be replaced when a fresh token is required; if there is no
-    /// token in the manager, then a new token should be supplied so that
-    /// when the [`Parser`] requests a new token, that token can be used.
-    #[inline]
-    pub fn unused(&self) -> bool {
-        self.uses < 1 && self.token.is_some()
-    }
-
-    /// Mark the token inside as unused. However, [`ParserToken::unused`] will
-    /// still return true if the token inside the struct does not exist.
-    #[inline]
-    pub fn set_unused(&mut self) -> &mut Self {
-        self.uses = 0;
-        self
-    }
-
-    /// Replace the token in the [`ParserToken`] manager with a new token,
-    /// resetting the use count back to 0, returning the previously-held
-    /// token.
-    //
-    /// See also: [`ParserToken::replace_used`].
-    pub fn replace(&mut self, token: Token) -> Option<Token> {
-        // println!("[{}] new token: {:?}\n", function_path!(), token);
-        let original = self.token.take();
-        self.token = Some(token);
-        self.uses = 0; // Do not remove this line
-        original
-    }
-
-    /// Replace the token in the [`ParserToken`] manager with a new token,
-    /// resetting the use count back to 0. However, if the old token hasn't
-    /// been used before, the old token gets discarded, returning [`None`]
-    /// in the process.
-    ///
-    /// See also: [`ParserToken::replace`].
-    #[inline]
-    pub fn replace_used(&mut self, token: Token) -> Option<Token> {
-        if self.unused() {
-            None
-        } else {
-            self.replace(token)
-        }
-    }
-
-    /// Peek at the token currently inside. This method clones the token
-    /// instead of returning a reference to avoid violating rust's
-    /// (im)mutable borrowing rules.
-    pub fn peek(&self) -> Option<Token> {
-        let token = self.token.clone();
-        log::trace!("Peeking at token: {:?}", token);
-        token
-    }
-
-    /// Increase the number of uses by one. This marks the token as "stale"
-    /// or used.
-    #[inline]
-    pub fn use_once(&mut self) -> &mut Self {
-        self.uses += 1;
-        self
-    }
-
-    /// Clone the token in the manager and mark it as used.
-    pub fn utilize(&mut self) -> Option<Token> {
-        self.use_once().peek()
-    }
+/// Wrap an error from the lexer in a parser error.
+fn lexer_error(error: LexerError) -> Error {
+    Error::from_err(Box::new(error), ErrorKind::LexerError)
 }
 
-impl ops::Deref for ParserToken {
-    type Target = Option<Token>;
-    fn deref(&self) -> &Self::Target {
-        &self.token
+/// Get the binary operator that a token stands for. Besides operator tokens,
+/// the `and` and `or` keywords are read as `&&` and `||`.
+fn binary_operator_of(token_kind: TokenKind) -> Option<Operator> {
+    match token_kind {
+        TokenKind::Operator(operator) => Some(operator),
+        TokenKind::Keyword(Keyword::And) => Some(Operator::LogicalAnd),
+        TokenKind::Keyword(Keyword::Or) => Some(Operator::LogicalOr),
+        _ => None
     }
 }
 
 /// The parser struct that converts a Kaleidoscope program into an Abstract
 /// Syntax Tree.
 pub struct Parser {
-    current_token: ParserToken
+    doc_comments: Vec<String>,
+    precedence:   PrecedenceTable,
+    loop_depth:   usize,
+    in_function:  bool
 }
 
 impl Parser {
     /// Create a new parser.
     pub fn new() -> Self {
+        Self::with_precedence_table(PrecedenceTable::new())
+    }
+
+    /// Create a new parser which knows about the operators in `precedence`.
+    pub fn with_precedence_table(precedence: PrecedenceTable) -> Self {
         Self {
-            current_token: Default::default()
+            doc_comments: Vec::new(),
+            precedence,
+            loop_depth: 0,
+            in_function: false
         }
     }
 
-    /// Replace the token the parser is currently reading with another one.
-    pub fn next_token(&mut self, token: Token) -> Result<&mut Self> {
-        self.current_token.replace(token);
-        Ok(self)
+    /// Get the table of operators known to the parser. User-defined
+    /// operators are added to this table as soon as their prototypes are
+    /// parsed.
+    pub fn get_precedence_table(&self) -> &PrecedenceTable {
+        &self.precedence
     }
 
-    /// Replace the token the parser is currently reading with another one,
-    /// given that it has been used or there was no token in the first place.
-    pub fn replace_used_token(&mut self, token: Token) -> Result<&mut Self> {
-        self.current_token.replace_used(token);
-        Ok(self)
+    /// Get a mutable reference to the table of operators known to the
+    /// parser.
+    pub fn get_mut_precedence_table(&mut self) -> &mut PrecedenceTable {
+        &mut self.precedence
     }
 
-    /// Mark the token in the manager as unused.
-    #[inline]
-    pub(crate) fn mark_unused(&mut self) -> &mut Self {
-        self.current_token.set_unused();
-        self
+    /// Peek at the token under the cursor without consuming it.
+    /// Documentation comments in front of the token are consumed and kept
+    /// for the function (or extern function) right after them.
+    pub fn peek_token<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> Result<Option<Token>> {
+        loop {
+            let token = match cursor.peek(0).map_err(lexer_error)? {
+                Some(token) => token,
+                None => return Ok(None)
+            };
+            match token.get_doc_comment() {
+                Some(doc) => {
+                    self.doc_comments.push(doc.to_string());
+                    cursor.next_token().map_err(lexer_error)?;
+                },
+                None => {
+                    // Documentation comments only belong to the function (or
+                    // extern function) immediately after them.
+                    if !matches!(
+                        token.token_kind,
+                        TokenKind::Keyword(Keyword::Def) | TokenKind::Keyword(Keyword::Extern)
+                    ) {
+                        self.doc_comments.clear();
+                    }
+                    return Ok(Some(token.clone()));
+                }
+            }
+        }
     }
 
-    /// Mark the token in the manager as used.
-    #[inline]
-    pub(crate) fn mark_used(&mut self) -> &mut Self {
-        self.current_token.use_once();
-        self
+    /// Consume the token under the cursor, returning it.
+    pub fn next_token<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> Result<Option<Token>> {
+        let token = self.peek_token(cursor)?;
+        cursor.next_token().map_err(lexer_error)?;
+        Ok(token)
     }
 
-    /// Replace the token with a new token from the stream and tokenizer.
-    #[inline]
-    #[allow(dead_code)]
-    fn grab_token_from_tokenizer<'a, 'b: 'a>(
-        &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
-    ) -> Result<&mut Self> {
-        self.next_token(match tokenizer.next_token(stream) {
-            Ok(token) => token,
-            Err(e) => return Err(Error::from_err(Box::new(e), ErrorKind::LexerError))
-        })
-    }
-
-    /// Pull a new token from the tokenizer and file stream if the token
-    /// stored by the parser has been used.
-    #[inline]
-    fn grab_if_used<'a, 'b: 'a>(
+    /// Take the documentation comments that came before the current token,
+    /// joining them into one string.
+    fn take_documentation(&mut self) -> Option<String> {
+        if self.doc_comments.is_empty() {
+            None
+        } else {
+            let documentation = self.doc_comments.join("\n");
+            self.doc_comments.clear();
+            Some(documentation)
+        }
+    }
+
+    /// Take the current token if it is of the kind `expected`. Otherwise, a
+    /// syntax error saying that `description` was expected `context` is
+    /// returned.
+    fn expect_token<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
-    ) -> Result<&mut Self> {
-        // DO NOT REMOVE THIS IF BLOCK
-        // BECAUSE IF YOU DO THE TOKENS
-        // WILL FALL OFF THE CLIFF BEFORE
-        // THEY GET PROCESSED
-        if self.current_token.unused() {
-            return Ok(self);
-        }
-        self.replace_used_token(match tokenizer.next_token(stream) {
-            Ok(token) => token,
-            Err(e) => return Err(Error::from_err(Box::new(e), ErrorKind::LexerError))
-        })
-    }
-
-    /// Get the current token being stored, marking as used in the process.
-    #[inline]
-    #[allow(dead_code)]
-    fn get_current_token(&mut self) -> Option<Token> {
-        let token = self.current_token.utilize();
-        // println!("GRABBED {:?}\n", token);
-        token
-    }
-
-    /// Peek at the current token being stored, without marking it as used.
-    #[inline]
-    pub fn peek_current_token(&self) -> Option<Token> {
-        let token = self.current_token.peek();
-        // println!("[{}] {:?}", function_path!(), token);
-        // println!("[{}] uses: {}\n", function_path!(), self.current_token.uses);
-        token
-    }
-
-    /// Helper code that finds a right bracket that cancels out a left bracket.
-    #[allow(dead_code)]
-    fn find_matching_right_round_bracket<'a, 'b: 'a>(
+        cursor: &mut TokenCursor<S>,
+        expected: TokenKind,
+        description: &str,
+        context: &str
+    ) -> Result<Token> {
+        self.expect_token_of(cursor, &[expected], description, context)
+    }
+
+    /// Take the current token if it is of any of the kinds in `expected`.
+    /// See [`Parser::expect_token`].
+    fn expect_token_of<S: TokenSource>(
         &mut self,
-        lbracket_index: FileIndex,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>,
+        expected: &[TokenKind],
+        description: &str,
+        context: &str
     ) -> Result<Token> {
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        match self.peek_current_token() {
+        let token = self.peek_token(cursor)?.ok_or_else(|| {
+            Error::new(
+                format!(
+                    "Unexpected EOF at {}: expected {} {}",
+                    cursor.get_index(),
+                    description,
+                    context
+                ),
+                ErrorKind::SyntaxError,
+                None
+            )
+        })?;
+        if !expected.contains(&token.token_kind) {
+            return Err(Error::new(
+                format!(
+                    "Expected {} {} at {}, found '{}'",
+                    description,
+                    context,
+                    token.start,
+                    token.borrow_span()
+                ),
+                ErrorKind::SyntaxError,
+                None
+            ));
+        }
+        self.next_token(cursor)?;
+        Ok(token)
+    }
+
+    /// Parse an expression that must be there, returning a syntax error
+    /// saying that an expression was expected `context` otherwise.
+    fn expect_expression<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>,
+        context: &str
+    ) -> Result<Box<dyn ExprNode>> {
+        match self.parse_expression(cursor)? {
+            Some(expression) => Ok(expression),
             None => Err(Error::new(
                 format!(
-                    "No matching right round bracket found for '(' at {}",
-                    lbracket_index
+                    "Expected an expression {} at {}",
+                    context,
+                    cursor.get_index()
                 ),
                 ErrorKind::SyntaxError,
                 None
-            )),
-            Some(token) => match token.token_kind {
-                TokenKind::Bracket(bracket) =>
-                    if bracket.side.is_right() && matches!(bracket.kind, BracketKind::Round) {
-                        self.mark_used();
-                        Ok(token.clone())
-                    } else {
-                        Err(Error::new(
-                            format!("Unmatched bracket {} at {}", bracket, token.start),
-                            ErrorKind::SyntaxError,
-                            None
-                        ))
-                    },
-                _ => Err(Error::new(
-                    format!("Expected right round bracket at {}", token.start),
-                    ErrorKind::SyntaxError,
-                    None
-                ))
-            }
+            ))
         }
     }
 
@@ -267,31 +240,29 @@ impl Parser {
     /// in the list is separated by a comma token (i.e. ",") and the entire
     /// list is flanked by a left bracket before the first expression and
     /// its corresponding right bracket after the last expression.
-    pub fn parse_comma_expression_list<'a, 'b: 'a>(
+    pub fn parse_comma_expression_list<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>,
+        cursor: &mut TokenCursor<S>,
         left_bracket: Bracket
     ) -> Result<Option<Vec<Box<dyn ExprNode>>>> {
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let lbracket_token = ok_none!(self.peek_current_token());
+        let lbracket_token = ok_none!(self.peek_token(cursor)?);
         log::trace!("lbracket_token: {}", lbracket_token);
         match lbracket_token.token_kind {
             TokenKind::Bracket(bracket) if bracket == left_bracket => (),
             _ => return Ok(None)
         }
-        self.mark_used();
+        self.next_token(cursor)?;
 
         let mut args: Vec<Box<dyn ExprNode>> = Vec::new();
 
         loop {
-            let expression = self.parse_expression(ltuplemut!(stream, tokenizer))?;
+            let expression = self.parse_expression(cursor)?;
 
-            self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-            let token_1 = self.peek_current_token().ok_or_else(|| {
+            let token_1 = self.peek_token(cursor)?.ok_or_else(|| {
                 Error::new(
                     format!(
                         "Unexpected EOF when trying to parse comma expression list at {}",
-                        stream.get_index()
+                        cursor.get_index()
                     ),
                     ErrorKind::SyntaxError,
                     None
@@ -309,14 +280,14 @@ impl Parser {
                         None
                     ))?;
                     args.push(expression);
-                    self.mark_used();
+                    self.next_token(cursor)?;
                 },
                 TokenKind::Bracket(bracket) =>
                     if left_bracket.cancels_out(bracket) {
                         if let Some(expression) = expression {
                             args.push(expression);
                         }
-                        self.mark_used();
+                        self.next_token(cursor)?;
                         break;
                     } else {
                         return Err(Error::new(
@@ -344,13 +315,13 @@ impl Parser {
     }
 
     /// Converts an expression into an anonymous function.
-    pub fn parse_top_level_expression<'a, 'b: 'a>(
+    pub fn parse_top_level_expression<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<dyn ExprNode> {
-        self.parse_expression(ltuplemut!(stream, tokenizer))
+        self.parse_expression(cursor)
         /* let expression = match
-            self.parse_expression(ltuplemut!(stream, tokenizer))?
+            self.parse_expression(cursor)?
         {
             Some(ex) => ex,
             None => return Ok(None)
@@ -366,25 +337,25 @@ impl Parser {
     }
 
     /// Parse an expression.
-    pub fn parse_expression<'a, 'b: 'a>(
+    pub fn parse_expression<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<dyn ExprNode> {
-        let lhs = self.parse_primary_expression(ltuplemut!(stream, tokenizer))?;
-        log::trace!("primary expression parsed");
+        let lhs = self.parse_unary_expression(cursor)?;
+        log::trace!("unary expression parsed");
         let mut escaped_from_inner = false;
         let expression = match lhs {
             None => Ok(None),
             Some(lhs) => self.parse_binary_operator_rhs_expression(
                 lhs,
                 Operator::Unknown,
-                BinaryOperatorPrecedence::Unknown,
+                0,
                 &mut escaped_from_inner,
                 0,
-                ltuplemut!(stream, tokenizer)
+                cursor
             )
         }?;
-        match self.peek_current_token() {
+        match self.peek_token(cursor)? {
             Some(token) =>
                 if token.denotes_end_of_expression() {
                     Ok(expression)
@@ -402,34 +373,79 @@ impl Parser {
         }
     }
 
+    /// Parse an expression which may start with prefix unary operators, such
+    /// as `-x` or `!(a < b)`. Unary operators bind tighter than any binary
+    /// operator, so `-x * y` is read as `(-x) * y`.
+    pub fn parse_unary_expression<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> ParseResult<dyn ExprNode> {
+        let token = ok_none!(self.peek_token(cursor)?);
+        let operator = match token.token_kind {
+            TokenKind::Operator(operator) if self.precedence.is_unary_operator(operator) =>
+                operator,
+            _ => return self.parse_primary_expression(cursor)
+        };
+        self.next_token(cursor)?;
+        let operand = match self.parse_unary_expression(cursor)? {
+            Some(operand) => operand,
+            None =>
+                return Err(Error::new(
+                    format!(
+                        "No expression after unary operator {} at {}",
+                        operator, token.start
+                    ),
+                    ErrorKind::SyntaxError,
+                    None
+                )),
+        };
+        Ok(Some(Box::new(UnaryOperatorNode::new(
+            Box::new(operator),
+            operand
+        ))))
+    }
+
     /// Parse a so-called "primary" expression. You can think of primary
     /// expressions as simple expressions, which means that parsing it is
     /// relatively trivial. This category includes integers, floats, variables
     /// and other expressions wrapped around two corresponding round brackets.
-    pub fn parse_primary_expression<'a, 'b: 'a>(
+    pub fn parse_primary_expression<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<dyn ExprNode> {
-        let integer = self.parse_integer_expression(ltuplemut!(stream, tokenizer))?;
+        let integer = self.parse_integer_expression(cursor)?;
         return_ok_some!(integer);
-        let float = self.parse_float_expression(ltuplemut!(stream, tokenizer))?;
+        let float = self.parse_float_expression(cursor)?;
         return_ok_some!(float);
-        let rbexpr = self.parse_round_bracket_expression(ltuplemut!(stream, tokenizer))?;
+        let rbexpr = self.parse_round_bracket_expression(cursor)?;
         return_ok_some!(rbexpr);
-        let funccall = self.parse_function_call_expression(ltuplemut!(stream, tokenizer))?;
+        let block = self.parse_block_expression(cursor)?;
+        return_ok_some!(block);
+        let forloop = self.parse_for_expression(cursor)?;
+        return_ok_some!(forloop);
+        let whileloop = self.parse_while_expression(cursor)?;
+        return_ok_some!(whileloop);
+        let varin = self.parse_var_expression(cursor)?;
+        return_ok_some!(varin);
+        let letexpr = self.parse_let_expression(cursor)?;
+        return_ok_some!(letexpr);
+        let loop_control = self.parse_loop_control_expression(cursor)?;
+        return_ok_some!(loop_control);
+        let returnexpr = self.parse_return_expression(cursor)?;
+        return_ok_some!(returnexpr);
+        let funccall = self.parse_function_call_expression(cursor)?;
         return_ok_some!(funccall);
-        let variable = self.parse_variable_expression(ltuplemut!(stream, tokenizer))?;
+        let variable = self.parse_variable_expression(cursor)?;
         return_ok_some!(variable);
         Ok(None)
     }
 
     /// Parse an integer expression.
-    pub fn parse_integer_expression<'a, 'b: 'a>(
+    pub fn parse_integer_expression<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<dyn ExprNode> {
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let token = ok_none!(self.peek_current_token());
+        let token = ok_none!(self.peek_token(cursor)?);
         // println!("[{}] token: {:?}\n", function_path!(), token);
         if let TokenKind::Integer = token.token_kind {
             // println!("[{}] integer detected\n", function_path!());
@@ -437,7 +453,7 @@ impl Parser {
                 Ok(i) => i,
                 Err(e) => return Err(Error::from_err(Box::new(e), ErrorKind::ParsingError))
             };
-            self.mark_used();
+            self.next_token(cursor)?;
             Ok(Some(Box::new(IntegerNode::new(rust_integer))))
         } else {
             Ok(None)
@@ -445,12 +461,11 @@ impl Parser {
     }
 
     /// Parse a float expression.
-    pub fn parse_float_expression<'a, 'b: 'a>(
+    pub fn parse_float_expression<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<dyn ExprNode> {
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let token = ok_none!(self.peek_current_token());
+        let token = ok_none!(self.peek_token(cursor)?);
         // println!("[{}] token: {:?}\n", function_path!(), token);
         if let TokenKind::Float = token.token_kind {
             // println!("[{}] float detected\n", function_path!());
@@ -458,7 +473,7 @@ impl Parser {
                 Ok(f) => f,
                 Err(e) => return Err(Error::from_err(Box::new(e), ErrorKind::ParsingError))
             };
-            self.mark_used();
+            self.next_token(cursor)?;
             Ok(Some(Box::new(FloatNode::new(rust_float))))
         } else {
             Ok(None)
@@ -466,17 +481,16 @@ impl Parser {
     }
 
     /// Parse a variable expression.
-    pub fn parse_variable_expression<'a, 'b: 'a>(
+    pub fn parse_variable_expression<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<dyn ExprNode> {
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let token = ok_none!(self.peek_current_token());
+        let token = ok_none!(self.peek_token(cursor)?);
         // println!("[{}] token: {:?}\n", function_path!(), token);
         if let TokenKind::Identifier = token.token_kind {
             // println!("[{}] identifier detected\n", function_path!());
             let identifier = Box::new(IdentifierNode::new(token.borrow_span().to_string()));
-            self.mark_used();
+            self.next_token(cursor)?;
             Ok(Some(Box::new(VariableExpressionNode::new(identifier))))
         } else {
             Ok(None)
@@ -484,12 +498,11 @@ impl Parser {
     }
 
     /// Parse an expression wrapped inside 2 round brackets.
-    pub fn parse_round_bracket_expression<'a, 'b: 'a>(
+    pub fn parse_round_bracket_expression<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<dyn ExprNode> {
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let token = ok_none!(self.peek_current_token());
+        let token = ok_none!(self.peek_token(cursor)?);
         // println!("[{}] token: {:?}\n", function_path!(), token);
         let left_bracket = match token.token_kind {
             TokenKind::Bracket(bracket) => bracket,
@@ -498,9 +511,9 @@ impl Parser {
         if left_bracket != LEFT_ROUND_BRACKET {
             return Ok(None);
         }
-        self.mark_used();
+        self.next_token(cursor)?;
         // println!("[{}] left bracket verified\n", function_path!());
-        let expression = match self.parse_expression(ltuplemut!(stream, tokenizer))? {
+        let expression = match self.parse_expression(cursor)? {
             Some(x) => x,
             None =>
                 return Err(Error::new(
@@ -510,8 +523,7 @@ impl Parser {
                 )),
         };
         // println!("[{}] inner expression parsed\n", function_path!());
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let token = match self.peek_current_token() {
+        let token = match self.peek_token(cursor)? {
             Some(t) => t,
             None =>
                 return Err(Error::new(
@@ -520,7 +532,7 @@ impl Parser {
                     None
                 )),
         };
-        self.mark_used();
+        self.next_token(cursor)?;
         let right_bracket = match token.token_kind {
             TokenKind::Bracket(bracket) => bracket,
             _ =>
@@ -541,16 +553,57 @@ impl Parser {
         Ok(Some(expression))
     }
 
+    /// Parse a block of expressions in curly brackets, such as
+    /// `{ f(x); g(x); x }`. The expressions are separated by semicolons, and
+    /// the last one can be followed by a semicolon too. See [`BlockNode`].
+    pub fn parse_block_expression<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> ParseResult<dyn ExprNode> {
+        let token = ok_none!(self.peek_token(cursor)?);
+        if token.token_kind != TokenKind::Bracket(LEFT_CURLY_BRACKET) {
+            return Ok(None);
+        }
+        self.next_token(cursor)?;
+
+        let mut expressions = Vec::new();
+        loop {
+            // A semicolon inside the block only separates expressions, so it
+            // must not be left for the caller to treat as the end of a
+            // statement.
+            if let Some(token) = self.peek_token(cursor)? {
+                if token.token_kind == TokenKind::Bracket(RIGHT_CURLY_BRACKET) {
+                    self.next_token(cursor)?;
+                    break;
+                }
+            }
+            expressions.push(self.expect_expression(cursor, "in a block")?);
+            let token = self.expect_token_of(
+                cursor,
+                &[
+                    TokenKind::Semicolon,
+                    TokenKind::Bracket(RIGHT_CURLY_BRACKET)
+                ],
+                "';' or '}'",
+                "after an expression in a block"
+            )?;
+            if token.token_kind != TokenKind::Semicolon {
+                break;
+            }
+        }
+        Ok(Some(Box::new(BlockNode::new(expressions))))
+    }
+
     /// Parse a binary operator expression. This is similar to simple math
     /// equations like `1 + 1` or `5 * 3`.
-    pub fn parse_binary_operator_rhs_expression<'a, 'b: 'a>(
+    pub fn parse_binary_operator_rhs_expression<S: TokenSource>(
         &mut self,
         mut lhs: Box<dyn ExprNode>,
         mut loperator: Operator,
-        minimum_operator_precedence: BinaryOperatorPrecedence,
+        minimum_operator_precedence: u32,
         escaped_from_inner: &mut bool,
         depth: usize,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<dyn ExprNode> {
         #[inline]
         fn up(
@@ -565,27 +618,24 @@ impl Parser {
             ))))
         }
 
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
         if let Operator::Unknown = loperator {
-            let possible_loperator = match self.peek_current_token() {
+            let possible_loperator = match self.peek_token(cursor)? {
                 Some(o) => o,
                 None => {
                     *escaped_from_inner = true;
                     return Ok(Some(lhs));
                 }
             };
-            match possible_loperator.token_kind {
-                TokenKind::Operator(operator) => {
-                    self.mark_used();
-                    loperator = operator;
-                },
-                _ => {
+            match binary_operator_of(possible_loperator.token_kind) {
+                Some(operator) => loperator = operator,
+                None => {
                     *escaped_from_inner = true;
                     return Ok(Some(lhs));
                 }
             }
         }
         let mut roperator = Operator::Unknown;
+        let mut previous_operator: Option<Operator> = None;
 
         // I have no idea what the code below does
         // UPDATE
@@ -622,16 +672,13 @@ impl Parser {
             //     escaped_from_inner
             // );
             if *escaped_from_inner {
-                let loperator_token = match self.peek_current_token() {
+                let loperator_token = match self.peek_token(cursor)? {
                     Some(t) => t,
                     None => return Ok(Some(lhs))
                 };
-                loperator = match loperator_token.token_kind {
-                    TokenKind::Operator(operator) => {
-                        self.mark_used();
-                        operator
-                    },
-                    _ => return Ok(Some(lhs))
+                loperator = match binary_operator_of(loperator_token.token_kind) {
+                    Some(operator) => operator,
+                    None => return Ok(Some(lhs))
                 };
             } else if !matches!(roperator, Operator::Unknown) {
                 loperator = roperator;
@@ -642,13 +689,42 @@ impl Parser {
             //     depth,
             //     loperator
             // );
-            let lprecedence: BinaryOperatorPrecedence = loperator.into();
+            let (lprecedence, lassociativity) = match (
+                self.precedence.get_binary_precedence(loperator),
+                self.precedence.get_associativity(loperator)
+            ) {
+                (Some(precedence), Some(associativity)) => (precedence, associativity),
+                _ =>
+                    return Err(Error::new(
+                        format!("'{}' is not a binary operator", loperator),
+                        ErrorKind::SyntaxError,
+                        None
+                    )),
+            };
+            if let Some(previous) = previous_operator {
+                let previous_associativity = self.precedence.get_associativity(previous);
+                if self.precedence.get_binary_precedence(previous) == Some(lprecedence)
+                    && (lassociativity == Associativity::None
+                        || previous_associativity == Some(Associativity::None))
+                {
+                    return Err(Error::new(
+                        format!(
+                            "'{}' and '{}' cannot be chained without brackets",
+                            previous, loperator
+                        ),
+                        ErrorKind::SyntaxError,
+                        None
+                    ));
+                }
+            }
+            // The operator is left under the cursor until it is known to take
+            // the expression before it as its left-hand side.
             if lprecedence < minimum_operator_precedence {
-                self.mark_unused();
                 *escaped_from_inner = true;
                 return Ok(Some(lhs));
             }
-            let mut rhs = match self.parse_primary_expression(ltuplemut!(stream, tokenizer))? {
+            self.next_token(cursor)?;
+            let mut rhs = match self.parse_unary_expression(cursor)? {
                 Some(rhs) => rhs,
                 None =>
                     return Err(Error::new(
@@ -657,43 +733,53 @@ impl Parser {
                         None
                     )),
             };
-            self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-            let possible_roperator = match self.peek_current_token() {
+            let possible_roperator = match self.peek_token(cursor)? {
                 Some(token) => token,
                 None => {
                     *escaped_from_inner = true;
                     return up(loperator, lhs, rhs);
                 }
             };
-            roperator = match possible_roperator.token_kind {
-                TokenKind::Operator(operator) => operator,
-                _ => {
+            roperator = match binary_operator_of(possible_roperator.token_kind) {
+                Some(operator) => operator,
+                None => {
                     *escaped_from_inner = true;
                     return up(loperator, lhs, rhs);
                 }
             };
-            self.mark_used();
             // println!(
             //     "[{}]{} roperator: {:?}\n",
             //     function_path!(),
             //     depth,
             //     roperator
             // );
-            let rprecedence = BinaryOperatorPrecedence::from_operator(roperator);
-            if lprecedence < rprecedence {
+            let rprecedence = self
+                .precedence
+                .get_binary_precedence(roperator)
+                .unwrap_or(0);
+            // A right-associative operator also lets the operators of the
+            // same precedence after it take its right-hand side.
+            let right_grouped =
+                lprecedence == rprecedence && lassociativity == Associativity::Right;
+            if lprecedence < rprecedence || right_grouped {
                 *escaped_from_inner = false;
                 rhs = ok_none!(self.parse_binary_operator_rhs_expression(
                     rhs,
                     roperator,
-                    rprecedence,
+                    if right_grouped {
+                        lprecedence
+                    } else {
+                        lprecedence + 1
+                    },
                     escaped_from_inner,
                     depth + 1,
-                    ltuplemut!(stream, tokenizer)
+                    cursor
                 )?);
             }
             // Collect all expressions to the left-hand side.
             // For a right-hand language, rhs is replaced instead.
             lhs = Box::new(BinaryOperatorNode::new(Box::new(loperator), lhs, rhs));
+            previous_operator = Some(loperator);
             // println!("[{}]{} new lhs: {}\n", function_path!(), depth, lhs);
         }
     }
@@ -707,38 +793,27 @@ impl Parser {
     /// a [`FunctionCallNode`] where the function being called is `pow` and
     /// the arguments (in the correct order) are `4` and `2`.
     ///
-    /// As the [`Parser`] is not allowed to reverse tokens when reading
-    /// a stream, this function has to shoulder the burden of also doing the
-    /// job of [`Parser::parse_variable_expression`]. If it encounters an
-    /// identifier which does not have a left, round bracket token ("(")
-    /// following it, it will do what [`Parser::parse_variable_expression`]
-    /// does and returns a [`VariableExpressionNode`].
-    pub fn parse_function_call_expression<'a, 'b: 'a>(
+    /// An identifier that is not followed by a left, round bracket token
+    /// ("(") is left for [`Parser::parse_variable_expression`].
+    pub fn parse_function_call_expression<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<dyn ExprNode> {
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let identifier_token = ok_none!(self.peek_current_token());
+        let identifier_token = ok_none!(self.peek_token(cursor)?);
         let identifier = match identifier_token.token_kind {
             TokenKind::Identifier => Box::new(IdentifierNode::new(
                 identifier_token.borrow_span().to_string()
             )),
             _ => return Ok(None)
         };
-        self.mark_used();
-
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let lbracket_token = match self.peek_current_token() {
-            Some(t) => t,
-            None => return Ok(Some(Box::new(VariableExpressionNode::new(identifier))))
-        };
-        match lbracket_token.token_kind {
-            TokenKind::Bracket(bracket) if bracket == LEFT_ROUND_BRACKET => (),
-            _ => return Ok(Some(Box::new(VariableExpressionNode::new(identifier))))
+        match cursor.peek(1).map_err(lexer_error)? {
+            Some(token) if token.token_kind == TokenKind::Bracket(LEFT_ROUND_BRACKET) => (),
+            _ => return Ok(None)
         }
+        self.next_token(cursor)?;
 
         let args = self
-            .parse_comma_expression_list(ltuplemut!(stream, tokenizer), LEFT_ROUND_BRACKET)?
+            .parse_comma_expression_list(cursor, LEFT_ROUND_BRACKET)?
             .ok_or_else(|| {
                 Error::new(
                     format!(
@@ -753,14 +828,227 @@ impl Parser {
         Ok(Some(Box::new(FunctionCallNode::new(identifier, args))))
     }
 
+    /// Parse a `for` loop, such as `for i = 0, i < n, 1 in f(i)`. The step
+    /// after the condition is optional. See [`ForLoopNode`].
+    pub fn parse_for_expression<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> ParseResult<dyn ExprNode> {
+        let for_token = ok_none!(self.peek_token(cursor)?);
+        if for_token.token_kind != TokenKind::Keyword(Keyword::For) {
+            return Ok(None);
+        }
+        self.next_token(cursor)?;
+
+        let variable = self.expect_token(
+            cursor,
+            TokenKind::Identifier,
+            "a loop variable",
+            "after 'for'"
+        )?;
+        let variable = Box::new(IdentifierNode::new(variable.borrow_span().to_string()));
+        self.expect_token(
+            cursor,
+            TokenKind::Operator(Operator::Assign),
+            "'='",
+            "after the loop variable"
+        )?;
+        let start = self.expect_expression(cursor, "for the start of the loop")?;
+        self.expect_token(
+            cursor,
+            TokenKind::Comma,
+            "','",
+            "after the start of the loop"
+        )?;
+        let condition = self.expect_expression(cursor, "for the loop condition")?;
+
+        let step = match self.peek_token(cursor)? {
+            Some(token) if token.token_kind == TokenKind::Comma => {
+                self.next_token(cursor)?;
+                Some(self.expect_expression(cursor, "for the loop step")?)
+            },
+            _ => None
+        };
+        self.expect_token(
+            cursor,
+            TokenKind::Keyword(Keyword::In),
+            "'in'",
+            "after the loop header"
+        )?;
+        let body = self.expect_loop_body(cursor)?;
+        Ok(Some(Box::new(ForLoopNode::new(
+            variable, start, condition, step, body
+        ))))
+    }
+
+    /// Parse a `while` loop, such as `while x < 10 do f(x)`. See
+    /// [`WhileLoopNode`].
+    pub fn parse_while_expression<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> ParseResult<dyn ExprNode> {
+        let while_token = ok_none!(self.peek_token(cursor)?);
+        if while_token.token_kind != TokenKind::Keyword(Keyword::While) {
+            return Ok(None);
+        }
+        self.next_token(cursor)?;
+
+        let condition = self.expect_expression(cursor, "for the loop condition")?;
+        self.expect_token(
+            cursor,
+            TokenKind::Keyword(Keyword::Do),
+            "'do'",
+            "after the loop condition"
+        )?;
+        let body = self.expect_loop_body(cursor)?;
+        Ok(Some(Box::new(WhileLoopNode::new(condition, body))))
+    }
+
+    /// Parse mutable local variables, such as `var x = 1, y in x + y`. See
+    /// [`VarInNode`].
+    pub fn parse_var_expression<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> ParseResult<dyn ExprNode> {
+        let var_token = ok_none!(self.peek_token(cursor)?);
+        if var_token.token_kind != TokenKind::Keyword(Keyword::Var) {
+            return Ok(None);
+        }
+        self.next_token(cursor)?;
+
+        let mut bindings = Vec::new();
+        loop {
+            let variable =
+                self.expect_token(cursor, TokenKind::Identifier, "a variable name", "in 'var'")?;
+            let variable = Box::new(IdentifierNode::new(variable.borrow_span().to_string()));
+            let value = match self.peek_token(cursor)? {
+                Some(token) if token.token_kind == TokenKind::Operator(Operator::Assign) => {
+                    self.next_token(cursor)?;
+                    Some(self.expect_expression(cursor, "for the value of a variable")?)
+                },
+                _ => None
+            };
+            bindings.push((variable, value));
+
+            match self.peek_token(cursor)? {
+                Some(token) if token.token_kind == TokenKind::Comma => {
+                    self.next_token(cursor)?;
+                },
+                _ => break
+            }
+        }
+        self.expect_token(
+            cursor,
+            TokenKind::Keyword(Keyword::In),
+            "'in'",
+            "after the variables"
+        )?;
+        let body = self.expect_expression(cursor, "for the body of 'var'")?;
+        Ok(Some(Box::new(VarInNode::new(bindings, body))))
+    }
+
+    /// Parse an immutable binding, such as `let x = f(1) in x * x`. See
+    /// [`LetNode`].
+    pub fn parse_let_expression<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> ParseResult<dyn ExprNode> {
+        let let_token = ok_none!(self.peek_token(cursor)?);
+        if let_token.token_kind != TokenKind::Keyword(Keyword::Let) {
+            return Ok(None);
+        }
+        self.next_token(cursor)?;
+
+        let identifier =
+            self.expect_token(cursor, TokenKind::Identifier, "a name", "after 'let'")?;
+        let identifier = Box::new(IdentifierNode::new(identifier.borrow_span().to_string()));
+        self.expect_token(
+            cursor,
+            TokenKind::Operator(Operator::Assign),
+            "'='",
+            "after the name in 'let'"
+        )?;
+        let value = self.expect_expression(cursor, "for the value in 'let'")?;
+        self.expect_token(
+            cursor,
+            TokenKind::Keyword(Keyword::In),
+            "'in'",
+            "after the value in 'let'"
+        )?;
+        let body = self.expect_expression(cursor, "for the body of 'let'")?;
+        Ok(Some(Box::new(LetNode::new(identifier, value, body))))
+    }
+
+    /// Parse `break` or `continue`. These are only allowed in the body of a
+    /// loop. See [`LoopControlNode`].
+    pub fn parse_loop_control_expression<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> ParseResult<dyn ExprNode> {
+        let token = ok_none!(self.peek_token(cursor)?);
+        let control = match token.token_kind {
+            TokenKind::Keyword(Keyword::Break) => LoopControl::Break,
+            TokenKind::Keyword(Keyword::Continue) => LoopControl::Continue,
+            _ => return Ok(None)
+        };
+        if self.loop_depth == 0 {
+            return Err(Error::new(
+                format!(
+                    "'{}' can only be used inside a loop at {}",
+                    control, token.start
+                ),
+                ErrorKind::SyntaxError,
+                None
+            ));
+        }
+        self.next_token(cursor)?;
+        Ok(Some(Box::new(LoopControlNode::new(control))))
+    }
+
+    /// Parse `return` followed by the value to return. This is only allowed
+    /// in the body of a function. See [`ReturnNode`].
+    pub fn parse_return_expression<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> ParseResult<dyn ExprNode> {
+        let return_token = ok_none!(self.peek_token(cursor)?);
+        if return_token.token_kind != TokenKind::Keyword(Keyword::Return) {
+            return Ok(None);
+        }
+        if !self.in_function {
+            return Err(Error::new(
+                format!(
+                    "'return' can only be used inside a function at {}",
+                    return_token.start
+                ),
+                ErrorKind::SyntaxError,
+                None
+            ));
+        }
+        self.next_token(cursor)?;
+        let value = self.expect_expression(cursor, "after 'return'")?;
+        Ok(Some(Box::new(ReturnNode::new(value))))
+    }
+
+    /// Parse the body of a loop, in which `break` and `continue` are
+    /// allowed.
+    fn expect_loop_body<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> Result<Box<dyn ExprNode>> {
+        self.loop_depth += 1;
+        let body = self.expect_expression(cursor, "for the loop body");
+        self.loop_depth -= 1;
+        body
+    }
+
     /// Parse a function prototype.
-    pub fn parse_function_prototype<'a, 'b: 'a>(
+    pub fn parse_function_prototype<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<FunctionPrototypeNode> {
         // println!("[{}] Entering\n", function_path!());
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let def_token = ok_none!(self.peek_current_token());
+        let def_token = ok_none!(self.peek_token(cursor)?);
         match def_token.token_kind {
             TokenKind::Keyword(keyword) => match keyword {
                 Keyword::Def => (),
@@ -768,11 +1056,11 @@ impl Parser {
             },
             _ => return Ok(None)
         };
-        self.mark_used();
+        self.next_token(cursor)?;
+        let documentation = self.take_documentation();
         // println!("[{}] def keyword found!\n", function_path!());
 
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let function_identifier_token = match self.peek_current_token() {
+        let function_identifier_token = match self.peek_token(cursor)? {
             Some(t) => t,
             None =>
                 return Err(Error::new(
@@ -784,11 +1072,18 @@ impl Parser {
                     None
                 )),
         };
-        self.mark_used();
+        self.next_token(cursor)?;
+        let kind = match function_identifier_token.token_kind {
+            TokenKind::Keyword(Keyword::Unary) | TokenKind::Keyword(Keyword::Binary) =>
+                self.parse_operator_prototype_kind(&function_identifier_token, cursor)?,
+            _ => PrototypeKind::Function
+        };
         let function_identifier = match function_identifier_token.token_kind {
             TokenKind::Identifier => Box::new(IdentifierNode::new(
                 function_identifier_token.borrow_span().to_string()
             )),
+            _ if kind != PrototypeKind::Function =>
+                Box::new(IdentifierNode::new(kind.operator_function_name().unwrap())),
             _ =>
                 return Err(Error::new(
                     format!(
@@ -805,8 +1100,7 @@ impl Parser {
         //     function_identifier.get_identifier()
         // );
 
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let lbracket_token = match self.get_current_token() {
+        let lbracket_token = match self.next_token(cursor)? {
             Some(t) => t,
             None =>
                 return Err(Error::new(
@@ -837,19 +1131,17 @@ impl Parser {
         let mut parameters: Vec<Box<IdentifierNode>> = Vec::new();
 
         loop {
-            self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-            if let Some(token_1) = self.get_current_token() {
+            if let Some(token_1) = self.next_token(cursor)? {
                 match token_1.token_kind {
                     TokenKind::Bracket(bracket) =>
                         if LEFT_ROUND_BRACKET.cancels_out(bracket) {
-                            self.mark_used();
                             break;
                         } else {
                             return Err(Error::new(
                                 format!(
                                     "Unexpected bracket '{}' at {}",
                                     bracket,
-                                    stream.get_index()
+                                    cursor.get_index()
                                 ),
                                 ErrorKind::SyntaxError,
                                 None
@@ -862,20 +1154,19 @@ impl Parser {
                     },
                     _ => {
                         return Err(Error::new(
-                            format!("Unexpected token '{}' at {}", token_1, stream.get_index()),
+                            format!("Unexpected token '{}' at {}", token_1, cursor.get_index()),
                             ErrorKind::SyntaxError,
                             None
                         ));
                     }
                 }
-                self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-                let token_2 = match self.get_current_token() {
+                let token_2 = match self.next_token(cursor)? {
                     Some(t) => t,
                     None =>
                         return Err(Error::new(
                             format!(
                                 "Unexpected EOF for function prototype at {}",
-                                stream.get_index()
+                                cursor.get_index()
                             ),
                             ErrorKind::SyntaxError,
                             None
@@ -891,18 +1182,25 @@ impl Parser {
                                 format!(
                                     "Unexpected bracket '{}' at {}",
                                     bracket,
-                                    stream.get_index()
+                                    cursor.get_index()
                                 ),
                                 ErrorKind::SyntaxError,
                                 None
                             ));
                         },
+                    // The parameters of an operator may also be separated by
+                    // whitespace, as in `def binary| 5 (a b)`.
+                    TokenKind::Identifier if kind != PrototypeKind::Function => {
+                        parameters.push(Box::new(IdentifierNode::new(
+                            token_2.borrow_span().to_string()
+                        )));
+                    },
                     TokenKind::Identifier => {
                         return Err(Error::new(
                             format!(
                                 "Identifier '{}' not separated by comma at {}",
                                 token_2,
-                                stream.get_index()
+                                cursor.get_index()
                             ),
                             ErrorKind::SyntaxError,
                             None
@@ -910,7 +1208,7 @@ impl Parser {
                     },
                     _ => {
                         return Err(Error::new(
-                            format!("Unexpected token '{}' at {}", token_2, stream.get_index()),
+                            format!("Unexpected token '{}' at {}", token_2, cursor.get_index()),
                             ErrorKind::SyntaxError,
                             None
                         ));
@@ -920,7 +1218,7 @@ impl Parser {
                 return Err(Error::new(
                     format!(
                         "Unexpected EOF for function prototype at {}",
-                        stream.get_index()
+                        cursor.get_index()
                     ),
                     ErrorKind::SyntaxError,
                     None
@@ -929,25 +1227,152 @@ impl Parser {
         }
         // println!("[{}] Parsed\n", function_path!());
 
-        Ok(Some(Box::new(FunctionPrototypeNode::new(
-            function_identifier,
-            parameters
-        ))))
+        let prototype = match kind {
+            PrototypeKind::Function => FunctionPrototypeNode::new(function_identifier, parameters),
+            _ => {
+                self.register_operator(kind, parameters.len(), &function_identifier_token)?;
+                FunctionPrototypeNode::new_operator(kind, parameters)
+            }
+        };
+        Ok(Some(Box::new(prototype.with_documentation(documentation))))
+    }
+
+    /// Parse the operator (and the precedence of a binary operator) after
+    /// the `unary` or `binary` keyword in the prototype of a user-defined
+    /// operator, such as `def binary@ 5 (a, b)`.
+    ///
+    /// A built-in operator can be redefined in the same position (such as
+    /// `def binary| 5 (a b)`), which replaces its built-in meaning from then
+    /// on, while an operator without a built-in meaning in that position
+    /// (such as `def unary| (x)`) becomes a new operator. Only the
+    /// assignment operator cannot be redefined.
+    fn parse_operator_prototype_kind<S: TokenSource>(
+        &mut self,
+        keyword_token: &Token,
+        cursor: &mut TokenCursor<S>
+    ) -> Result<PrototypeKind> {
+        let operator_token = self.peek_token(cursor)?.ok_or_else(|| {
+            Error::new(
+                format!(
+                    "Expected an operator after '{}' at {}",
+                    keyword_token, keyword_token.start
+                ),
+                ErrorKind::SyntaxError,
+                None
+            )
+        })?;
+        let operator = match operator_token.token_kind {
+            TokenKind::Operator(operator) => operator,
+            _ =>
+                return Err(Error::new(
+                    format!(
+                        "Expected an operator after '{}' at {}",
+                        keyword_token, operator_token.start
+                    ),
+                    ErrorKind::SyntaxError,
+                    None
+                )),
+        };
+        if operator == Operator::Assign {
+            return Err(Error::new(
+                format!(
+                    "Cannot redefine the assignment operator at {}",
+                    operator_token.start
+                ),
+                ErrorKind::SyntaxError,
+                None
+            ));
+        }
+        self.next_token(cursor)?;
+        if let TokenKind::Keyword(Keyword::Unary) = keyword_token.token_kind {
+            return Ok(PrototypeKind::Unary(operator));
+        }
+
+        // Redefining a binary operator keeps its precedence unless a new one
+        // is given.
+        let mut precedence = self
+            .precedence
+            .get_binary_precedence(operator)
+            .unwrap_or(DEFAULT_BINARY_PRECEDENCE);
+        if let Some(token) = self.peek_token(cursor)? {
+            if let TokenKind::Integer = token.token_kind {
+                precedence = match token.borrow_span().parse::<u32>() {
+                    Ok(p) if (1..=100).contains(&p) => p,
+                    _ =>
+                        return Err(Error::new(
+                            format!(
+                                "Invalid precedence '{}' at {}: must be between 1 and 100",
+                                token, token.start
+                            ),
+                            ErrorKind::SyntaxError,
+                            None
+                        )),
+                };
+                self.next_token(cursor)?;
+            }
+        }
+        Ok(PrototypeKind::Binary(operator, precedence))
+    }
+
+    /// Check that a user-defined operator has the right number of
+    /// parameters, then add it to the precedence table so that it can be
+    /// used in the expressions parsed after it. A redefined binary operator
+    /// keeps its associativity.
+    fn register_operator(
+        &mut self,
+        kind: PrototypeKind,
+        parameter_count: usize,
+        keyword_token: &Token
+    ) -> Result<()> {
+        let (expected, operator) = match kind {
+            PrototypeKind::Unary(operator) => (1, operator),
+            PrototypeKind::Binary(operator, _) => (2, operator),
+            PrototypeKind::Function => return Ok(())
+        };
+        if parameter_count != expected {
+            return Err(Error::new(
+                format!(
+                    "Operator '{}' defined at {} must have {} parameter(s), found {}",
+                    operator, keyword_token.start, expected, parameter_count
+                ),
+                ErrorKind::SyntaxError,
+                None
+            ));
+        }
+        match kind {
+            PrototypeKind::Unary(operator) => self.precedence.add_unary_operator(operator),
+            PrototypeKind::Binary(operator, precedence) => {
+                let associativity = self
+                    .precedence
+                    .get_associativity(operator)
+                    .unwrap_or_default();
+                self.precedence.add_binary_operator_with_associativity(
+                    operator,
+                    precedence,
+                    associativity
+                )
+            },
+            PrototypeKind::Function => &mut self.precedence
+        };
+        Ok(())
     }
 
     /// Parse a function definition.
-    pub fn parse_function<'a, 'b: 'a>(
+    pub fn parse_function<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<FunctionNode> {
-        let prototype = ok_none!(self.parse_function_prototype(ltuplemut!(stream, tokenizer))?);
-        let body = match self.parse_expression(ltuplemut!(stream, tokenizer))? {
+        let prototype = ok_none!(self.parse_function_prototype(cursor)?);
+        self.in_function = true;
+        let body = self.parse_expression(cursor);
+        self.in_function = false;
+        let body = match body? {
             Some(expression) => expression,
             None =>
                 return Err(Error::new(
                     format!(
                         "Expected function body for function prototype at {}",
-                        stream.get_index()
+                        cursor.get_index()
                     ),
                     ErrorKind::SyntaxError,
                     None
@@ -956,27 +1381,55 @@ impl Parser {
         Ok(Some(Box::new(FunctionNode::new(prototype, body))))
     }
 
+    /// Parse a global constant or variable, such as `const PI = 3.14159;`
+    /// or `global counter = 0;`. Like an extern function declaration, it
+    /// must end with a `;`. See [`GlobalNode`].
+    pub fn parse_global_declaration<S: TokenSource>(
+        &mut self,
+        cursor: &mut TokenCursor<S>
+    ) -> ParseResult<GlobalNode> {
+        let keyword_token = ok_none!(self.peek_token(cursor)?);
+        let kind = match keyword_token.token_kind {
+            TokenKind::Keyword(Keyword::Const) => GlobalKind::Constant,
+            TokenKind::Keyword(Keyword::Global) => GlobalKind::Variable,
+            _ => return Ok(None)
+        };
+        self.next_token(cursor)?;
+        let context = format!("after '{}'", kind);
+
+        let identifier = self.expect_token(cursor, TokenKind::Identifier, "a name", &context)?;
+        let identifier = Box::new(IdentifierNode::new(identifier.borrow_span().to_string()));
+        self.expect_token(
+            cursor,
+            TokenKind::Operator(Operator::Assign),
+            "'='",
+            "after the name of a global"
+        )?;
+        let value = self.expect_expression(cursor, "for the value of a global")?;
+        self.expect_token(
+            cursor,
+            TokenKind::Semicolon,
+            "';'",
+            "after the value of a global"
+        )?;
+        Ok(Some(Box::new(GlobalNode::new(kind, identifier, value))))
+    }
+
     /// Parse an extern function declaration.
-    pub fn parse_extern_function<'a, 'b: 'a>(
+    pub fn parse_extern_function<S: TokenSource>(
         &mut self,
-        ltuplemut!(stream, tokenizer): LexerTupleMut<'a, 'b>
+        cursor: &mut TokenCursor<S>
     ) -> ParseResult<ExternFunctionNode> {
         // println!("[{}] Entering\n", function_path!());
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        let extern_token = ok_none!(self.peek_current_token());
+        let extern_token = ok_none!(self.peek_token(cursor)?);
         match extern_token.token_kind {
             TokenKind::Keyword(keyword) if matches!(keyword, Keyword::Extern) => (),
-            _ => {
-                if self.current_token.uses > 0 {
-                    self.current_token.uses -= 1;
-                }
-                return Ok(None);
-            }
+            _ => return Ok(None)
         };
-        self.mark_used();
+        self.next_token(cursor)?;
         // println!("[{}] extern keyword found\n", function_path!());
 
-        let prototype = match self.parse_function_prototype(ltuplemut!(stream, tokenizer))? {
+        let prototype = match self.parse_function_prototype(cursor)? {
             Some(p) => p,
             None =>
                 return Err(Error::new(
@@ -990,14 +1443,13 @@ impl Parser {
         };
         // println!("[{}] Parsed\n", function_path!());
         log::trace!("finding semicolon for extern func def");
-        self.grab_if_used(ltuplemut!(stream, tokenizer))?;
-        match self.peek_current_token() {
+        match self.peek_token(cursor)? {
             Some(token) =>
                 if let TokenKind::Semicolon = token.token_kind {
-                    self.mark_used();
+                    self.next_token(cursor)?;
                     Ok(Some(Box::new(ExternFunctionNode::new(prototype))))
                 } else {
-                    self.mark_used();
+                    self.next_token(cursor)?;
                     Err(Error::new(
                         format!(
                             "Extern function prototype not terminated by ';' at {}",
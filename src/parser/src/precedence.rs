@@ -12,13 +12,51 @@
 //! more important operator rather than an adjacent operator of a lower
 //! priority. Such an appointment allows the parser to "understand" the
 //! expression unambiguously to the user's expectations.
+//!
+//! Operators sharing a precedence are grouped by their [`Associativity`].
+//! Both are kept in a [`PrecedenceTable`], which the parser consults and
+//! which can be changed at runtime.
+
+use std::collections::HashMap;
 
 use kaleidoscope_ast::nodes::Operator;
 use kaleidoscope_macro::impl_display;
 
+/// The operators with a built-in meaning as binary operators.
+const BUILTIN_BINARY_OPERATORS: &[Operator] = &[
+    Operator::Plus,
+    Operator::Minus,
+    Operator::Multiply,
+    Operator::Divide,
+    Operator::LessThan,
+    Operator::GreaterThan,
+    Operator::Equals,
+    Operator::LessThanEqual,
+    Operator::GreaterThanEqual,
+    Operator::NotEquals,
+    Operator::Power,
+    Operator::Modulo,
+    Operator::BitwiseAnd,
+    Operator::BitwiseOr,
+    Operator::ShiftLeft,
+    Operator::ShiftRight,
+    Operator::LogicalAnd,
+    Operator::LogicalOr,
+    Operator::Assign
+];
+
+/// The operators with a built-in meaning as unary (prefix) operators.
+const BUILTIN_UNARY_OPERATORS: &[Operator] =
+    &[Operator::Minus, Operator::Not, Operator::BitwiseNot];
+
 /// An enumerator defining the precedence of each operator. Operators are
 /// categorised into a smaller number of actions, such as "Comparison",
-/// "Addition", "Multiplication" and "Exponentiation".
+/// "Addition", "Multiplication" and "Exponentiation". The bitwise
+/// operators bind more tightly than comparisons but more loosely than
+/// addition, so `a & 1 == 0` is `(a & 1) == 0`, while the logical operators
+/// bind more loosely than comparisons, so `a < b && b < c` needs no brackets.
+/// Assignment binds the most loosely of all, so `x = a || b` assigns the
+/// result of `a || b`.
 ///
 /// These actions can be compared with each other by their priority.
 /// For example, multiplication operations (* or /) have a higher precedence
@@ -32,7 +70,13 @@ use kaleidoscope_macro::impl_display;
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BinaryOperatorPrecedence {
     Unknown,
+    Assignment,
+    LogicalOr,
+    LogicalAnd,
     Comparison,
+    BitwiseOr,
+    BitwiseAnd,
+    Shift,
     Addition,
     Multiplication,
     Exponentiation
@@ -45,14 +89,23 @@ impl BinaryOperatorPrecedence {
     pub fn from_operator(operator: Operator) -> Self {
         use BinaryOperatorPrecedence::*;
         match operator {
-            Operator::Unknown => Unknown,
+            Operator::Unknown | Operator::Not | Operator::BitwiseNot | Operator::Custom(_) =>
+                Unknown,
             Operator::GreaterThan
             | Operator::GreaterThanEqual
             | Operator::LessThan
             | Operator::LessThanEqual
-            | Operator::Equals => Comparison,
+            | Operator::Equals
+            | Operator::NotEquals => Comparison,
+            Operator::Assign => Assignment,
+            Operator::LogicalOr => LogicalOr,
+            Operator::LogicalAnd => LogicalAnd,
+            Operator::BitwiseOr => BitwiseOr,
+            Operator::BitwiseAnd => BitwiseAnd,
+            Operator::ShiftLeft | Operator::ShiftRight => Shift,
             Operator::Plus | Operator::Minus => Addition,
-            Operator::Multiply | Operator::Divide => Multiplication
+            Operator::Multiply | Operator::Divide | Operator::Modulo => Multiplication,
+            Operator::Power => Exponentiation
         }
     }
 
@@ -62,6 +115,37 @@ impl BinaryOperatorPrecedence {
         Self::from_operator(Operator::from_string(slice))
     }
 
+    /// Get the numeric precedence used for this category in a
+    /// [`PrecedenceTable`]. The gaps between the categories leave room for
+    /// user-defined operators.
+    pub fn value(&self) -> u32 {
+        use BinaryOperatorPrecedence::*;
+        match self {
+            Unknown => 0,
+            Assignment => 2,
+            LogicalOr => 4,
+            LogicalAnd => 6,
+            Comparison => 10,
+            BitwiseOr => 12,
+            BitwiseAnd => 14,
+            Shift => 16,
+            Addition => 20,
+            Multiplication => 40,
+            Exponentiation => 60
+        }
+    }
+
+    /// Get the associativity of the operators in this category. Only
+    /// exponentiation and assignment are right-associative, so `2 ^ 3 ^ 2`
+    /// is `2 ^ (3 ^ 2)` and `a = b = 1` is `a = (b = 1)`.
+    pub fn get_associativity(&self) -> Associativity {
+        match self {
+            BinaryOperatorPrecedence::Exponentiation | BinaryOperatorPrecedence::Assignment =>
+                Associativity::Right,
+            _ => Associativity::Left
+        }
+    }
+
     /// Get the precedence variant with the lowest priority.
     ///
     /// In this case, it's [`BinaryOperatorPrecedence::Unknown`].
@@ -82,3 +166,172 @@ impl Into<BinaryOperatorPrecedence> for Operator {
         return BinaryOperatorPrecedence::from_operator(self);
     }
 }
+
+/// The way a chain of binary operators with the same precedence is grouped.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Associativity {
+    /// `a - b - c` is parsed as `(a - b) - c`.
+    #[default]
+    Left,
+    /// `a ^ b ^ c` is parsed as `a ^ (b ^ c)`.
+    Right,
+    /// The operator cannot be chained, so `a < b < c` is a syntax error.
+    None
+}
+
+impl_display!(Associativity);
+
+/// A table of the operators the parser knows about. Binary operators are
+/// mapped to a numeric precedence, where operators with a larger precedence
+/// bind more tightly, and an [`Associativity`]. Unary (prefix) operators are
+/// kept in a separate set.
+///
+/// The table starts out with the built-in operators, and can be extended at
+/// runtime, which is how user-defined operators such as
+/// `def binary@ 5 (a, b)` become known to the [`crate::parser::Parser`].
+///
+/// # Example
+///
+/// ```
+/// use kaleidoscope_ast::nodes::Operator;
+/// use kaleidoscope_parser::precedence::{Associativity, PrecedenceTable};
+///
+/// let mut table = PrecedenceTable::new();
+/// assert!(
+///     table.get_binary_precedence(Operator::Plus)
+///         < table.get_binary_precedence(Operator::Multiply)
+/// );
+/// assert_eq!(table.get_binary_precedence(Operator::Custom('$')), None);
+///
+/// table.add_binary_operator(Operator::Custom('$'), 5);
+/// assert_eq!(table.get_binary_precedence(Operator::Custom('$')), Some(5));
+///
+/// table.add_binary_operator_with_associativity(Operator::Custom('@'), 70, Associativity::Right);
+/// assert_eq!(
+///     table.get_associativity(Operator::Custom('@')),
+///     Some(Associativity::Right)
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrecedenceTable {
+    binary: HashMap<Operator, (u32, Associativity)>,
+    unary:  Vec<Operator>
+}
+
+impl PrecedenceTable {
+    /// Create a table containing the built-in operators.
+    pub fn new() -> Self {
+        let binary = BUILTIN_BINARY_OPERATORS
+            .iter()
+            .map(|operator| {
+                let precedence = BinaryOperatorPrecedence::from_operator(*operator);
+                (
+                    *operator,
+                    (precedence.value(), precedence.get_associativity())
+                )
+            })
+            .collect();
+        Self {
+            binary,
+            unary: BUILTIN_UNARY_OPERATORS.to_vec()
+        }
+    }
+
+    /// Create a table without any operators.
+    pub fn empty() -> Self {
+        Self {
+            binary: HashMap::new(),
+            unary:  Vec::new()
+        }
+    }
+
+    /// Get the precedence of a binary operator. [`None`] is returned if
+    /// `operator` is not a binary operator.
+    pub fn get_binary_precedence(&self, operator: Operator) -> Option<u32> {
+        self.binary
+            .get(&operator)
+            .map(|(precedence, _)| *precedence)
+    }
+
+    /// Get the associativity of a binary operator. [`None`] is returned if
+    /// `operator` is not a binary operator.
+    pub fn get_associativity(&self, operator: Operator) -> Option<Associativity> {
+        self.binary
+            .get(&operator)
+            .map(|(_, associativity)| *associativity)
+    }
+
+    /// Check if `operator` is a binary operator.
+    pub fn is_binary_operator(&self, operator: Operator) -> bool {
+        self.binary.contains_key(&operator)
+    }
+
+    /// Check if `operator` is a unary operator.
+    pub fn is_unary_operator(&self, operator: Operator) -> bool {
+        self.unary.contains(&operator)
+    }
+
+    /// Add a left-associative binary operator with a precedence, replacing
+    /// its precedence if it is already in the table.
+    pub fn add_binary_operator(&mut self, operator: Operator, precedence: u32) -> &mut Self {
+        self.add_binary_operator_with_associativity(operator, precedence, Associativity::Left)
+    }
+
+    /// Add a binary operator with a precedence and an associativity,
+    /// replacing both if it is already in the table.
+    pub fn add_binary_operator_with_associativity(
+        &mut self,
+        operator: Operator,
+        precedence: u32,
+        associativity: Associativity
+    ) -> &mut Self {
+        self.binary.insert(operator, (precedence, associativity));
+        self
+    }
+
+    /// Change the associativity of a binary operator already in the table.
+    pub fn set_associativity(
+        &mut self,
+        operator: Operator,
+        associativity: Associativity
+    ) -> &mut Self {
+        if let Some(entry) = self.binary.get_mut(&operator) {
+            entry.1 = associativity;
+        }
+        self
+    }
+
+    /// Iterate over the binary operators in the table, along with their
+    /// precedence and associativity.
+    pub fn binary_operators(&self) -> impl Iterator<Item = (Operator, u32, Associativity)> + '_ {
+        self.binary
+            .iter()
+            .map(|(operator, (precedence, associativity))| (*operator, *precedence, *associativity))
+    }
+
+    /// Add a unary operator.
+    pub fn add_unary_operator(&mut self, operator: Operator) -> &mut Self {
+        if !self.unary.contains(&operator) {
+            self.unary.push(operator);
+        }
+        self
+    }
+
+    /// Remove a binary operator from the table.
+    pub fn remove_binary_operator(&mut self, operator: Operator) -> &mut Self {
+        self.binary.remove(&operator);
+        self
+    }
+
+    /// Remove a unary operator from the table.
+    pub fn remove_unary_operator(&mut self, operator: Operator) -> &mut Self {
+        self.unary.retain(|o| *o != operator);
+        self
+    }
+}
+
+impl Default for PrecedenceTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,118 @@
+use kaleidoscope_ast::nodes::ProgramItem;
+use kaleidoscope_lexer::tokenizer::{FileStream, TokenizerOptions};
+
+use crate::{driver::Interpreter, parse_program};
+
+#[test]
+fn test_batch_parse_program() {
+    let source = "extern def sin(x);\ndef f(x) sin(x) * 2;\nf(3);\n1 + 2";
+    let mut interpreter = Interpreter::batch(FileStream::from(source));
+    let program = interpreter.parse_program().unwrap();
+    assert_eq!(program.len(), 4);
+    let items = program.get_items();
+    assert!(matches!(items[0], ProgramItem::Extern(_)));
+    assert!(matches!(items[1], ProgramItem::Function(_)));
+    assert!(matches!(items[2], ProgramItem::Expression(_)));
+    assert!(matches!(items[3], ProgramItem::Expression(_)));
+}
+
+#[test]
+fn test_batch_parse_many_functions() {
+    let source = (0..100)
+        .map(|i| format!("def f{}(x) x + {};\n", i, i))
+        .collect::<String>();
+    let mut interpreter = Interpreter::batch(FileStream::from(&source[..]));
+    let program = interpreter.parse_program().unwrap();
+    assert_eq!(program.len(), 100);
+}
+
+#[test]
+fn test_batch_parse_program_error() {
+    let mut interpreter = Interpreter::batch(FileStream::from("1 + 2;\n)"));
+    let diagnostics = interpreter.parse_program().unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn test_parse_program_in_order() {
+    let program = parse_program("def f(x) x;\nextern def g(y);\nf(1);\ndef h() 2;").unwrap();
+    assert_eq!(program.len(), 4);
+    let items = program.get_items();
+    assert!(matches!(items[0], ProgramItem::Function(_)));
+    assert!(matches!(items[1], ProgramItem::Extern(_)));
+    assert!(matches!(items[2], ProgramItem::Expression(_)));
+    assert!(matches!(items[3], ProgramItem::Function(_)));
+    let names = program
+        .functions()
+        .map(|f| f.get_prototype().get_identifier().get_value().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["f", "h"]);
+}
+
+#[test]
+fn test_parse_program_diagnostics() {
+    let source = "def f(x x;\n1 + 2;\nextern 3;";
+    let diagnostics = parse_program(source).unwrap_err();
+    assert_eq!(diagnostics.len(), 2);
+    let lines = diagnostics
+        .iter()
+        .map(|d| (d.get_start().get_line(), d.get_end().get_line()))
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![(Some(0), Some(0)), (Some(2), Some(2))]);
+}
+
+#[test]
+fn test_parse_program_diagnostic_positions() {
+    let diagnostics = parse_program("1;\n\ndef f(x) x;\n  2 3;").unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics.get_diagnostics()[0];
+    assert_eq!(diagnostic.get_start().get_line(), Some(3));
+    assert!(diagnostic.get_error().to_string().contains("line: 3"));
+}
+
+#[test]
+fn test_interpreter_tab_width() {
+    let mut interpreter = Interpreter::batch(FileStream::from("1;\n\t2 3;"));
+    interpreter.set_tokenizer_options(TokenizerOptions::new().with_tab_width(4));
+    let diagnostics = interpreter.parse_program().unwrap_err();
+    let diagnostic = &diagnostics.get_diagnostics()[0];
+    assert_eq!(diagnostic.get_start().get_column(), 2);
+    assert_eq!(diagnostic.get_start().get_display_column(), 5);
+    assert_eq!(interpreter.relinquish_istream().get_tab_width(), 4);
+}
+
+#[test]
+fn test_parse_program_recovers_after_block() {
+    let source = "def f(x) { x; 1 +; };\nf(1);\n)\n2;";
+    let diagnostics = parse_program(source).unwrap_err();
+    let lines = diagnostics
+        .iter()
+        .map(|d| d.get_start().get_line())
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![Some(0), Some(2)]);
+}
+
+#[test]
+fn test_parse_empty_program() {
+    let program = parse_program("  # nothing here\n").unwrap();
+    assert!(program.is_empty());
+}
+
+#[test]
+fn test_parse_program_with_globals() {
+    let program = parse_program(
+        "const PI = 3.14159;\nglobal counter = 0;\ndef f(x) counter = counter + x * PI;\nf(1);"
+    )
+    .unwrap();
+    assert_eq!(program.len(), 4);
+    let items = program.get_items();
+    assert!(matches!(items[0], ProgramItem::Global(_)));
+    assert!(matches!(items[1], ProgramItem::Global(_)));
+    assert!(matches!(items[2], ProgramItem::Function(_)));
+    assert!(matches!(items[3], ProgramItem::Expression(_)));
+    let names = program
+        .globals()
+        .map(|g| g.get_identifier().get_value().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["PI", "counter"]);
+}
@@ -0,0 +1,140 @@
+use kaleidoscope_ast::{
+    node::{reify_node_ref, NodeEnum},
+    nodes::FunctionNode
+};
+use kaleidoscope_lexer::tokenizer::TokenizerOptions;
+
+use crate::incremental::{IncrementalParser, TextEdit};
+
+fn function_at(parser: &IncrementalParser, index: usize) -> &FunctionNode {
+    match parser.get_items()[index].get_node().unwrap().unwrap() {
+        NodeEnum::AnyNode(node) => reify_node_ref::<FunctionNode>(node).unwrap(),
+        NodeEnum::ExprNode(_) => panic!("item {} is not a function", index)
+    }
+}
+
+fn expression_at(parser: &IncrementalParser, index: usize) -> String {
+    match parser.get_items()[index].get_node().unwrap().unwrap() {
+        NodeEnum::ExprNode(node) => format!("{}", node),
+        NodeEnum::AnyNode(_) => panic!("item {} is not an expression", index)
+    }
+}
+
+#[test]
+fn test_edit_reuses_untouched_items() {
+    let source = "def f(x) x + 1;\ndef g(x) x * 2;\nextern def sin(x);";
+    let mut parser = IncrementalParser::new(source);
+    assert_eq!(parser.get_items().len(), 3);
+    let f = function_at(&parser, 0) as *const FunctionNode;
+
+    let offset = source.find("2;").unwrap();
+    let reparsed = parser
+        .apply_edit(&TextEdit::new(offset..offset + 1, "y"))
+        .unwrap();
+    assert_eq!(reparsed, 1..2);
+    assert_eq!(function_at(&parser, 0) as *const FunctionNode, f);
+    assert_eq!(format!("{}", function_at(&parser, 1).get_body()), "(x * y)");
+    assert_eq!(parser.get_nodes().count(), 3);
+}
+
+#[test]
+fn test_edit_shifts_later_items() {
+    let source = "def f(x) x;\ndef g(x) x;";
+    let mut parser = IncrementalParser::new(source);
+    let reparsed = parser.apply_edit(&TextEdit::insert(9, " + 100")).unwrap();
+    assert_eq!(reparsed, 0..1);
+    let second = parser.get_items()[1].get_range();
+    assert_eq!(&parser.get_source()[second], "\ndef g(x) x;");
+}
+
+#[test]
+fn test_deleting_semicolon_merges_items() {
+    let source = "1 + 2;\n3 + 4;\n5;";
+    let mut parser = IncrementalParser::new(source);
+    assert_eq!(parser.get_items().len(), 3);
+    let reparsed = parser.apply_edit(&TextEdit::delete(5..6)).unwrap();
+    assert_eq!(reparsed, 0..1);
+    assert_eq!(parser.get_items().len(), 2);
+    assert_eq!(parser.get_errors().count(), 1);
+}
+
+#[test]
+fn test_commenting_out_semicolon_merges_items() {
+    let mut parser = IncrementalParser::new("1;\n2;\n3;");
+    parser.apply_edit(&TextEdit::insert(3, "#")).unwrap();
+    assert_eq!(parser.get_source(), "1;\n#2;\n3;");
+    assert_eq!(parser.get_items().len(), 2);
+    assert_eq!(parser.get_errors().count(), 0);
+}
+
+#[test]
+fn test_append_new_item() {
+    let mut parser = IncrementalParser::new("def f(x) x;");
+    let reparsed = parser.apply_edit(&TextEdit::insert(11, "\nf(2);")).unwrap();
+    assert_eq!(reparsed, 1..2);
+    assert!(parser.get_items()[1]
+        .get_node()
+        .unwrap()
+        .unwrap()
+        .is_expr_node());
+}
+
+#[test]
+fn test_invalid_edit() {
+    let mut parser = IncrementalParser::new("1;");
+    assert!(parser.apply_edit(&TextEdit::delete(1..5)).is_err());
+}
+
+#[test]
+fn test_operator_definitions_carry_over() {
+    let source = "def binary@ 5 (a, b) a;\n1 + 2 @ 3;";
+    let mut parser = IncrementalParser::new(source);
+    assert_eq!(parser.get_errors().count(), 0);
+    assert_eq!(expression_at(&parser, 1), "((1 + 2) @ 3)");
+
+    // Raising the precedence of `@` changes how the second item is parsed,
+    // even though the edit does not touch it.
+    let offset = source.find('5').unwrap();
+    let reparsed = parser
+        .apply_edit(&TextEdit::new(offset..offset + 1, "50"))
+        .unwrap();
+    assert_eq!(reparsed, 0..2);
+    assert_eq!(expression_at(&parser, 1), "(1 + (2 @ 3))");
+
+    // Without the definition, `@` is not an operator.
+    let end = parser.get_source().find(';').unwrap() + 1;
+    parser.apply_edit(&TextEdit::delete(0..end)).unwrap();
+    assert_eq!(parser.get_errors().count(), 1);
+}
+
+#[test]
+fn test_semicolons_in_blocks_do_not_split_items() {
+    let mut parser = IncrementalParser::new("def f(x) { g(x); x };\n{ 1; 2 };");
+    assert_eq!(parser.get_items().len(), 2);
+    assert_eq!(parser.get_errors().count(), 0);
+    assert_eq!(
+        format!("{}", function_at(&parser, 0).get_body()),
+        "{g(x); x}"
+    );
+    assert_eq!(expression_at(&parser, 1), "{1; 2}");
+
+    // Removing the closing bracket leaves the rest of the source in the
+    // block.
+    let offset = parser.get_source().find('}').unwrap();
+    parser
+        .apply_edit(&TextEdit::delete(offset..offset + 1))
+        .unwrap();
+    assert_eq!(parser.get_items().len(), 1);
+    assert_eq!(parser.get_errors().count(), 1);
+}
+
+#[test]
+fn test_tab_width() {
+    let options = TokenizerOptions::new().with_tab_width(2);
+    let parser = IncrementalParser::with_options("\tf(\tx);", options);
+    let tokens = parser.get_items()[0].get_tokens();
+    assert_eq!(tokens[0].span, "f");
+    assert_eq!(tokens[0].start.get_display_column(), 3);
+    assert_eq!(tokens[2].span, "x");
+    assert_eq!(tokens[2].start.get_display_column(), 7);
+}
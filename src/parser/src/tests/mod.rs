@@ -1,4 +1,8 @@
 #[cfg(test)]
+mod driver_tests;
+#[cfg(test)]
+mod incremental_tests;
+#[cfg(test)]
 mod parser_tests;
 #[cfg(test)]
 mod precedence_tests;
@@ -3,19 +3,32 @@ use kaleidoscope_ast::{
     nodes::{
         BinaryOperatorNode,
         FloatNode,
+        GlobalKind,
         IdentifierNode,
         IntegerNode,
         Operator,
+        PrototypeKind,
+        UnaryOperatorNode,
         VariableExpressionNode
     }
 };
 use kaleidoscope_lexer::{
-    ltuplemut,
-    tokenizer::{FileStream, Tokenizer}
+    token::{FileIndex, Token, TokenKind},
+    tokenizer::{
+        FileStream,
+        LexerDeserializer,
+        LexerSerializer,
+        TokenBuffer,
+        TokenCursor,
+        TokenIterator,
+        Tokenizer,
+        TokenizerOptions,
+        DEFAULT_OPERATOR_CHARS
+    }
 };
 use kaleidoscope_macro::function_name;
 
-use crate::parser::Parser;
+use crate::{parser::Parser, precedence::Associativity};
 
 #[allow(dead_code)]
 fn print_tokenizer<'a>(tokenizer: Tokenizer, stream: FileStream<'a>) {
@@ -26,15 +39,18 @@ fn print_tokenizer<'a>(tokenizer: Tokenizer, stream: FileStream<'a>) {
 }
 
 #[inline]
-fn get_parser<'a>(input: &'a str) -> (Parser, FileStream<'a>, Tokenizer) {
-    (Parser::new(), FileStream::from(input), Tokenizer::new())
+fn get_parser<'a>(input: &'a str) -> (Parser, TokenCursor<TokenIterator<'a>>) {
+    (
+        Parser::new(),
+        Tokenizer::new().to_cursor(FileStream::from(input))
+    )
 }
 
 #[test]
 fn test_parse_integer() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("420");
+    let (mut parser, mut cursor) = get_parser("420");
     let expression = parser
-        .parse_integer_expression(ltuplemut!(&mut stream, &mut tokenizer))
+        .parse_integer_expression(&mut cursor)
         .unwrap()
         .unwrap();
     let node = reify_expr_node::<IntegerNode>(expression).unwrap();
@@ -43,20 +59,17 @@ fn test_parse_integer() {
 
 #[test]
 fn test_parse_float() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("3.8");
-    let expression = parser
-        .parse_float_expression(ltuplemut!(&mut stream, &mut tokenizer))
-        .unwrap()
-        .unwrap();
+    let (mut parser, mut cursor) = get_parser("3.8");
+    let expression = parser.parse_float_expression(&mut cursor).unwrap().unwrap();
     let node = reify_expr_node::<FloatNode>(expression).unwrap();
     assert_eq!(node.get_value(), 3.8);
 }
 
 #[test]
 fn test_parse_variable_expression() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("var1");
+    let (mut parser, mut cursor) = get_parser("var1");
     let expression = parser
-        .parse_variable_expression(ltuplemut!(&mut stream, &mut tokenizer))
+        .parse_variable_expression(&mut cursor)
         .unwrap()
         .unwrap();
     let node = reify_expr_node::<VariableExpressionNode>(expression).unwrap();
@@ -65,9 +78,9 @@ fn test_parse_variable_expression() {
 
 #[test]
 fn test_round_bracket() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("(5.0)");
+    let (mut parser, mut cursor) = get_parser("(5.0)");
     let expression = parser
-        .parse_round_bracket_expression(ltuplemut!(&mut stream, &mut tokenizer))
+        .parse_round_bracket_expression(&mut cursor)
         .unwrap()
         .unwrap();
     let node = reify_expr_node::<FloatNode>(expression).unwrap();
@@ -76,11 +89,8 @@ fn test_round_bracket() {
 
 #[test]
 fn test_binop_0() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("1 + 2");
-    let expression = parser
-        .parse_expression(ltuplemut!(&mut stream, &mut tokenizer))
-        .unwrap()
-        .unwrap();
+    let (mut parser, mut cursor) = get_parser("1 + 2");
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
     println!("{}: {}", function_name!(), expression);
 
     let node = reify_expr_node::<BinaryOperatorNode>(expression).unwrap();
@@ -93,11 +103,8 @@ fn test_binop_0() {
 
 #[test]
 fn test_binop_1() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("1 + 2 * 3");
-    let expression = parser
-        .parse_expression(ltuplemut!(&mut stream, &mut tokenizer))
-        .unwrap()
-        .unwrap();
+    let (mut parser, mut cursor) = get_parser("1 + 2 * 3");
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
     println!("{}: {}", function_name!(), expression);
 
     let node = reify_expr_node::<BinaryOperatorNode>(expression).unwrap();
@@ -114,11 +121,8 @@ fn test_binop_1() {
 
 #[test]
 fn test_binop_2() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("1 + 2 * 3 / 4");
-    let expression = parser
-        .parse_expression(ltuplemut!(&mut stream, &mut tokenizer))
-        .unwrap()
-        .unwrap();
+    let (mut parser, mut cursor) = get_parser("1 + 2 * 3 / 4");
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
     println!("{}: {}", function_name!(), expression);
 
     let node = reify_expr_node::<BinaryOperatorNode>(expression).unwrap();
@@ -141,11 +145,8 @@ fn test_binop_2() {
 
 #[test]
 fn test_binop_3() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("1 + 2 * 3 / 4 - 5");
-    let expression = parser
-        .parse_expression(ltuplemut!(&mut stream, &mut tokenizer))
-        .unwrap()
-        .unwrap();
+    let (mut parser, mut cursor) = get_parser("1 + 2 * 3 / 4 - 5");
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
     println!("{}: {}", function_name!(), expression);
 
     let node = reify_expr_node::<BinaryOperatorNode>(expression).unwrap();
@@ -174,11 +175,8 @@ fn test_binop_3() {
 
 #[test]
 fn test_binop_4() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("1 + 2 * 3 / (4 - 5)");
-    let expression = parser
-        .parse_expression(ltuplemut!(&mut stream, &mut tokenizer))
-        .unwrap()
-        .unwrap();
+    let (mut parser, mut cursor) = get_parser("1 + 2 * 3 / (4 - 5)");
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
     println!("{}: {}", function_name!(), expression);
     // Doing the checks like in the previous binop tests are going to become
     // too bulky
@@ -187,22 +185,85 @@ fn test_binop_4() {
 
 #[test]
 fn test_binop_5() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("(1 + 2) * 3 / (4 - 5)");
-    let expression = parser
-        .parse_expression(ltuplemut!(&mut stream, &mut tokenizer))
-        .unwrap()
-        .unwrap();
+    let (mut parser, mut cursor) = get_parser("(1 + 2) * 3 / (4 - 5)");
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
     println!("{}: {}", function_name!(), expression);
     assert_eq!(format!("{}", expression), "(((1 + 2) * 3) / (4 - 5))");
 }
 
+#[test]
+fn test_unary_negation() {
+    let (mut parser, mut cursor) = get_parser("-5");
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+    let node = reify_expr_node::<UnaryOperatorNode>(expression).unwrap();
+    assert_eq!(*node.get_operator(), Operator::Minus);
+    let operand = reify_expr_node_ref::<IntegerNode>(node.get_first()).unwrap();
+    assert_eq!(operand.get_value(), 5);
+}
+
+#[test]
+fn test_unary_operators() {
+    for (input, output) in [
+        ("0 - -x", "(0 - (-x))"),
+        ("1+-2", "(1 + (-2))"),
+        ("-x * y", "((-x) * y)"),
+        ("a * -b + c", "((a * (-b)) + c)"),
+        ("!a < b", "((!a) < b)"),
+        ("!(a < b)", "(!(a < b))"),
+        ("- -2.5", "(-(-2.5))"),
+        ("!-f(x)", "(!(-f(x)))")
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+        assert_eq!(format!("{}", expression), output);
+    }
+}
+
+#[test]
+fn test_arithmetic_and_bitwise_operators() {
+    for (input, output) in [
+        ("a % b * c", "((a % b) * c)"),
+        ("a != b + 1", "(a != (b + 1))"),
+        ("a & 1 == 0", "((a & 1) == 0)"),
+        ("a | b & c", "(a | (b & c))"),
+        ("1 << n + 1", "(1 << (n + 1))"),
+        ("a >> 2 | b << 2", "((a >> 2) | (b << 2))"),
+        ("~a & b", "((~a) & b)"),
+        ("a<<-b", "(a << (-b))")
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+        assert_eq!(format!("{}", expression), output);
+    }
+}
+
+#[test]
+fn test_logical_operators() {
+    for (input, output) in [
+        ("a < b && b < c", "((a < b) && (b < c))"),
+        ("a || b && c", "(a || (b && c))"),
+        ("a and b or c", "((a && b) || c)"),
+        ("!a or a == 1 & b", "((!a) || (a == (1 & b)))"),
+        ("f(x) and (g(x) or y)", "(f(x) && (g(x) || y))")
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+        assert_eq!(format!("{}", expression), output);
+    }
+}
+
+#[test]
+fn test_unary_operator_errors() {
+    for input in ["-", "1 + !", "1 ! 2"] {
+        let (mut parser, mut cursor) = get_parser(input);
+        assert!(parser.parse_expression(&mut cursor).is_err());
+    }
+}
+
 #[test]
 fn test_function_call() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("randint(x, 2 + max(24, a - time()))");
-    let expression = parser
-        .parse_expression(ltuplemut!(&mut stream, &mut tokenizer))
-        .unwrap()
-        .unwrap();
+    let (mut parser, mut cursor) = get_parser("randint(x, 2 + max(24, a - time()))");
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
     println!("{}: {}", function_name!(), expression);
     assert_eq!(
         format!("{}", expression),
@@ -210,11 +271,23 @@ fn test_function_call() {
     );
 }
 
+#[test]
+fn test_function_call_lookahead() {
+    let (mut parser, mut cursor) = get_parser("x + f(1)");
+    assert!(parser
+        .parse_function_call_expression(&mut cursor)
+        .unwrap()
+        .is_none());
+    assert_eq!(cursor.get_position(), 0);
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+    assert_eq!(format!("{}", expression), "(x + f(1))");
+}
+
 #[test]
 fn test_function_prototype() {
-    let (mut parser, mut stream, mut tokenizer) = get_parser("def pow(a, b)");
+    let (mut parser, mut cursor) = get_parser("def pow(a, b)");
     let prototype = parser
-        .parse_function_prototype(ltuplemut!(&mut stream, &mut tokenizer))
+        .parse_function_prototype(&mut cursor)
         .unwrap()
         .unwrap();
     assert_eq!(prototype.get_identifier().get_value(), "pow");
@@ -229,12 +302,9 @@ fn test_function_prototype() {
 
 #[test]
 fn test_extern_function() {
-    let (mut parser, mut stream, mut tokenizer) =
+    let (mut parser, mut cursor) =
         get_parser("extern def iconv(cd, inbuf, inbytesleft, outbuf, outbytesleft);");
-    let external = parser
-        .parse_extern_function(ltuplemut!(&mut stream, &mut tokenizer))
-        .unwrap()
-        .unwrap();
+    let external = parser.parse_extern_function(&mut cursor).unwrap().unwrap();
     let prototype = external.get_prototype();
     assert_eq!(prototype.get_identifier().get_value(), "iconv");
     assert_eq!(
@@ -245,3 +315,466 @@ fn test_extern_function() {
             .collect::<Vec<Box<IdentifierNode>>>()
     );
 }
+
+#[test]
+fn test_parse_token_buffer() {
+    let index = FileIndex::new(Some(0), 0);
+    let tokens = vec![
+        Token::new(TokenKind::Identifier, "x".to_string(), index, index),
+        Token::new(
+            TokenKind::Operator(Operator::Multiply),
+            "*".to_string(),
+            index,
+            index
+        ),
+        Token::new(TokenKind::Integer, "2".to_string(), index, index),
+    ];
+    let mut parser = Parser::new();
+    let expression = parser
+        .parse_expression(&mut TokenCursor::new(TokenBuffer::from(tokens)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(format!("{}", expression), "(x * 2)");
+}
+
+#[test]
+fn test_parse_deserialized_tokens() {
+    let source = "extern def atan2(y, x);";
+    let serializer = LexerSerializer::new(Tokenizer::new().to_iter(FileStream::from(source)));
+    let json = serde_json::to_string(&serializer).unwrap();
+    let deserializer: LexerDeserializer = serde_json::from_str(&json).unwrap();
+    let mut parser = Parser::new();
+    let external = parser
+        .parse_extern_function(&mut TokenCursor::new(deserializer.into_buffer()))
+        .unwrap()
+        .unwrap();
+    let prototype = external.get_prototype();
+    assert_eq!(prototype.get_identifier().get_value(), "atan2");
+    assert_eq!(prototype.get_parameters().len(), 2);
+}
+
+#[test]
+fn test_doc_comments() {
+    let (mut parser, mut cursor) = get_parser(
+        "## The sine of x.\n## x is in radians.\nextern def sin(x);\n\n## Not documentation.\n1 + 2;\ndef f(x) x"
+    );
+    let external = parser.parse_extern_function(&mut cursor).unwrap().unwrap();
+    assert_eq!(
+        external.get_documentation(),
+        Some("The sine of x.\nx is in radians.")
+    );
+    parser.parse_expression(&mut cursor).unwrap().unwrap();
+    parser.next_token(&mut cursor).unwrap();
+    let function = parser.parse_function(&mut cursor).unwrap().unwrap();
+    assert_eq!(function.get_documentation(), None);
+
+    let (mut parser, mut cursor) =
+        get_parser("## The sine of x.\r\n## x is in radians.\r\nextern def sin(x);\r\n");
+    let external = parser.parse_extern_function(&mut cursor).unwrap().unwrap();
+    assert_eq!(
+        external.get_documentation(),
+        Some("The sine of x.\nx is in radians.")
+    );
+}
+
+#[test]
+fn test_user_defined_binary_operator() {
+    let (mut parser, mut cursor) = get_parser("def binary@ 5 (a, b) a + b;\n1 + 2 @ 3 * 4;\n");
+    let function = parser.parse_function(&mut cursor).unwrap().unwrap();
+    let prototype = function.get_prototype();
+    assert_eq!(prototype.get_identifier().get_value(), "binary@");
+    assert_eq!(
+        prototype.get_kind(),
+        PrototypeKind::Binary(Operator::Custom('@'), 5)
+    );
+    assert_eq!(
+        parser
+            .get_precedence_table()
+            .get_binary_precedence(Operator::Custom('@')),
+        Some(5)
+    );
+    parser.next_token(&mut cursor).unwrap();
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+    assert_eq!(format!("{}", expression), "((1 + 2) @ (3 * 4))");
+}
+
+#[test]
+fn test_user_defined_unary_operator() {
+    let (mut parser, mut cursor) = get_parser("def unary$ (x) 0 - x;\n$a * b;\n");
+    let function = parser.parse_function(&mut cursor).unwrap().unwrap();
+    assert!(function.get_prototype().is_unary_operator());
+    assert_eq!(
+        function.get_prototype().get_identifier().get_value(),
+        "unary$"
+    );
+    parser.next_token(&mut cursor).unwrap();
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+    assert_eq!(format!("{}", expression), "(($a) * b)");
+}
+
+#[test]
+fn test_user_defined_operator_errors() {
+    for input in [
+        "def binary@ (a b, c) a",
+        "def f(a b) a",
+        "def binary@ (a) a",
+        "def unary$ (a, b) a",
+        "def binary@ 500 (a, b) a",
+        "def binary x (a, b) a"
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        assert!(parser.parse_function(&mut cursor).is_err(), "{}", input);
+    }
+    let (mut parser, mut cursor) = get_parser("1 @ 2");
+    assert!(parser.parse_expression(&mut cursor).is_err());
+
+    let (mut parser, mut cursor) = get_parser("def binary= 5 (a, b) a");
+    let error = parser.parse_function(&mut cursor).unwrap_err();
+    assert!(
+        format!("{}", error).contains("Cannot redefine the assignment operator"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_redefined_builtin_operators() {
+    let (mut parser, mut cursor) = get_parser(
+        "def binary| 5 (a b) a;\ndef unary! (x) x;\n!a | b * c;\ndef binary^ (a b) a;\n"
+    );
+    let function = parser.parse_function(&mut cursor).unwrap().unwrap();
+    let prototype = function.get_prototype();
+    assert_eq!(prototype.get_identifier().get_value(), "binary|");
+    assert_eq!(
+        prototype.get_kind(),
+        PrototypeKind::Binary(Operator::BitwiseOr, 5)
+    );
+    assert_eq!(prototype.get_parameters().len(), 2);
+    parser.next_token(&mut cursor).unwrap();
+    let function = parser.parse_function(&mut cursor).unwrap().unwrap();
+    assert_eq!(
+        function.get_prototype().get_kind(),
+        PrototypeKind::Unary(Operator::Not)
+    );
+    parser.next_token(&mut cursor).unwrap();
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+    assert_eq!(format!("{}", expression), "((!a) | (b * c))");
+
+    // Without a new precedence, a redefined operator keeps its own.
+    parser.next_token(&mut cursor).unwrap();
+    let table = parser.get_precedence_table().clone();
+    parser.parse_function(&mut cursor).unwrap().unwrap();
+    let precedence = parser.get_precedence_table();
+    assert_eq!(
+        precedence.get_binary_precedence(Operator::Power),
+        table.get_binary_precedence(Operator::Power)
+    );
+    assert_eq!(
+        precedence.get_associativity(Operator::Power),
+        Some(Associativity::Right)
+    );
+}
+
+#[test]
+fn test_operator_in_a_new_position() {
+    // `|` has no built-in unary meaning, so it becomes a new unary operator.
+    let (mut parser, mut cursor) = get_parser("def unary| (x) x;\n|a | b;\n");
+    assert!(!parser
+        .get_precedence_table()
+        .is_unary_operator(Operator::BitwiseOr));
+    parser.parse_function(&mut cursor).unwrap().unwrap();
+    assert!(parser
+        .get_precedence_table()
+        .is_unary_operator(Operator::BitwiseOr));
+    parser.next_token(&mut cursor).unwrap();
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+    assert_eq!(format!("{}", expression), "((|a) | b)");
+}
+
+#[test]
+fn test_user_defined_operator_with_extra_operator_char() {
+    let mut operator_chars = DEFAULT_OPERATOR_CHARS.to_vec();
+    operator_chars.push('£');
+    let mut cursor = TokenizerOptions::new()
+        .with_operator_chars(&operator_chars)
+        .build()
+        .to_cursor(FileStream::from("def binary£ 5 (a, b) a;\na £ b;\n"));
+    let mut parser = Parser::new();
+    let function = parser.parse_function(&mut cursor).unwrap().unwrap();
+    assert_eq!(
+        function.get_prototype().get_kind(),
+        PrototypeKind::Binary(Operator::Custom('£'), 5)
+    );
+    parser.next_token(&mut cursor).unwrap();
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+    assert_eq!(format!("{}", expression), "(a £ b)");
+}
+
+#[test]
+fn test_custom_operator_tokens_round_trip() {
+    let source = "a @ $b";
+    let serializer = LexerSerializer::new(Tokenizer::new().to_iter(FileStream::from(source)));
+    let json = serde_json::to_string(&serializer).unwrap();
+    let deserializer: LexerDeserializer = serde_json::from_str(&json).unwrap();
+    let kinds = deserializer
+        .get_tokens()
+        .iter()
+        .map(|token| token.token_kind)
+        .collect::<Vec<_>>();
+    assert_eq!(kinds[1], TokenKind::Operator(Operator::Custom('@')));
+    assert_eq!(kinds[2], TokenKind::Operator(Operator::Custom('$')));
+}
+
+#[test]
+fn test_for_loop() {
+    for (input, output) in [
+        (
+            "for i = 1, i < n, 2 in f(i)",
+            "(for i = 1, (i < n), 2 in f(i))"
+        ),
+        (
+            "for i = 0, i < n in f(i) + 1",
+            "(for i = 0, (i < n) in (f(i) + 1))"
+        ),
+        (
+            "for i = 0, i < 10 in for j = i, j < 10 in g(i, j)",
+            "(for i = 0, (i < 10) in (for j = i, (j < 10) in g(i, j)))"
+        ),
+        ("1 + for x = a, x in x", "(1 + (for x = a, x in x))")
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+        assert_eq!(format!("{}", expression), output);
+    }
+}
+
+#[test]
+fn test_for_loop_errors() {
+    for input in [
+        "for 1 = 0, 1 in x",
+        "for i 0, i < n in x",
+        "for i = 0 in x",
+        "for i = 0, i < n x",
+        "for i = 0, i < n, 1 in",
+        "for i = 0, i < n, in x"
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        assert!(parser.parse_expression(&mut cursor).is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn test_while_loop() {
+    for (input, output) in [
+        ("while x < 10 do f(x)", "(while (x < 10) do f(x))"),
+        ("while 1 do break", "(while 1 do break)"),
+        (
+            "while a do for i = 0, i < a in (i == 2 && continue) || f(i)",
+            "(while a do (for i = 0, (i < a) in (((i == 2) && continue) || f(i))))"
+        ),
+        (
+            "while a do (while b do break) + continue",
+            "(while a do ((while b do break) + continue))"
+        )
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+        assert_eq!(format!("{}", expression), output);
+    }
+}
+
+#[test]
+fn test_loop_control_outside_loop() {
+    for input in [
+        "break",
+        "1 + continue",
+        "while break do 1",
+        "for i = break, i < 10 in i",
+        "while x 1",
+        "while do 1"
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        assert!(parser.parse_expression(&mut cursor).is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn test_var_expression() {
+    for (input, output) in [
+        ("var x = 1, y in x + y", "(var x = 1, y in (x + y))"),
+        ("var x in x = x + 1", "(var x in (x = (x + 1)))"),
+        ("var a = b = 2 in a", "(var a = (b = 2) in a)"),
+        ("a = b = c || d", "(a = (b = (c || d)))"),
+        (
+            "var i = 0 in while i < n do i = i + 1",
+            "(var i = 0 in (while (i < n) do (i = (i + 1))))"
+        )
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+        assert_eq!(format!("{}", expression), output);
+    }
+}
+
+#[test]
+fn test_var_expression_errors() {
+    for input in [
+        "var in x",
+        "var x = in x",
+        "var x y in x",
+        "var x, in x",
+        "var x = 1",
+        "var 1 in x"
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        assert!(parser.parse_expression(&mut cursor).is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn test_block_expression() {
+    for (input, output) in [
+        ("{ f(x); g(x); x }", "{f(x); g(x); x}"),
+        ("{ f(x); }", "{f(x)}"),
+        ("{}", "{}"),
+        ("{ a; { b; c } } + 1", "({a; {b; c}} + 1)"),
+        (
+            "while i < n do { f(i); i = i + 1 }",
+            "(while (i < n) do {f(i); (i = (i + 1))})"
+        )
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+        assert_eq!(format!("{}", expression), output);
+    }
+}
+
+#[test]
+fn test_block_expression_errors() {
+    for input in ["{ a b }", "{ a; b", "{ ; }", "{ a )"] {
+        let (mut parser, mut cursor) = get_parser(input);
+        assert!(parser.parse_expression(&mut cursor).is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn test_function_with_block_body() {
+    let (mut parser, mut cursor) = get_parser("def f(x) { putchard(x); x + 1 };\nf(2);");
+    let function = parser.parse_function(&mut cursor).unwrap().unwrap();
+    assert_eq!(format!("{}", function.get_body()), "{putchard(x); (x + 1)}");
+    parser.next_token(&mut cursor).unwrap();
+    let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+    assert_eq!(format!("{}", expression), "f(2)");
+}
+
+#[test]
+fn test_let_expression() {
+    for (input, output) in [
+        ("let x = f(1) in x * x", "(let x = f(1) in (x * x))"),
+        (
+            "let x = 1 in let y = x + 1 in x + y",
+            "(let x = 1 in (let y = (x + 1) in (x + y)))"
+        ),
+        ("let x = x in { g(x); x }", "(let x = x in {g(x); x})"),
+        ("1 + let x = 2 in x", "(1 + (let x = 2 in x))")
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        let expression = parser.parse_expression(&mut cursor).unwrap().unwrap();
+        assert_eq!(format!("{}", expression), output);
+    }
+}
+
+#[test]
+fn test_let_expression_errors() {
+    for input in [
+        "let in x",
+        "let x in x",
+        "let x = 1",
+        "let x = 1, y = 2 in x",
+        "let 1 = x in x"
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        assert!(parser.parse_expression(&mut cursor).is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn test_global_declaration() {
+    for (input, kind, output) in [
+        (
+            "const PI = 3.14159;",
+            GlobalKind::Constant,
+            "const PI = 3.14159"
+        ),
+        (
+            "global counter = 0;",
+            GlobalKind::Variable,
+            "global counter = 0"
+        ),
+        (
+            "const DOWN = -1;",
+            GlobalKind::Constant,
+            "const DOWN = (-1)"
+        )
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        let global = parser
+            .parse_global_declaration(&mut cursor)
+            .unwrap()
+            .unwrap();
+        assert_eq!(global.get_kind(), kind);
+        assert_eq!(format!("{}", global), output);
+    }
+}
+
+#[test]
+fn test_global_declaration_errors() {
+    for input in [
+        "const = 1;",
+        "const x 1;",
+        "global x = ;",
+        "global x = 1",
+        "const 2 = 1;"
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        assert!(
+            parser.parse_global_declaration(&mut cursor).is_err(),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_return_expression() {
+    for (input, output) in [
+        ("def f(x) return x + 1", "(return (x + 1))"),
+        (
+            "def f(x) { x < 0 && return 0; x }",
+            "{((x < 0) && (return 0)); x}"
+        ),
+        (
+            "def f(n) for i = 0, i < n in g(i) == 0 && return i",
+            "(for i = 0, (i < n) in ((g(i) == 0) && (return i)))"
+        )
+    ] {
+        let (mut parser, mut cursor) = get_parser(input);
+        let function = parser.parse_function(&mut cursor).unwrap().unwrap();
+        assert_eq!(format!("{}", function.get_body()), output);
+    }
+}
+
+#[test]
+fn test_return_outside_function() {
+    for input in ["return 1", "1 + return 2", "while 1 do return 0"] {
+        let (mut parser, mut cursor) = get_parser(input);
+        assert!(parser.parse_expression(&mut cursor).is_err(), "{}", input);
+    }
+    let (mut parser, mut cursor) = get_parser("def f(x) return;");
+    assert!(parser.parse_function(&mut cursor).is_err());
+    // The parser is no longer inside a function after the body.
+    let (mut parser, mut cursor) = get_parser("def f(x) x;\nreturn 1;");
+    parser.parse_function(&mut cursor).unwrap().unwrap();
+    parser.next_token(&mut cursor).unwrap();
+    assert!(parser.parse_expression(&mut cursor).is_err());
+}
@@ -1,4 +1,16 @@
-use crate::precedence::*;
+use kaleidoscope_ast::nodes::Operator;
+use kaleidoscope_lexer::tokenizer::{FileStream, Tokenizer};
+
+use crate::{parser::Parser, precedence::*};
+
+fn parse_with(table: &PrecedenceTable, input: &str) -> Result<String, String> {
+    let mut parser = Parser::with_precedence_table(table.clone());
+    let mut cursor = Tokenizer::new().to_cursor(FileStream::from(input));
+    match parser.parse_expression(&mut cursor) {
+        Ok(expression) => Ok(format!("{}", expression.unwrap())),
+        Err(error) => Err(format!("{}", error))
+    }
+}
 
 
 #[test]
@@ -9,3 +21,76 @@ fn test_precedence() {
     assert!(bad < plus);
     assert!(plus < multiply);
 }
+
+#[test]
+fn test_precedence_table() {
+    let mut table = PrecedenceTable::new();
+    let less = table.get_binary_precedence(Operator::LessThan).unwrap();
+    let plus = table.get_binary_precedence(Operator::Plus).unwrap();
+    let multiply = table.get_binary_precedence(Operator::Multiply).unwrap();
+    assert!(less < plus && plus < multiply);
+    assert!(table.is_unary_operator(Operator::Minus));
+    assert!(!table.is_binary_operator(Operator::Not));
+
+    table
+        .add_binary_operator(Operator::Custom('@'), 50)
+        .add_unary_operator(Operator::Custom('?'));
+    assert_eq!(table.get_binary_precedence(Operator::Custom('@')), Some(50));
+    assert!(table.is_unary_operator(Operator::Custom('?')));
+    table.remove_unary_operator(Operator::Custom('?'));
+    assert!(!table.is_unary_operator(Operator::Custom('?')));
+    assert!(PrecedenceTable::empty()
+        .get_binary_precedence(Operator::Plus)
+        .is_none());
+}
+
+#[test]
+fn test_precedence_climbing() {
+    let table = PrecedenceTable::new();
+    assert_eq!(
+        parse_with(&table, "a < b * c + d").unwrap(),
+        "(a < ((b * c) + d))"
+    );
+    assert_eq!(
+        parse_with(&table, "1 - 2 * 3 - 4").unwrap(),
+        "((1 - (2 * 3)) - 4)"
+    );
+}
+
+#[test]
+fn test_associativity() {
+    let mut table = PrecedenceTable::new();
+    table.add_binary_operator_with_associativity(Operator::Custom('@'), 50, Associativity::Right);
+    assert_eq!(
+        table.get_associativity(Operator::Plus),
+        Some(Associativity::Left)
+    );
+    assert_eq!(
+        parse_with(&table, "a @ b @ c + d").unwrap(),
+        "((a @ (b @ c)) + d)"
+    );
+    assert_eq!(
+        parse_with(&table, "a + b @ c @ d * e").unwrap(),
+        "(a + ((b @ (c @ d)) * e))"
+    );
+
+    table.set_associativity(Operator::LessThan, Associativity::None);
+    assert!(parse_with(&table, "a < b < c").is_err());
+    assert!(parse_with(&table, "a < b + c < d").is_err());
+    assert_eq!(parse_with(&table, "(a < b) < c").unwrap(), "((a < b) < c)");
+}
+
+#[test]
+fn test_power_is_right_associative() {
+    let table = PrecedenceTable::new();
+    assert_eq!(
+        table.get_associativity(Operator::Power),
+        Some(Associativity::Right)
+    );
+    assert_eq!(parse_with(&table, "2 ^ 3 ^ 2").unwrap(), "(2 ^ (3 ^ 2))");
+    assert_eq!(
+        parse_with(&table, "a * b ^ c ^ d - e").unwrap(),
+        "((a * (b ^ (c ^ d))) - e)"
+    );
+    assert_eq!(parse_with(&table, "2 ^ 3 * 4").unwrap(), "((2 ^ 3) * 4)");
+}